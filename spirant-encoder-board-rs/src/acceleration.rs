@@ -0,0 +1,242 @@
+//! Velocity-sensitive encoder acceleration.
+//!
+//! Scales raw encoder deltas by how fast the knob is turning so that large
+//! parameter ranges can be swept quickly while slow movements keep
+//! single-tick precision. Everything here is a pure function of deltas and
+//! [`Instant`] timestamps — no I2C, no timers — so it is testable on the
+//! host.
+
+use embassy_time::{Duration, Instant};
+
+/// Mapping from turning speed to delta multiplier.
+///
+/// Speed is measured as the time per tick since the previous movement of
+/// the same encoder. Ticks slower than `slow_interval` are passed through
+/// unchanged; ticks at or faster than `fast_interval` are multiplied by
+/// `max_multiplier`; speeds in between are interpolated linearly.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct AccelerationCurve {
+    /// Time per tick at or above which no acceleration is applied.
+    pub slow_interval: Duration,
+    /// Time per tick at or below which `max_multiplier` is applied.
+    pub fast_interval: Duration,
+    /// Multiplier at full speed (1 disables acceleration).
+    pub max_multiplier: u32,
+}
+
+impl AccelerationCurve {
+    /// Curve that never accelerates — every tick counts once.
+    pub const NONE: Self = Self {
+        slow_interval: Duration::from_millis(0),
+        fast_interval: Duration::from_millis(0),
+        max_multiplier: 1,
+    };
+
+    /// Multiplier for a movement of `ticks` ticks (absolute) spread over
+    /// `elapsed`.
+    pub fn multiplier(&self, ticks: u32, elapsed: Duration) -> u32 {
+        if self.max_multiplier <= 1 || ticks == 0 {
+            return 1;
+        }
+
+        let per_tick = elapsed.as_micros() / ticks as u64;
+        let slow = self.slow_interval.as_micros();
+        let fast = self.fast_interval.as_micros().min(slow);
+
+        if per_tick >= slow {
+            1
+        } else if per_tick <= fast {
+            self.max_multiplier
+        } else {
+            // Linear interpolation: 1 at `slow`, max at `fast`.
+            let extra = (self.max_multiplier - 1) as u64 * (slow - per_tick) / (slow - fast);
+            1 + extra as u32
+        }
+    }
+
+    /// Scale `delta` for a movement that took `elapsed` since the previous
+    /// one. The result saturates at the `i32` range.
+    pub fn apply(&self, delta: i32, elapsed: Duration) -> i32 {
+        let m = self.multiplier(delta.unsigned_abs(), elapsed);
+        delta.saturating_mul(i32::try_from(m).unwrap_or(i32::MAX))
+    }
+}
+
+impl Default for AccelerationCurve {
+    /// Moderate acceleration: none slower than 40 ms per tick, ×16 at
+    /// 4 ms per tick or faster.
+    fn default() -> Self {
+        Self {
+            slow_interval: Duration::from_millis(40),
+            fast_interval: Duration::from_millis(4),
+            max_multiplier: 16,
+        }
+    }
+}
+
+/// Per-encoder acceleration state for a bank of `N` encoders.
+///
+/// Remembers when each encoder last moved and in which direction. A
+/// change of direction, or the first movement, is never accelerated so
+/// that fine corrections land exactly.
+///
+/// Each encoder has its own [`AccelerationCurve`]; the firmware can swap
+/// curves with [`set_curve()`](Self::set_curve) when the page changes to
+/// give individual parameters their own feel.
+///
+/// # Example
+///
+/// ```
+/// use embassy_time::Instant;
+/// use encoder_driver::{AccelerationCurve, EncoderAccelerator};
+///
+/// let mut accel = EncoderAccelerator::<4>::new(AccelerationCurve::default());
+/// let t0 = Instant::from_millis(1000);
+/// assert_eq!(accel.apply(0, 1, t0), 1);
+/// // Two ticks 4 ms later: fast enough for the full ×16.
+/// assert_eq!(accel.apply(0, 2, Instant::from_millis(1004)), 32);
+/// ```
+pub struct EncoderAccelerator<const N: usize> {
+    curves: [AccelerationCurve; N],
+    last_move: [Option<(Instant, bool)>; N],
+}
+
+impl<const N: usize> EncoderAccelerator<N> {
+    /// Create an accelerator using `curve` for every encoder.
+    pub fn new(curve: AccelerationCurve) -> Self {
+        Self {
+            curves: [curve; N],
+            last_move: [None; N],
+        }
+    }
+
+    /// Replace the curve for one encoder. Out-of-range indices are ignored.
+    pub fn set_curve(&mut self, encoder: usize, curve: AccelerationCurve) {
+        if let Some(c) = self.curves.get_mut(encoder) {
+            *c = curve;
+        }
+    }
+
+    /// Returns the curve currently used by `encoder`.
+    pub fn curve(&self, encoder: usize) -> Option<&AccelerationCurve> {
+        self.curves.get(encoder)
+    }
+
+    /// Forget all movement history (e.g. after a page change).
+    pub fn reset(&mut self) {
+        self.last_move = [None; N];
+    }
+
+    /// Scale one encoder's raw `delta` observed at `now`.
+    ///
+    /// Zero deltas and out-of-range indices are returned unchanged and do
+    /// not update the history.
+    pub fn apply(&mut self, encoder: usize, delta: i32, now: Instant) -> i32 {
+        if delta == 0 || encoder >= N {
+            return delta;
+        }
+
+        let positive = delta > 0;
+        let scaled = match self.last_move[encoder] {
+            Some((last, last_positive)) if last_positive == positive && now >= last => {
+                self.curves[encoder].apply(delta, now - last)
+            }
+            // First movement or direction reversal: no acceleration.
+            _ => delta,
+        };
+
+        self.last_move[encoder] = Some((now, positive));
+        scaled
+    }
+
+    /// Scale the deltas of all encoders observed at the same instant.
+    pub fn apply_all(&mut self, deltas: [i32; N], now: Instant) -> [i32; N] {
+        core::array::from_fn(|i| self.apply(i, deltas[i], now))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ms(v: u64) -> Instant {
+        Instant::from_millis(v)
+    }
+
+    #[test]
+    fn slow_turns_are_not_accelerated() {
+        let curve = AccelerationCurve::default();
+        assert_eq!(curve.apply(1, Duration::from_millis(40)), 1);
+        assert_eq!(curve.apply(-3, Duration::from_millis(500)), -3);
+    }
+
+    #[test]
+    fn fast_turns_reach_max_multiplier() {
+        let curve = AccelerationCurve::default();
+        assert_eq!(curve.apply(1, Duration::from_millis(4)), 16);
+        assert_eq!(curve.apply(-2, Duration::from_millis(2)), -32);
+    }
+
+    #[test]
+    fn intermediate_speed_interpolates() {
+        let curve = AccelerationCurve::default();
+        // 22 ms per tick is halfway between 40 ms and 4 ms: 1 + 15 / 2.
+        assert_eq!(curve.multiplier(1, Duration::from_millis(22)), 8);
+        // The same speed expressed as 2 ticks in 44 ms.
+        assert_eq!(curve.multiplier(2, Duration::from_millis(44)), 8);
+    }
+
+    #[test]
+    fn huge_multiplier_saturates() {
+        let curve = AccelerationCurve {
+            max_multiplier: u32::MAX,
+            ..AccelerationCurve::default()
+        };
+        assert_eq!(curve.apply(1, Duration::from_millis(1)), i32::MAX);
+        assert_eq!(curve.apply(-1, Duration::from_millis(1)), -i32::MAX);
+    }
+
+    #[test]
+    fn none_curve_is_identity() {
+        let curve = AccelerationCurve::NONE;
+        assert_eq!(curve.apply(5, Duration::from_millis(0)), 5);
+    }
+
+    #[test]
+    fn first_movement_and_reversal_are_not_accelerated() {
+        let mut accel = EncoderAccelerator::<2>::new(AccelerationCurve::default());
+        assert_eq!(accel.apply(0, 1, ms(100)), 1);
+        assert_eq!(accel.apply(0, 1, ms(102)), 16);
+        // Direction reversal 2 ms later: unscaled.
+        assert_eq!(accel.apply(0, -1, ms(104)), -1);
+        assert_eq!(accel.apply(0, -1, ms(106)), -16);
+    }
+
+    #[test]
+    fn encoders_have_independent_history_and_curves() {
+        let mut accel = EncoderAccelerator::<2>::new(AccelerationCurve::default());
+        accel.set_curve(1, AccelerationCurve::NONE);
+
+        accel.apply(0, 1, ms(0));
+        accel.apply(1, 1, ms(0));
+        assert_eq!(accel.apply_all([1, 1], ms(2)), [16, 1]);
+        assert_eq!(accel.curve(1), Some(&AccelerationCurve::NONE));
+        assert_eq!(accel.curve(2), None);
+    }
+
+    #[test]
+    fn reset_forgets_history() {
+        let mut accel = EncoderAccelerator::<1>::new(AccelerationCurve::default());
+        accel.apply(0, 1, ms(0));
+        accel.reset();
+        assert_eq!(accel.apply(0, 1, ms(1)), 1);
+    }
+
+    #[test]
+    fn zero_and_out_of_range_pass_through() {
+        let mut accel = EncoderAccelerator::<1>::new(AccelerationCurve::default());
+        assert_eq!(accel.apply(0, 0, ms(0)), 0);
+        assert_eq!(accel.apply(5, 3, ms(0)), 3);
+    }
+}
@@ -0,0 +1,321 @@
+//! Several quad encoder boards on one bus, addressed as one bank.
+//!
+//! [`EncoderBank`] numbers the encoders of `BOARDS` boards consecutively
+//! (board 0 holds encoders 0–3, board 1 holds 4–7, …), reads the movement
+//! of every board when the shared INT line fires, and maps each encoder to
+//! a parameter slot.
+
+use core::array;
+
+use embedded_hal_async::delay::DelayNs;
+use embedded_hal_async::i2c::I2c;
+
+use crate::encoder_board::QuadEncoderBoard;
+use crate::error::EncoderError;
+use crate::monitor::{EncoderMonitor, MonitorMode};
+use crate::registers::ENCODER_COUNT;
+
+/// Movement of one encoder reported by [`EncoderBank::poll`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct BankTurn {
+    /// Bank-wide encoder index.
+    pub encoder: usize,
+    /// Parameter slot the encoder is mapped to, if any.
+    pub slot: Option<usize>,
+    /// Ticks moved since the previous poll (positive = clockwise).
+    pub delta: i32,
+}
+
+/// `BOARDS` quad encoder boards sharing one INT line.
+///
+/// Each board is a [`QuadEncoderBoard`] at its own Seesaw address, usually
+/// built on a shared-bus device such as `embassy_embedded_hal`'s
+/// `I2cDevice`. Encoders are addressed by bank-wide index
+/// `board * 4 + encoder`, and by default encoder `i` controls parameter
+/// slot `i`.
+///
+/// Encoder movement does not show in the Seesaw INTFLAG register, so INT
+/// alone cannot tell which board moved: [`poll`](Self::poll) reads the
+/// deltas of every board, which also releases their encoder interrupts.
+/// INTFLAG only reports push-switch edges; service those through
+/// [`boards_mut`](Self::boards_mut).
+///
+/// # Example
+///
+/// ```no_run
+/// use encoder_driver::{EncoderBank, QuadEncoderBoard};
+///
+/// let mut bank = EncoderBank::new([
+///     QuadEncoderBoard::new(bus_a, 0x49),
+///     QuadEncoderBoard::new(bus_b, 0x4A),
+/// ]);
+///
+/// // After INT goes LOW:
+/// bank.poll(|turn| {
+///     if let Some(slot) = turn.slot {
+///         params.update_from_encoder(slot, turn.delta);
+///     }
+/// })
+/// .await?;
+/// ```
+pub struct EncoderBank<I2C, D, const BOARDS: usize> {
+    boards: [QuadEncoderBoard<I2C, D>; BOARDS],
+    /// One delta monitor per board, so a failed read loses no ticks.
+    monitors: [EncoderMonitor; BOARDS],
+    /// Parameter slot of each encoder, indexed by board then encoder.
+    slots: [[Option<usize>; ENCODER_COUNT]; BOARDS],
+}
+
+impl<I2C, D, const BOARDS: usize> EncoderBank<I2C, D, BOARDS>
+where
+    I2C: I2c,
+    D: DelayNs,
+{
+    /// Number of encoders in the bank.
+    pub const ENCODERS: usize = BOARDS * ENCODER_COUNT;
+
+    /// Group `boards` into a bank with encoder `i` mapped to slot `i`.
+    pub fn new(boards: [QuadEncoderBoard<I2C, D>; BOARDS]) -> Self {
+        const { assert!(BOARDS <= 32, "poll reports boards in a u32 bitmask") };
+        Self {
+            boards,
+            monitors: array::from_fn(|_| EncoderMonitor::new(MonitorMode::Delta)),
+            slots: array::from_fn(|board| {
+                array::from_fn(|encoder| Some(board * ENCODER_COUNT + encoder))
+            }),
+        }
+    }
+
+    /// Split a bank-wide encoder index into `(board, encoder)`.
+    pub fn locate(encoder: usize) -> Option<(usize, u8)> {
+        (encoder < Self::ENCODERS)
+            .then_some((encoder / ENCODER_COUNT, (encoder % ENCODER_COUNT) as u8))
+    }
+
+    /// Returns one of the boards.
+    pub fn board_mut(&mut self, board: usize) -> Option<&mut QuadEncoderBoard<I2C, D>> {
+        self.boards.get_mut(board)
+    }
+
+    /// Returns all boards, e.g. to configure switches or pixels on each.
+    pub fn boards_mut(&mut self) -> &mut [QuadEncoderBoard<I2C, D>; BOARDS] {
+        &mut self.boards
+    }
+
+    /// Give back the boards.
+    pub fn release(self) -> [QuadEncoderBoard<I2C, D>; BOARDS] {
+        self.boards
+    }
+
+    // -----------------------------------------------------------------------
+    // Slot mapping
+    // -----------------------------------------------------------------------
+
+    /// Parameter slot controlled by a bank-wide encoder index.
+    pub fn slot(&self, encoder: usize) -> Option<usize> {
+        let (board, encoder) = Self::locate(encoder)?;
+        self.slots[board][encoder as usize]
+    }
+
+    /// Map an encoder to a parameter slot, or unmap it with `None`.
+    ///
+    /// # Errors
+    /// * [`EncoderError::InvalidEncoder`] if `encoder` is not in the bank
+    pub fn set_slot(
+        &mut self,
+        encoder: usize,
+        slot: Option<usize>,
+    ) -> Result<(), EncoderError<I2C::Error>> {
+        let (board, encoder) = Self::locate(encoder).ok_or(EncoderError::InvalidEncoder)?;
+        self.slots[board][encoder as usize] = slot;
+        Ok(())
+    }
+
+    // -----------------------------------------------------------------------
+    // Interrupts
+    // -----------------------------------------------------------------------
+
+    /// Enable interrupts for exactly the encoders mapped to a slot whose
+    /// bit is set in `slot_mask` and disable the rest.
+    ///
+    /// Pass `ParameterValues::active_encoder_mask()` so that only encoders
+    /// with a parameter on the active page wake the firmware. As with
+    /// [`QuadEncoderBoard::set_interrupt_mask`], only changed encoders are
+    /// written.
+    ///
+    /// # Errors
+    /// Returns the first I2C error; boards before the failing one keep
+    /// their new mask.
+    pub async fn set_slot_interrupt_mask(
+        &mut self,
+        slot_mask: u32,
+    ) -> Result<(), EncoderError<I2C::Error>> {
+        for (board, slots) in self.boards.iter_mut().zip(&self.slots) {
+            let mut mask = 0u8;
+            for (encoder, slot) in slots.iter().enumerate() {
+                if slot.is_some_and(|s| s < 32 && slot_mask & (1 << s) != 0) {
+                    mask |= 1 << encoder;
+                }
+            }
+            board.set_interrupt_mask(mask).await?;
+        }
+        Ok(())
+    }
+
+    /// Enable interrupts for every encoder on every board.
+    pub async fn enable_all_interrupts(
+        &mut self,
+    ) -> Result<(), EncoderError<I2C::Error>> {
+        for board in &mut self.boards {
+            board.enable_all_interrupts().await?;
+        }
+        Ok(())
+    }
+
+    // -----------------------------------------------------------------------
+    // Reading
+    // -----------------------------------------------------------------------
+
+    /// Read the deltas of every board and report their movement.
+    ///
+    /// `on_turn` is called once per encoder that moved.
+    ///
+    /// # Returns
+    /// A bitmask of the boards that reported movement (bit `b` = board
+    /// `b`).
+    ///
+    /// # Errors
+    /// A failing board does not stop the others from being read; the
+    /// first error is returned after all boards were tried. Deltas already
+    /// read from a failing board are reported on its next successful poll.
+    pub async fn poll(
+        &mut self,
+        mut on_turn: impl FnMut(BankTurn),
+    ) -> Result<u32, EncoderError<I2C::Error>> {
+        let mut moved = 0u32;
+        let mut first_error = None;
+
+        for (b, board) in self.boards.iter_mut().enumerate() {
+            let deltas = match self.monitors[b].poll(board).await {
+                Ok(deltas) => deltas,
+                Err(e) => {
+                    first_error.get_or_insert(e);
+                    continue;
+                }
+            };
+
+            for (encoder, &delta) in deltas.iter().enumerate() {
+                if delta != 0 {
+                    moved |= 1 << b;
+                    on_turn(BankTurn {
+                        encoder: b * ENCODER_COUNT + encoder,
+                        slot: self.slots[b][encoder],
+                        delta,
+                    });
+                }
+            }
+        }
+
+        match first_error {
+            Some(e) => Err(e),
+            None => Ok(moved),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use std::vec::Vec;
+
+    use super::*;
+    use crate::sim::{block_on, SimDelay, SimI2c, SimulatedBoard};
+
+    fn bank() -> ([SimulatedBoard; 2], EncoderBank<SimI2c, SimDelay, 2>) {
+        let sims = [SimulatedBoard::new(), SimulatedBoard::new()];
+        let bank = EncoderBank::new([sims[0].board(), sims[1].board()]);
+        (sims, bank)
+    }
+
+    fn turn(encoder: usize, slot: Option<usize>, delta: i32) -> BankTurn {
+        BankTurn {
+            encoder,
+            slot,
+            delta,
+        }
+    }
+
+    fn poll(bank: &mut EncoderBank<SimI2c, SimDelay, 2>) -> (u32, Vec<BankTurn>) {
+        let mut turns = Vec::new();
+        let serviced = block_on(bank.poll(|t| turns.push(t))).unwrap();
+        (serviced, turns)
+    }
+
+    #[test]
+    fn global_indices_span_boards() {
+        assert_eq!(EncoderBank::<SimI2c, SimDelay, 2>::ENCODERS, 8);
+        assert_eq!(EncoderBank::<SimI2c, SimDelay, 2>::locate(5), Some((1, 1)));
+        assert_eq!(EncoderBank::<SimI2c, SimDelay, 2>::locate(8), None);
+    }
+
+    #[test]
+    fn movement_is_found_without_intflag() {
+        let (sims, mut bank) = bank();
+        block_on(bank.enable_all_interrupts()).unwrap();
+
+        sims[1].turn(2, 3);
+        let (moved, turns) = poll(&mut bank);
+
+        assert_eq!(moved, 0b10);
+        assert_eq!(turns, [turn(6, Some(6), 3)]);
+        // Reading the deltas released INT.
+        assert!(!sims[1].int_asserted());
+        assert_eq!(poll(&mut bank), (0, Vec::new()));
+    }
+
+    #[test]
+    fn slots_can_be_remapped() {
+        let (sims, mut bank) = bank();
+        block_on(bank.enable_all_interrupts()).unwrap();
+        bank.set_slot(4, Some(0)).unwrap();
+        bank.set_slot(0, None).unwrap();
+        assert!(bank.set_slot(8, Some(1)).is_err());
+
+        sims[0].turn(0, 1);
+        sims[1].turn(0, -2);
+        let (_, turns) = poll(&mut bank);
+        assert_eq!(turns, [turn(0, None, 1), turn(4, Some(0), -2)]);
+    }
+
+    #[test]
+    fn slot_mask_enables_mapped_encoders() {
+        let (sims, mut bank) = bank();
+        bank.set_slot(5, Some(1)).unwrap();
+        block_on(bank.set_slot_interrupt_mask(0b10)).unwrap();
+
+        // Slot 1 is driven by encoder 1 and by encoder 5.
+        assert!(sims[0].interrupt_enabled(1));
+        assert!(sims[1].interrupt_enabled(1));
+        assert!(!sims[0].interrupt_enabled(0));
+        assert!(!sims[1].interrupt_enabled(0));
+    }
+
+    #[test]
+    fn failing_board_does_not_block_others() {
+        let (sims, mut bank) = bank();
+        block_on(bank.enable_all_interrupts()).unwrap();
+        sims[0].turn(1, 1);
+        sims[1].turn(3, 1);
+        // Board 0's second delta read fails.
+        sims[0].fail_transactions(2, 1);
+
+        let mut turns = Vec::new();
+        assert!(block_on(bank.poll(|t| turns.push(t))).is_err());
+        assert_eq!(turns, [turn(7, Some(7), 1)]);
+
+        // Board 0's movement is reported once it answers again.
+        assert_eq!(poll(&mut bank), (0b01, std::vec![turn(1, Some(1), 1)]));
+    }
+}
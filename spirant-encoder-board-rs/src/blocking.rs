@@ -0,0 +1,267 @@
+//! Blocking interface for the Adafruit Quad Rotary Encoder Breakout.
+//!
+//! [`QuadEncoderBoard`] here offers the same API as the async
+//! [`crate::QuadEncoderBoard`] over the blocking `embedded-hal` 1.0
+//! [`I2c`] and [`DelayNs`] traits, for host tools and firmware that does
+//! not run an async executor.
+//!
+//! It does not duplicate any register logic: the blocking bus and delay
+//! are wrapped in adapters that implement the async traits and complete
+//! immediately, the async board runs on top of them, and each call is
+//! driven to completion in place. No Embassy timer is involved.
+//!
+//! Enabled by the **`blocking`** feature.
+
+use embassy_futures::block_on;
+use embedded_hal::delay::DelayNs;
+use embedded_hal::i2c::{ErrorType, I2c, Operation};
+
+use crate::error::EncoderError;
+use crate::neopixel::Rgb;
+use crate::registers::ENCODER_COUNT;
+use crate::status::DeviceInfo;
+
+// ---------------------------------------------------------------------------
+// Async adapters
+// ---------------------------------------------------------------------------
+
+/// Blocking bus or delay presented through the async traits.
+///
+/// Every future it returns is ready on its first poll.
+#[derive(Debug)]
+struct Blocking<T>(T);
+
+impl<T: ErrorType> ErrorType for Blocking<T> {
+    type Error = T::Error;
+}
+
+impl<T: I2c> embedded_hal_async::i2c::I2c for Blocking<T> {
+    async fn transaction(
+        &mut self,
+        address: u8,
+        operations: &mut [Operation<'_>],
+    ) -> Result<(), Self::Error> {
+        self.0.transaction(address, operations)
+    }
+}
+
+impl<T: DelayNs> embedded_hal_async::delay::DelayNs for Blocking<T> {
+    async fn delay_ns(&mut self, ns: u32) {
+        self.0.delay_ns(ns);
+    }
+
+    async fn delay_us(&mut self, us: u32) {
+        self.0.delay_us(us);
+    }
+
+    async fn delay_ms(&mut self, ms: u32) {
+        self.0.delay_ms(ms);
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Board
+// ---------------------------------------------------------------------------
+
+/// Forward blocking methods to the async board.
+macro_rules! forward {
+    ($(
+        $(#[$attr:meta])*
+        fn $name:ident(&mut self $(, $arg:ident: $ty:ty)*) -> $ret:ty;
+    )*) => {
+        $(
+            $(#[$attr])*
+            pub fn $name(&mut self $(, $arg: $ty)*) -> Result<$ret, EncoderError<I2C::Error>> {
+                // The adapters never return `Pending`, so this polls once.
+                block_on(self.inner.$name($($arg),*))
+            }
+        )*
+    };
+}
+
+/// Blocking interface for the Adafruit Quad Rotary Encoder Breakout.
+///
+/// Method for method the same as the async
+/// [`QuadEncoderBoard`](crate::QuadEncoderBoard); see there for details.
+///
+/// # Example
+///
+/// ```no_run
+/// use encoder_driver::blocking::QuadEncoderBoard;
+/// use encoder_driver::DEFAULT_ADDRESS;
+///
+/// // `i2c` and `delay` implement the blocking `embedded-hal` 1.0 traits
+/// let mut board = QuadEncoderBoard::new(i2c, delay, DEFAULT_ADDRESS);
+///
+/// board.probe()?;
+/// let positions = board.read_all_positions()?;
+/// ```
+pub struct QuadEncoderBoard<I2C, D> {
+    inner: crate::QuadEncoderBoard<Blocking<I2C>, Blocking<D>>,
+}
+
+impl<I2C, D> QuadEncoderBoard<I2C, D>
+where
+    I2C: I2c,
+    D: DelayNs,
+{
+    /// Create a new blocking encoder board interface.
+    ///
+    /// # Arguments
+    /// * `i2c` — I2C peripheral (takes ownership for exclusive access)
+    /// * `delay` — Delay provider for the Seesaw read pause
+    /// * `address` — 7-bit I2C device address (usually
+    ///   [`DEFAULT_ADDRESS`](crate::DEFAULT_ADDRESS), 0x49)
+    pub fn new(i2c: I2C, delay: D, address: u8) -> Self {
+        Self {
+            inner: crate::QuadEncoderBoard::with_delay(Blocking(i2c), Blocking(delay), address),
+        }
+    }
+
+    /// Give back the I2C peripheral and delay provider.
+    pub fn release(self) -> (I2C, D) {
+        let (i2c, delay) = self.inner.release().release();
+        (i2c.0, delay.0)
+    }
+
+    forward! {
+        /// Read the Seesaw chip's hardware ID.
+        fn read_hw_id(&mut self) -> u8;
+        /// Read the raw firmware VERSION register.
+        fn read_version(&mut self) -> u32;
+        /// Read the bitmask of modules compiled into the firmware.
+        fn read_options(&mut self) -> u32;
+        /// Confirm that a quad encoder board answers at the address.
+        fn probe(&mut self) -> DeviceInfo;
+        /// Reset the Seesaw chip and wait for it to restart.
+        fn software_reset(&mut self) -> ();
+        /// Reset the chip and replay the configuration this driver has written.
+        fn reinitialise(&mut self) -> ();
+
+        /// Read the absolute position of a specific encoder.
+        fn read_position(&mut self, encoder: u8) -> i32;
+        /// Read the absolute positions of all four encoders.
+        fn read_all_positions(&mut self) -> [i32; ENCODER_COUNT];
+        /// Read the movement of a specific encoder since its previous delta read.
+        fn read_delta(&mut self, encoder: u8) -> i32;
+        /// Read the deltas of all four encoders in sequence.
+        fn read_all_deltas(&mut self) -> [i32; ENCODER_COUNT];
+        /// Set the absolute position of a specific encoder.
+        fn set_position(&mut self, encoder: u8, value: i32) -> ();
+
+        /// Enable the hardware interrupt for a specific encoder.
+        fn enable_interrupt(&mut self, encoder: u8) -> ();
+        /// Disable the hardware interrupt for a specific encoder.
+        fn disable_interrupt(&mut self, encoder: u8) -> ();
+        /// Enable hardware interrupts for all four encoders.
+        fn enable_all_interrupts(&mut self) -> ();
+        /// Disable hardware interrupts for all four encoders.
+        fn disable_all_interrupts(&mut self) -> ();
+        /// Enable interrupts for exactly the encoders whose bit is set in `mask`.
+        fn set_interrupt_mask(&mut self, mask: u8) -> ();
+        /// Clear all pending interrupt flags and reset the INT pin.
+        fn clear_interrupt_flags(&mut self) -> ();
+        /// Read and clear the INTFLAG register.
+        fn read_interrupt_flags(&mut self) -> u32;
+
+        /// Configure the four push-switch pins as inputs with pull-ups.
+        fn configure_switches(&mut self) -> ();
+        /// Read all four push switches (bit `i` set = encoder `i` pressed).
+        fn read_switches(&mut self) -> u8;
+        /// Returns `true` if the push switch of a specific encoder is pressed.
+        fn read_switch(&mut self, encoder: u8) -> bool;
+        /// Enable pin-change interrupts for all four push switches.
+        fn enable_switch_interrupts(&mut self) -> ();
+        /// Disable pin-change interrupts for all four push switches.
+        fn disable_switch_interrupts(&mut self) -> ();
+
+        /// Configure the Seesaw NeoPixel module for the board's four pixels.
+        fn configure_pixels(&mut self) -> ();
+        /// Send the local frame to the board and latch it onto the pixels.
+        fn show(&mut self) -> ();
+    }
+
+    /// Bitmask of encoders whose interrupt is enabled (bit `i` = encoder `i`).
+    pub fn interrupt_mask(&self) -> u8 {
+        self.inner.interrupt_mask()
+    }
+
+    /// Set the colour of one encoder's pixel in the local frame.
+    pub fn set_pixel(&mut self, encoder: u8, colour: Rgb) -> Result<(), EncoderError<I2C::Error>> {
+        self.inner.set_pixel(encoder, colour)
+    }
+
+    /// Set every pixel in the local frame to `colour`.
+    pub fn set_all_pixels(&mut self, colour: Rgb) {
+        self.inner.set_all_pixels(colour);
+    }
+
+    /// Returns the local frame, indexed by encoder.
+    pub fn pixels(&self) -> &[Rgb; ENCODER_COUNT] {
+        self.inner.pixels()
+    }
+
+    /// Set the global brightness applied on [`show`](Self::show).
+    pub fn set_brightness(&mut self, brightness: u8) {
+        self.inner.set_brightness(brightness);
+    }
+
+    /// Returns the global brightness.
+    pub fn brightness(&self) -> u8 {
+        self.inner.brightness()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sim::SimulatedBoard;
+
+    fn board(sim: &SimulatedBoard) -> QuadEncoderBoard<crate::sim::SimI2c, crate::sim::SimDelay> {
+        QuadEncoderBoard::new(sim.bus(), sim.delay(), sim.address())
+    }
+
+    #[test]
+    fn reads_and_writes_through_shared_logic() {
+        let sim = SimulatedBoard::new();
+        let mut board = board(&sim);
+
+        assert!(board.probe().is_ok());
+        sim.turn(1, 7);
+        assert_eq!(board.read_all_positions().unwrap(), [0, 7, 0, 0]);
+        assert_eq!(board.read_delta(1).unwrap(), 7);
+        board.set_position(3, 12).unwrap();
+        assert_eq!(sim.position(3), 12);
+        assert!(matches!(
+            board.read_position(4),
+            Err(EncoderError::InvalidEncoder)
+        ));
+        // The blocking delay still spaces address and data phases.
+        assert_eq!(sim.timing_violations(), 0);
+    }
+
+    #[test]
+    fn interrupts_and_pixels() {
+        let sim = SimulatedBoard::new();
+        let mut board = board(&sim);
+
+        board.set_interrupt_mask(0b1000).unwrap();
+        assert_eq!(board.interrupt_mask(), 0b1000);
+        sim.turn(3, 1);
+        assert!(sim.int_asserted());
+        assert_eq!(board.read_delta(3).unwrap(), 1);
+        assert!(!sim.int_asserted());
+
+        board.configure_pixels().unwrap();
+        board.set_all_pixels(Rgb::WHITE);
+        board.show().unwrap();
+        assert_eq!(sim.pixel(0), Rgb::WHITE);
+    }
+
+    #[test]
+    fn release_returns_parts() {
+        let sim = SimulatedBoard::new();
+        let (mut i2c, _delay) = board(&sim).release();
+        assert!(I2c::write(&mut i2c, sim.address(), &[0x00, 0x01]).is_ok());
+    }
+}
@@ -0,0 +1,322 @@
+//! Debounced push-switch gesture detection.
+//!
+//! [`ButtonEventDetector`] turns raw sampled switch bits (as returned by
+//! [`QuadEncoderBoard::read_switches`](crate::QuadEncoderBoard::read_switches))
+//! plus timestamps into high-level [`ButtonEvent`]s. Like the acceleration
+//! module it performs no I/O, so it can be unit-tested on the host.
+
+use embassy_time::{Duration, Instant};
+
+/// A gesture recognised on one push switch.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum ButtonEvent {
+    /// The switch went down (after debouncing).
+    Pressed,
+    /// The switch came up (after debouncing).
+    Released,
+    /// The switch has been held for [`ButtonTimings::long_press`] without
+    /// the encoder being turned. Emitted once per hold.
+    LongPress,
+    /// A second press arrived within [`ButtonTimings::double_click`] of the
+    /// previous one. Emitted together with that press's
+    /// [`Pressed`](ButtonEvent::Pressed).
+    DoubleClick,
+    /// The encoder was turned while its switch was held. Emitted once per
+    /// hold, on the first turn; suppresses [`LongPress`](ButtonEvent::LongPress)
+    /// for that hold.
+    HeldWhileTurned,
+}
+
+/// Timing thresholds for [`ButtonEventDetector`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct ButtonTimings {
+    /// A raw level must be stable this long before it is accepted.
+    pub debounce: Duration,
+    /// Hold time before [`ButtonEvent::LongPress`] fires.
+    pub long_press: Duration,
+    /// Maximum press-to-press interval for [`ButtonEvent::DoubleClick`].
+    pub double_click: Duration,
+}
+
+impl Default for ButtonTimings {
+    fn default() -> Self {
+        Self {
+            debounce: Duration::from_millis(10),
+            long_press: Duration::from_millis(600),
+            double_click: Duration::from_millis(300),
+        }
+    }
+}
+
+/// Per-switch tracking state.
+#[derive(Debug, Clone, Copy)]
+struct ButtonState {
+    /// Debounced level (`true` = pressed).
+    stable: bool,
+    /// Most recent raw level and when it was first seen.
+    raw: bool,
+    raw_since: Instant,
+    /// When the current hold started, if pressed.
+    pressed_at: Option<Instant>,
+    /// Start of the previous press, for double-click detection.
+    last_press: Option<Instant>,
+    /// `LongPress` or `HeldWhileTurned` already emitted for this hold.
+    long_fired: bool,
+    turned_fired: bool,
+}
+
+impl ButtonState {
+    const fn new() -> Self {
+        Self {
+            stable: false,
+            raw: false,
+            raw_since: Instant::from_ticks(0),
+            pressed_at: None,
+            last_press: None,
+            long_fired: false,
+            turned_fired: false,
+        }
+    }
+}
+
+/// Debounced gesture detector for `N` push switches (at most 32, one bit
+/// each).
+///
+/// Feed it with [`update()`](Self::update) whenever switch states are
+/// sampled (e.g. after every encoder-board interrupt). Because long
+/// presses and debounce settling happen *between* samples, the caller
+/// should also wake up at [`next_deadline()`](Self::next_deadline) and call
+/// `update()` again with the last sampled bits.
+///
+/// # Example
+///
+/// ```
+/// use embassy_time::Instant;
+/// use encoder_driver::{ButtonEvent, ButtonEventDetector, ButtonTimings};
+///
+/// let mut buttons = ButtonEventDetector::<4>::new(ButtonTimings::default());
+/// let mut events = [None; 4];
+///
+/// // Switch 1 goes down; accepted once stable for the debounce time.
+/// buttons.update(Instant::from_millis(0), 0b0010, 0, |_, _| {});
+/// buttons.update(Instant::from_millis(20), 0b0010, 0, |i, e| events[i] = Some(e));
+/// assert_eq!(events[1], Some(ButtonEvent::Pressed));
+/// ```
+pub struct ButtonEventDetector<const N: usize> {
+    timings: ButtonTimings,
+    buttons: [ButtonState; N],
+}
+
+impl<const N: usize> ButtonEventDetector<N> {
+    /// Create a detector with all switches released.
+    pub fn new(timings: ButtonTimings) -> Self {
+        const { assert!(N <= 32, "switch states are passed as a u32 bitmask") };
+        Self {
+            timings,
+            buttons: [ButtonState::new(); N],
+        }
+    }
+
+    /// Returns the configured timings.
+    pub fn timings(&self) -> &ButtonTimings {
+        &self.timings
+    }
+
+    /// Bitmask of switches currently held (debounced).
+    pub fn held(&self) -> u32 {
+        self.buttons
+            .iter()
+            .enumerate()
+            .filter(|(_, b)| b.stable)
+            .fold(0, |mask, (i, _)| mask | (1 << i))
+    }
+
+    /// Process one sample.
+    ///
+    /// # Arguments
+    /// * `now` — Time the sample was taken
+    /// * `pressed` — Raw switch levels, bit `i` set if switch `i` is down
+    /// * `turned` — Bit `i` set if encoder `i` moved since the last sample
+    /// * `on_event` — Called with `(switch index, event)` for every event,
+    ///   in switch order
+    pub fn update(
+        &mut self,
+        now: Instant,
+        pressed: u32,
+        turned: u32,
+        mut on_event: impl FnMut(usize, ButtonEvent),
+    ) {
+        let timings = self.timings;
+
+        for (i, b) in self.buttons.iter_mut().enumerate() {
+            let raw = pressed & (1 << i) != 0;
+
+            // ── Debounce ────────────────────────────────────────────
+            if raw != b.raw {
+                b.raw = raw;
+                b.raw_since = now;
+            }
+            let settled = now.saturating_duration_since(b.raw_since) >= timings.debounce;
+
+            if settled && b.raw != b.stable {
+                b.stable = b.raw;
+                if b.stable {
+                    on_event(i, ButtonEvent::Pressed);
+                    let double = b
+                        .last_press
+                        .is_some_and(|t| now.saturating_duration_since(t) <= timings.double_click);
+                    if double {
+                        on_event(i, ButtonEvent::DoubleClick);
+                        b.last_press = None;
+                    } else {
+                        b.last_press = Some(now);
+                    }
+                    b.pressed_at = Some(now);
+                    b.long_fired = false;
+                    b.turned_fired = false;
+                } else {
+                    on_event(i, ButtonEvent::Released);
+                    b.pressed_at = None;
+                }
+            }
+
+            // ── Gestures while held ─────────────────────────────────
+            if let Some(since) = b.pressed_at {
+                if turned & (1 << i) != 0 && !b.turned_fired {
+                    b.turned_fired = true;
+                    on_event(i, ButtonEvent::HeldWhileTurned);
+                }
+                if !b.long_fired
+                    && !b.turned_fired
+                    && now.saturating_duration_since(since) >= timings.long_press
+                {
+                    b.long_fired = true;
+                    // A long press is not the first half of a double click.
+                    b.last_press = None;
+                    on_event(i, ButtonEvent::LongPress);
+                }
+            }
+        }
+    }
+
+    /// Earliest time at which calling [`update()`](Self::update) again
+    /// could produce an event without a new sample, or `None` if nothing
+    /// is pending.
+    pub fn next_deadline(&self) -> Option<Instant> {
+        self.buttons
+            .iter()
+            .filter_map(|b| {
+                if b.raw != b.stable {
+                    Some(b.raw_since + self.timings.debounce)
+                } else if !b.long_fired && !b.turned_fired {
+                    b.pressed_at.map(|t| t + self.timings.long_press)
+                } else {
+                    None
+                }
+            })
+            .min()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use std::vec::Vec;
+
+    use super::*;
+
+    fn ms(v: u64) -> Instant {
+        Instant::from_millis(v)
+    }
+
+    /// Feed one sample and collect the events it produced.
+    fn step(
+        det: &mut ButtonEventDetector<2>,
+        t: u64,
+        pressed: u32,
+        turned: u32,
+    ) -> Vec<(usize, ButtonEvent)> {
+        let mut events = Vec::new();
+        det.update(ms(t), pressed, turned, |i, e| events.push((i, e)));
+        events
+    }
+
+    #[test]
+    fn press_and_release_are_debounced() {
+        let mut det = ButtonEventDetector::<2>::new(ButtonTimings::default());
+        assert!(step(&mut det, 0, 0b01, 0).is_empty());
+        // Bounce back up before the debounce time elapses.
+        assert!(step(&mut det, 3, 0b00, 0).is_empty());
+        assert!(step(&mut det, 5, 0b01, 0).is_empty());
+        assert_eq!(step(&mut det, 15, 0b01, 0), [(0, ButtonEvent::Pressed)]);
+        assert_eq!(det.held(), 0b01);
+
+        assert!(step(&mut det, 100, 0b00, 0).is_empty());
+        assert_eq!(step(&mut det, 110, 0b00, 0), [(0, ButtonEvent::Released)]);
+        assert_eq!(det.held(), 0);
+    }
+
+    #[test]
+    fn long_press_fires_once() {
+        let mut det = ButtonEventDetector::<2>::new(ButtonTimings::default());
+        step(&mut det, 0, 0b10, 0);
+        assert_eq!(step(&mut det, 10, 0b10, 0), [(1, ButtonEvent::Pressed)]);
+        assert_eq!(det.next_deadline(), Some(ms(610)));
+        assert!(step(&mut det, 500, 0b10, 0).is_empty());
+        assert_eq!(step(&mut det, 610, 0b10, 0), [(1, ButtonEvent::LongPress)]);
+        assert!(step(&mut det, 2000, 0b10, 0).is_empty());
+        assert_eq!(det.next_deadline(), None);
+    }
+
+    #[test]
+    fn double_click_within_window() {
+        let mut det = ButtonEventDetector::<2>::new(ButtonTimings::default());
+        step(&mut det, 0, 0b01, 0);
+        step(&mut det, 10, 0b01, 0);
+        step(&mut det, 60, 0b00, 0);
+        step(&mut det, 70, 0b00, 0);
+        step(&mut det, 150, 0b01, 0);
+        assert_eq!(
+            step(&mut det, 160, 0b01, 0),
+            [(0, ButtonEvent::Pressed), (0, ButtonEvent::DoubleClick)]
+        );
+    }
+
+    #[test]
+    fn slow_second_press_is_not_double_click() {
+        let mut det = ButtonEventDetector::<2>::new(ButtonTimings::default());
+        step(&mut det, 0, 0b01, 0);
+        step(&mut det, 10, 0b01, 0);
+        step(&mut det, 50, 0b00, 0);
+        step(&mut det, 60, 0b00, 0);
+        step(&mut det, 400, 0b01, 0);
+        assert_eq!(step(&mut det, 410, 0b01, 0), [(0, ButtonEvent::Pressed)]);
+    }
+
+    #[test]
+    fn turning_while_held_suppresses_long_press() {
+        let mut det = ButtonEventDetector::<2>::new(ButtonTimings::default());
+        step(&mut det, 0, 0b01, 0);
+        step(&mut det, 10, 0b01, 0);
+        assert_eq!(step(&mut det, 100, 0b01, 0b01), [(0, ButtonEvent::HeldWhileTurned)]);
+        assert!(step(&mut det, 200, 0b01, 0b01).is_empty());
+        assert!(step(&mut det, 1000, 0b01, 0).is_empty());
+    }
+
+    #[test]
+    fn turning_without_press_is_ignored() {
+        let mut det = ButtonEventDetector::<2>::new(ButtonTimings::default());
+        assert!(step(&mut det, 0, 0, 0b11).is_empty());
+        assert_eq!(det.next_deadline(), None);
+    }
+
+    #[test]
+    fn deadline_covers_debounce_settling() {
+        let mut det = ButtonEventDetector::<2>::new(ButtonTimings::default());
+        step(&mut det, 100, 0b10, 0);
+        assert_eq!(det.next_deadline(), Some(ms(110)));
+    }
+}
@@ -0,0 +1,191 @@
+//! Conversion of raw encoder ticks into detents.
+//!
+//! Depending on the encoder part, one click of the knob can be 1, 2 or 4
+//! ticks of the Seesaw counter, and an encoder mounted the other way round
+//! counts backwards. [`DetentNormaliser`] turns raw deltas into exactly
+//! one step per click, in the right direction, optionally scaled. Like the
+//! accelerator it is a pure function of the deltas, testable on the host.
+
+/// How one encoder's raw ticks map to steps.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct DetentConfig {
+    /// Raw ticks per click (0 is treated as 1).
+    pub ticks_per_detent: u32,
+    /// Count clockwise as negative, for encoders mounted reversed.
+    pub inverted: bool,
+    /// Steps reported per click.
+    pub scale: u32,
+}
+
+impl DetentConfig {
+    /// One tick per click, not inverted, not scaled — raw deltas pass
+    /// through unchanged.
+    pub const IDENTITY: Self = Self {
+        ticks_per_detent: 1,
+        inverted: false,
+        scale: 1,
+    };
+}
+
+impl Default for DetentConfig {
+    fn default() -> Self {
+        Self::IDENTITY
+    }
+}
+
+/// Per-encoder detent normalisation for a bank of `N` encoders.
+///
+/// Ticks that do not add up to a whole click are carried over to the next
+/// delta of the same encoder, so a click spread across two reads still
+/// counts once. Turning back before the click completes cancels the
+/// carried ticks.
+///
+/// # Example
+///
+/// ```
+/// use encoder_driver::{DetentConfig, DetentNormaliser};
+///
+/// let mut detents = DetentNormaliser::<4>::new(DetentConfig {
+///     ticks_per_detent: 4,
+///     ..DetentConfig::IDENTITY
+/// });
+/// assert_eq!(detents.apply(0, 3), 0);
+/// // The fourth tick completes the click.
+/// assert_eq!(detents.apply(0, 1), 1);
+/// ```
+#[derive(Debug, Clone)]
+pub struct DetentNormaliser<const N: usize> {
+    configs: [DetentConfig; N],
+    /// Ticks towards the next click, after inversion.
+    carry: [i32; N],
+}
+
+impl<const N: usize> DetentNormaliser<N> {
+    /// Create a normaliser using `config` for every encoder.
+    pub fn new(config: DetentConfig) -> Self {
+        Self {
+            configs: [config; N],
+            carry: [0; N],
+        }
+    }
+
+    /// Create a normaliser with its own configuration per encoder.
+    pub fn with_configs(configs: [DetentConfig; N]) -> Self {
+        Self {
+            configs,
+            carry: [0; N],
+        }
+    }
+
+    /// Replace the configuration for one encoder and drop its carried
+    /// ticks. Out-of-range indices are ignored.
+    pub fn set_config(&mut self, encoder: usize, config: DetentConfig) {
+        if let Some(c) = self.configs.get_mut(encoder) {
+            *c = config;
+            self.carry[encoder] = 0;
+        }
+    }
+
+    /// Returns the configuration used by `encoder`.
+    pub fn config(&self, encoder: usize) -> Option<&DetentConfig> {
+        self.configs.get(encoder)
+    }
+
+    /// Drop all carried ticks (e.g. after the board was reset).
+    pub fn reset(&mut self) {
+        self.carry = [0; N];
+    }
+
+    /// Convert one encoder's raw `delta` into scaled clicks.
+    ///
+    /// Out-of-range indices are returned unchanged.
+    pub fn apply(&mut self, encoder: usize, delta: i32) -> i32 {
+        let Some(config) = self.configs.get(encoder) else {
+            return delta;
+        };
+
+        let ticks = if config.inverted {
+            delta.saturating_neg()
+        } else {
+            delta
+        };
+        let per_detent = config.ticks_per_detent.clamp(1, i32::MAX as u32) as i32;
+        let total = self.carry[encoder].saturating_add(ticks);
+        self.carry[encoder] = total % per_detent;
+        (total / per_detent).saturating_mul(config.scale.min(i32::MAX as u32) as i32)
+    }
+
+    /// Convert the raw deltas of all encoders.
+    pub fn apply_all(&mut self, deltas: [i32; N]) -> [i32; N] {
+        core::array::from_fn(|i| self.apply(i, deltas[i]))
+    }
+}
+
+impl<const N: usize> Default for DetentNormaliser<N> {
+    fn default() -> Self {
+        Self::new(DetentConfig::IDENTITY)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ticks(per_detent: u32) -> DetentConfig {
+        DetentConfig {
+            ticks_per_detent: per_detent,
+            ..DetentConfig::IDENTITY
+        }
+    }
+
+    #[test]
+    fn identity_passes_deltas_through() {
+        let mut detents = DetentNormaliser::<2>::default();
+        assert_eq!(detents.apply_all([3, -7]), [3, -7]);
+    }
+
+    #[test]
+    fn partial_clicks_carry_over() {
+        let mut detents = DetentNormaliser::<1>::new(ticks(4));
+        assert_eq!(detents.apply(0, 6), 1);
+        assert_eq!(detents.apply(0, 1), 0);
+        assert_eq!(detents.apply(0, 1), 1);
+        assert_eq!(detents.apply(0, -3), 0);
+        assert_eq!(detents.apply(0, -1), -1);
+    }
+
+    #[test]
+    fn turning_back_cancels_carried_ticks() {
+        let mut detents = DetentNormaliser::<1>::new(ticks(2));
+        assert_eq!(detents.apply(0, 1), 0);
+        assert_eq!(detents.apply(0, -1), 0);
+        assert_eq!(detents.apply(0, 1), 0);
+        assert_eq!(detents.apply(0, 1), 1);
+    }
+
+    #[test]
+    fn inversion_and_scale_are_per_encoder() {
+        let mut detents = DetentNormaliser::with_configs([
+            DetentConfig {
+                inverted: true,
+                ..ticks(2)
+            },
+            DetentConfig {
+                scale: 10,
+                ..ticks(4)
+            },
+        ]);
+        assert_eq!(detents.apply_all([4, 8]), [-2, 20]);
+        assert_eq!(detents.apply(2, 5), 5);
+    }
+
+    #[test]
+    fn set_config_drops_carry() {
+        let mut detents = DetentNormaliser::<1>::new(ticks(4));
+        assert_eq!(detents.apply(0, 3), 0);
+        detents.set_config(0, ticks(2));
+        assert_eq!(detents.apply(0, 1), 0);
+        assert_eq!(detents.config(0), Some(&ticks(2)));
+    }
+}
@@ -1,27 +1,44 @@
 //! High-level interface for the Adafruit Quad Rotary Encoder Breakout.
 //!
-//! [`QuadEncoderBoard`] wraps the low-level Seesaw driver with input
-//! validation, encoder-specific register addressing, and a batch-read
-//! convenience method.
+//! [`QuadEncoderBoard`] wraps a [`Seesaw`] device with input
+//! validation, encoder-specific register addressing, a batch-read
+//! convenience method, device identification, and access to the encoders'
+//! push switches and NeoPixels.
 
+#[cfg(feature = "time")]
+use embassy_time::Delay;
+use embedded_hal_async::delay::DelayNs;
 use embedded_hal_async::i2c::I2c;
+use seesaw_driver::registers::{EncoderRegister, GpioRegister, NeopixelRegister, Register};
+use seesaw_driver::Seesaw;
 
-use crate::driver::SeesawDriver;
 use crate::error::EncoderError;
-use crate::registers::{ENCODER_COUNT, ENCODER_INT_SET, ENCODER_POSITION, MODULE_ENCODER, MODULE_GPIO, STATUS_INTFLAG};
+use crate::neopixel::Rgb;
+use crate::registers::{
+    ENCODER_COUNT, NEOPIXEL_BYTES_PER_PIXEL, NEOPIXEL_DATA_PIN, SWITCH_PINS, SWITCH_PIN_MASK,
+};
+use crate::status::DeviceInfo;
+
+/// Size of the NeoPixel frame in bytes.
+const PIXEL_BUF_LEN: usize = ENCODER_COUNT * NEOPIXEL_BYTES_PER_PIXEL;
 
 /// High-level interface for the Adafruit Quad Rotary Encoder Breakout.
 ///
 /// Provides validated, async methods for reading and writing encoder
 /// positions over I2C via the Seesaw protocol.
 ///
+/// The delay provider `D` times the Seesaw read pause. With the `time`
+/// feature it defaults to Embassy's `Delay`; use
+/// [`with_delay`](Self::with_delay) to supply another [`DelayNs`]
+/// implementation.
+///
 /// # Example
 ///
 /// ```no_run
-/// use encoder_driver::QuadEncoderBoard;
+/// use encoder_driver::{QuadEncoderBoard, DEFAULT_ADDRESS};
 ///
 /// // `i2c` is any `embedded-hal-async` I2C implementation
-/// let mut board = QuadEncoderBoard::new(i2c, 0x36);
+/// let mut board = QuadEncoderBoard::new(i2c, DEFAULT_ADDRESS);
 ///
 /// // Read a single encoder
 /// let pos = board.read_position(0).await.unwrap();
@@ -29,25 +46,207 @@ use crate::registers::{ENCODER_COUNT, ENCODER_INT_SET, ENCODER_POSITION, MODULE_
 /// // Read all four encoders at once
 /// let positions = board.read_all_positions().await.unwrap();
 /// ```
-pub struct QuadEncoderBoard<I2C> {
-    driver: SeesawDriver<I2C>,
+pub struct QuadEncoderBoard<
+    I2C,
+    #[cfg(feature = "time")] D = Delay,
+    #[cfg(not(feature = "time"))] D,
+> {
+    seesaw: Seesaw<I2C, D>,
+    /// Local NeoPixel frame, pushed to the board by `show()`.
+    pixels: [Rgb; ENCODER_COUNT],
+    /// Global brightness applied to every pixel on `show()`.
+    brightness: u8,
+    /// Encoders whose interrupt is enabled, as last written by this driver.
+    interrupt_mask: u8,
+    /// Device-side setup written by this driver, replayed by `reinitialise()`.
+    setup: Setup,
 }
 
+/// Which optional board features this driver has configured.
+#[derive(Debug, Clone, Copy, Default)]
+struct Setup {
+    switches: bool,
+    switch_interrupts: bool,
+    pixels: bool,
+}
+
+#[cfg(feature = "time")]
 impl<I2C> QuadEncoderBoard<I2C>
 where
     I2C: I2c,
 {
-    /// Create a new encoder board interface.
+    /// Create a new encoder board interface timed by Embassy's [`Delay`].
     ///
     /// # Arguments
     /// * `i2c` — I2C peripheral (takes ownership for exclusive access)
-    /// * `address` — 7-bit I2C device address (typically 0x36)
+    /// * `address` — 7-bit I2C device address (usually
+    ///   [`DEFAULT_ADDRESS`](crate::DEFAULT_ADDRESS), 0x49)
     pub fn new(i2c: I2C, address: u8) -> Self {
+        Self::with_delay(i2c, Delay, address)
+    }
+}
+
+impl<I2C, D> QuadEncoderBoard<I2C, D>
+where
+    I2C: I2c,
+    D: DelayNs,
+{
+    /// Create a new encoder board interface with a custom delay provider.
+    pub fn with_delay(i2c: I2C, delay: D, address: u8) -> Self {
+        Self::from_seesaw(Seesaw::new(i2c, delay, address))
+    }
+
+    /// Wrap an already configured [`Seesaw`] device.
+    pub fn from_seesaw(seesaw: Seesaw<I2C, D>) -> Self {
         Self {
-            driver: SeesawDriver::new(i2c, address),
+            seesaw,
+            pixels: [Rgb::OFF; ENCODER_COUNT],
+            brightness: u8::MAX,
+            interrupt_mask: 0,
+            setup: Setup::default(),
         }
     }
 
+    /// Returns the underlying Seesaw device, e.g. to change its timings.
+    pub fn seesaw_mut(&mut self) -> &mut Seesaw<I2C, D> {
+        &mut self.seesaw
+    }
+
+    /// Give back the underlying Seesaw device.
+    pub fn release(self) -> Seesaw<I2C, D> {
+        self.seesaw
+    }
+
+    // -----------------------------------------------------------------------
+    // Identification and reset (status module)
+    // -----------------------------------------------------------------------
+
+    /// Read the Seesaw chip's hardware ID (e.g. `0x87` for an ATtiny817).
+    ///
+    /// # Errors
+    /// * [`EncoderError::I2c`] on communication failure
+    pub async fn read_hw_id(
+        &mut self,
+    ) -> Result<u8, EncoderError<I2C::Error>> {
+        Ok(self.seesaw.hw_id().await?)
+    }
+
+    /// Read the raw firmware VERSION register.
+    ///
+    /// The upper 16 bits are the Adafruit product number (5752 for this
+    /// board), the lower 16 bits a build date code. See
+    /// [`DeviceInfo::from_registers`] for decoding.
+    ///
+    /// # Errors
+    /// * [`EncoderError::I2c`] on communication failure
+    pub async fn read_version(
+        &mut self,
+    ) -> Result<u32, EncoderError<I2C::Error>> {
+        Ok(self.seesaw.version().await?)
+    }
+
+    /// Read the bitmask of modules compiled into the firmware
+    /// (bit `n` = module ID `n`).
+    ///
+    /// # Errors
+    /// * [`EncoderError::I2c`] on communication failure
+    pub async fn read_options(
+        &mut self,
+    ) -> Result<u32, EncoderError<I2C::Error>> {
+        Ok(self.seesaw.options().await?)
+    }
+
+    /// Check that the device at this address is a quad encoder board.
+    ///
+    /// Reads HW_ID and VERSION and validates them with
+    /// [`DeviceInfo::validate`]. Call once at startup, before any other
+    /// operation, to catch a wrong address or a different Seesaw breakout.
+    ///
+    /// # Returns
+    /// The decoded [`DeviceInfo`] on success.
+    ///
+    /// # Errors
+    /// * [`EncoderError::WrongDevice`] if another device answers
+    /// * [`EncoderError::UnsupportedFirmware`] if the firmware cannot be
+    ///   identified
+    /// * [`EncoderError::I2c`] on communication failure (including no
+    ///   device at the address)
+    ///
+    /// # Example
+    /// ```no_run
+    /// let info = board.probe().await?;
+    /// ```
+    pub async fn probe(
+        &mut self,
+    ) -> Result<DeviceInfo, EncoderError<I2C::Error>> {
+        let hw_id = self.read_hw_id().await?;
+        let version = self.read_version().await?;
+        let info = DeviceInfo::from_registers(hw_id, version);
+        info.validate()?;
+        Ok(info)
+    }
+
+    /// Reset the Seesaw chip and wait for it to restart.
+    ///
+    /// All device-side configuration is lost: encoder positions return to
+    /// zero, interrupts are disabled and the switch and NeoPixel setup
+    /// must be repeated. The tracked
+    /// [`interrupt_mask`](Self::interrupt_mask) is cleared to match; the
+    /// local pixel frame is kept so [`show`](Self::show) can restore it.
+    ///
+    /// # Errors
+    /// * [`EncoderError::I2c`] on communication failure
+    pub async fn software_reset(
+        &mut self,
+    ) -> Result<(), EncoderError<I2C::Error>> {
+        self.seesaw.software_reset().await?;
+        self.interrupt_mask = 0;
+        Ok(())
+    }
+
+    /// Reset the chip and replay the configuration this driver has written.
+    ///
+    /// Restores the encoder interrupt mask, the switch inputs and their
+    /// interrupts, the NeoPixel setup and the current pixel frame. Use
+    /// after the board was power-cycled or replugged, when all
+    /// device-side state is unknown. Encoder positions restart at zero.
+    ///
+    /// # Errors
+    /// * [`EncoderError::I2c`] on communication failure; call again to
+    ///   retry from the start
+    pub async fn reinitialise(
+        &mut self,
+    ) -> Result<(), EncoderError<I2C::Error>> {
+        let mask = self.interrupt_mask;
+        let setup = self.setup;
+        self.software_reset().await?;
+        // software_reset() cleared the tracked mask; put it back if a
+        // later step fails so the next attempt restores it too.
+        let result = self.replay(mask, setup).await;
+        if result.is_err() {
+            self.interrupt_mask = mask;
+        }
+        result
+    }
+
+    async fn replay(
+        &mut self,
+        mask: u8,
+        setup: Setup,
+    ) -> Result<(), EncoderError<I2C::Error>> {
+        if setup.switches {
+            self.configure_switches().await?;
+        }
+        if setup.switch_interrupts {
+            self.enable_switch_interrupts().await?;
+        }
+        if setup.pixels {
+            self.configure_pixels().await?;
+            self.show().await?;
+        }
+        self.set_interrupt_mask(mask).await
+    }
+
     // -----------------------------------------------------------------------
     // Read operations
     // -----------------------------------------------------------------------
@@ -76,8 +275,8 @@ where
             return Err(EncoderError::InvalidEncoder);
         }
 
-        let register = [MODULE_ENCODER, ENCODER_POSITION | encoder];
-        self.driver.read_i32(&register).await
+        let register = EncoderRegister::Position.channel(encoder);
+        Ok(self.seesaw.read(register).await?)
     }
 
     /// Read all four encoder positions in sequence.
@@ -113,6 +312,54 @@ where
         Ok(positions)
     }
 
+    /// Read the movement of a specific encoder since its previous delta read.
+    ///
+    /// The Seesaw firmware resets the delta accumulator on every read, so
+    /// each tick is reported exactly once regardless of the absolute
+    /// position (which is also unaffected).
+    ///
+    /// # Arguments
+    /// * `encoder` — Encoder index (0–3)
+    ///
+    /// # Errors
+    /// * [`EncoderError::InvalidEncoder`] if `encoder >= 4`
+    /// * [`EncoderError::I2c`] on communication failure
+    ///
+    /// # Example
+    /// ```no_run
+    /// let delta = board.read_delta(0).await?;
+    /// ```
+    pub async fn read_delta(
+        &mut self,
+        encoder: u8,
+    ) -> Result<i32, EncoderError<I2C::Error>> {
+        if encoder >= ENCODER_COUNT as u8 {
+            return Err(EncoderError::InvalidEncoder);
+        }
+
+        let register = EncoderRegister::Delta.channel(encoder);
+        Ok(self.seesaw.read(register).await?)
+    }
+
+    /// Read the deltas of all four encoders in sequence.
+    ///
+    /// # Errors
+    /// Returns the first I2C error encountered. Deltas already read (and
+    /// therefore reset on the device) before the failure are discarded;
+    /// use [`EncoderMonitor`](crate::EncoderMonitor) to carry them over to
+    /// the next read instead.
+    pub async fn read_all_deltas(
+        &mut self,
+    ) -> Result<[i32; 4], EncoderError<I2C::Error>> {
+        let mut deltas = [0i32; 4];
+
+        for encoder in 0..4u8 {
+            deltas[encoder as usize] = self.read_delta(encoder).await?;
+        }
+
+        Ok(deltas)
+    }
+
     // -----------------------------------------------------------------------
     // Write operations
     // -----------------------------------------------------------------------
@@ -145,8 +392,8 @@ where
             return Err(EncoderError::InvalidEncoder);
         }
 
-        let register = [MODULE_ENCODER, ENCODER_POSITION | encoder];
-        self.driver.write_i32(&register, value).await
+        let register = EncoderRegister::Position.channel(encoder);
+        Ok(self.seesaw.write(register, value).await?)
     }
 
     // -----------------------------------------------------------------------
@@ -177,8 +424,35 @@ where
             return Err(EncoderError::InvalidEncoder);
         }
 
-        let register = [MODULE_ENCODER, ENCODER_INT_SET | encoder];
-        self.driver.write_u8(&register, 1).await
+        let register = EncoderRegister::IntSet.channel(encoder);
+        self.seesaw.write(register, 1u8).await?;
+        self.interrupt_mask |= 1 << encoder;
+        Ok(())
+    }
+
+    /// Disable the hardware interrupt for a specific encoder.
+    ///
+    /// The encoder keeps counting, but its movement no longer pulls the
+    /// INT pin LOW.
+    ///
+    /// # Arguments
+    /// * `encoder` — Encoder index (0–3)
+    ///
+    /// # Errors
+    /// * [`EncoderError::InvalidEncoder`] if `encoder >= 4`
+    /// * [`EncoderError::I2c`] on communication failure
+    pub async fn disable_interrupt(
+        &mut self,
+        encoder: u8,
+    ) -> Result<(), EncoderError<I2C::Error>> {
+        if encoder >= ENCODER_COUNT as u8 {
+            return Err(EncoderError::InvalidEncoder);
+        }
+
+        let register = EncoderRegister::IntClr.channel(encoder);
+        self.seesaw.write(register, 1u8).await?;
+        self.interrupt_mask &= !(1 << encoder);
+        Ok(())
     }
 
     /// Enable hardware interrupts for all four encoders.
@@ -201,6 +475,61 @@ where
         Ok(())
     }
 
+    /// Disable hardware interrupts for all four encoders.
+    pub async fn disable_all_interrupts(
+        &mut self,
+    ) -> Result<(), EncoderError<I2C::Error>> {
+        for encoder in 0..4u8 {
+            self.disable_interrupt(encoder).await?;
+        }
+        Ok(())
+    }
+
+    /// Enable interrupts for exactly the encoders whose bit is set in
+    /// `mask` and disable the rest.
+    ///
+    /// Only encoders whose state differs from
+    /// [`interrupt_mask`](Self::interrupt_mask) are written, so calling this
+    /// on every page change costs no bus traffic when nothing changes.
+    /// Bits above bit 3 are ignored.
+    ///
+    /// # Errors
+    /// Returns the first I2C error encountered; encoders updated before
+    /// the failure keep their new state and are reflected in
+    /// [`interrupt_mask`](Self::interrupt_mask).
+    ///
+    /// # Example
+    /// ```no_run
+    /// // Only encoders 0 and 1 are mapped on this page.
+    /// board.set_interrupt_mask(0b0011).await?;
+    /// ```
+    pub async fn set_interrupt_mask(
+        &mut self,
+        mask: u8,
+    ) -> Result<(), EncoderError<I2C::Error>> {
+        for encoder in 0..ENCODER_COUNT as u8 {
+            let bit = 1 << encoder;
+            let wanted = mask & bit != 0;
+            if wanted == (self.interrupt_mask & bit != 0) {
+                continue;
+            }
+            if wanted {
+                self.enable_interrupt(encoder).await?;
+            } else {
+                self.disable_interrupt(encoder).await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Bitmask of encoders whose interrupt is enabled (bit `i` = encoder `i`).
+    ///
+    /// Tracks what this driver has written since construction; interrupts
+    /// are disabled after power-on, so a fresh board starts at 0.
+    pub fn interrupt_mask(&self) -> u8 {
+        self.interrupt_mask
+    }
+
     /// Clear all pending interrupt flags and reset the INT pin.
     ///
     /// The Seesaw STATUS INTFLAG register is read-only and self-clearing:
@@ -216,9 +545,348 @@ where
     pub async fn clear_interrupt_flags(
         &mut self,
     ) -> Result<(), EncoderError<I2C::Error>> {
-        let register = [MODULE_GPIO, STATUS_INTFLAG];
         // Reading the register clears the flags; discard the value.
-        let _ = self.driver.read_i32(&register).await?;
+        self.read_interrupt_flags().await?;
+        Ok(())
+    }
+
+    /// Read and clear the INTFLAG register.
+    ///
+    /// Only GPIO pin-change interrupts (the push switches) are reported.
+    /// Encoder movement holds INT LOW without setting a flag here, until
+    /// the encoder's delta is read; see [`EncoderBank`](crate::EncoderBank).
+    ///
+    /// # Errors
+    /// * [`EncoderError::I2c`] on communication failure
+    pub async fn read_interrupt_flags(
+        &mut self,
+    ) -> Result<u32, EncoderError<I2C::Error>> {
+        Ok(self.seesaw.read(GpioRegister::IntFlag).await?)
+    }
+
+    // -----------------------------------------------------------------------
+    // Push switches (GPIO module)
+    // -----------------------------------------------------------------------
+
+    /// Configure the four push-switch pins as inputs with pull-ups.
+    ///
+    /// The switches short their pin to ground when pressed, so the
+    /// internal pull-up must be enabled before
+    /// [`read_switches`](Self::read_switches) returns meaningful data.
+    /// Call once after power-on.
+    ///
+    /// # Example
+    /// ```no_run
+    /// board.configure_switches().await?;
+    /// let pressed = board.read_switches().await?;
+    /// ```
+    pub async fn configure_switches(
+        &mut self,
+    ) -> Result<(), EncoderError<I2C::Error>> {
+        // Input direction, pull resistor enabled, output latch HIGH = pull-up.
+        self.seesaw.write(GpioRegister::DirClrBulk, SWITCH_PIN_MASK).await?;
+        self.seesaw.write(GpioRegister::PullEnSet, SWITCH_PIN_MASK).await?;
+        self.seesaw.write(GpioRegister::BulkSet, SWITCH_PIN_MASK).await?;
+        self.setup.switches = true;
+        Ok(())
+    }
+
+    /// Read all four push switches in a single bulk GPIO read.
+    ///
+    /// # Returns
+    /// A bitmask where bit `i` is set if encoder `i`'s switch is currently
+    /// pressed (the active-low pin levels are inverted for you).
+    ///
+    /// # Errors
+    /// * [`EncoderError::I2c`] on communication failure
+    pub async fn read_switches(
+        &mut self,
+    ) -> Result<u8, EncoderError<I2C::Error>> {
+        let levels: u32 = self.seesaw.read(GpioRegister::Bulk).await?;
+
+        let mut pressed = 0u8;
+        for (encoder, &pin) in SWITCH_PINS.iter().enumerate() {
+            if levels & (1 << pin) == 0 {
+                pressed |= 1 << encoder;
+            }
+        }
+        Ok(pressed)
+    }
+
+    /// Returns `true` if the push switch of a specific encoder is pressed.
+    ///
+    /// # Arguments
+    /// * `encoder` — Encoder index (0–3)
+    ///
+    /// # Errors
+    /// * [`EncoderError::InvalidEncoder`] if `encoder >= 4`
+    /// * [`EncoderError::I2c`] on communication failure
+    pub async fn read_switch(
+        &mut self,
+        encoder: u8,
+    ) -> Result<bool, EncoderError<I2C::Error>> {
+        if encoder >= ENCODER_COUNT as u8 {
+            return Err(EncoderError::InvalidEncoder);
+        }
+
+        Ok(self.read_switches().await? & (1 << encoder) != 0)
+    }
+
+    /// Enable pin-change interrupts for all four push switches.
+    ///
+    /// The shared INT pin then also pulses LOW on every press and release,
+    /// so a single `wait_for_low()` loop can service both rotation and
+    /// switches. Clear with [`clear_interrupt_flags`](Self::clear_interrupt_flags)
+    /// as for encoder interrupts.
+    pub async fn enable_switch_interrupts(
+        &mut self,
+    ) -> Result<(), EncoderError<I2C::Error>> {
+        self.seesaw.write(GpioRegister::IntEnSet, SWITCH_PIN_MASK).await?;
+        self.setup.switch_interrupts = true;
         Ok(())
     }
+
+    /// Disable pin-change interrupts for all four push switches.
+    pub async fn disable_switch_interrupts(
+        &mut self,
+    ) -> Result<(), EncoderError<I2C::Error>> {
+        self.seesaw.write(GpioRegister::IntEnClr, SWITCH_PIN_MASK).await?;
+        self.setup.switch_interrupts = false;
+        Ok(())
+    }
+
+    // -----------------------------------------------------------------------
+    // NeoPixels
+    // -----------------------------------------------------------------------
+
+    /// Configure the Seesaw NeoPixel module for the board's four pixels.
+    ///
+    /// Selects the data pin, the 800 kHz data rate and the buffer length.
+    /// Call once after power-on, before the first [`show`](Self::show).
+    ///
+    /// # Example
+    /// ```no_run
+    /// board.configure_pixels().await?;
+    /// board.set_pixel(0, Rgb::new(255, 0, 0))?;
+    /// board.show().await?;
+    /// ```
+    pub async fn configure_pixels(
+        &mut self,
+    ) -> Result<(), EncoderError<I2C::Error>> {
+        self.seesaw.write(NeopixelRegister::Pin, NEOPIXEL_DATA_PIN).await?;
+        self.seesaw.write(NeopixelRegister::Speed, 1u8).await?;
+        self.seesaw
+            .write(NeopixelRegister::BufLength, PIXEL_BUF_LEN as u16)
+            .await?;
+        self.setup.pixels = true;
+        Ok(())
+    }
+
+    /// Set the colour of one encoder's pixel in the local frame.
+    ///
+    /// Nothing is sent to the board until [`show`](Self::show) is called,
+    /// so several pixels can be updated in one I2C burst.
+    ///
+    /// # Arguments
+    /// * `encoder` — Encoder index (0–3)
+    /// * `colour` — Colour before brightness scaling
+    ///
+    /// # Errors
+    /// * [`EncoderError::InvalidEncoder`] if `encoder >= 4`
+    pub fn set_pixel(
+        &mut self,
+        encoder: u8,
+        colour: Rgb,
+    ) -> Result<(), EncoderError<I2C::Error>> {
+        let pixel = self
+            .pixels
+            .get_mut(encoder as usize)
+            .ok_or(EncoderError::InvalidEncoder)?;
+        *pixel = colour;
+        Ok(())
+    }
+
+    /// Set every pixel in the local frame to `colour`.
+    pub fn set_all_pixels(&mut self, colour: Rgb) {
+        self.pixels = [colour; ENCODER_COUNT];
+    }
+
+    /// Returns the local frame, indexed by encoder.
+    pub fn pixels(&self) -> &[Rgb; ENCODER_COUNT] {
+        &self.pixels
+    }
+
+    /// Set the global brightness (0 = off, 255 = full) applied on
+    /// [`show`](Self::show).
+    pub fn set_brightness(&mut self, brightness: u8) {
+        self.brightness = brightness;
+    }
+
+    /// Returns the global brightness.
+    pub fn brightness(&self) -> u8 {
+        self.brightness
+    }
+
+    /// Send the local frame to the board and latch it onto the pixels.
+    ///
+    /// The whole frame is written in a single buffer transaction followed
+    /// by the SHOW command.
+    ///
+    /// # Errors
+    /// * [`EncoderError::I2c`] on communication failure
+    pub async fn show(
+        &mut self,
+    ) -> Result<(), EncoderError<I2C::Error>> {
+        // Payload: 16-bit start offset (0), then GRB bytes per pixel.
+        let mut buf = [0u8; 2 + PIXEL_BUF_LEN];
+        for (i, colour) in self.pixels.iter().enumerate() {
+            let start = 2 + i * NEOPIXEL_BYTES_PER_PIXEL;
+            buf[start..start + NEOPIXEL_BYTES_PER_PIXEL]
+                .copy_from_slice(&colour.scale(self.brightness).to_grb());
+        }
+
+        self.seesaw.write_bytes(NeopixelRegister::Buf, &buf).await?;
+        self.seesaw.write_bytes(NeopixelRegister::Show, &[]).await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::*;
+    use crate::sim::{block_on, SimulatedBoard, TransferKind};
+    use crate::QUAD_ENCODER_PRODUCT_ID;
+
+    #[test]
+    fn reads_positions_and_deltas() {
+        let sim = SimulatedBoard::new();
+        let mut board = sim.board();
+
+        sim.turn(0, 4);
+        sim.turn(3, -2);
+        assert_eq!(block_on(board.read_all_positions()).unwrap(), [4, 0, 0, -2]);
+        assert_eq!(block_on(board.read_delta(0)).unwrap(), 4);
+        // Delta resets on read; position does not.
+        assert_eq!(block_on(board.read_delta(0)).unwrap(), 0);
+        assert_eq!(block_on(board.read_position(0)).unwrap(), 4);
+        assert_eq!(sim.timing_violations(), 0);
+    }
+
+    #[test]
+    fn set_position_writes_accumulator() {
+        let sim = SimulatedBoard::new();
+        let mut board = sim.board();
+        block_on(board.set_position(2, -100)).unwrap();
+        assert_eq!(sim.position(2), -100);
+    }
+
+    #[test]
+    fn invalid_encoder_sends_nothing() {
+        let sim = SimulatedBoard::new();
+        let mut board = sim.board();
+        assert!(matches!(
+            block_on(board.read_position(4)),
+            Err(EncoderError::InvalidEncoder)
+        ));
+        assert!(board.set_pixel(4, Rgb::WHITE).is_err());
+        assert!(sim.transfers().is_empty());
+    }
+
+    #[test]
+    fn interrupt_mask_writes_only_changes() {
+        let sim = SimulatedBoard::new();
+        let mut board = sim.board();
+
+        block_on(board.set_interrupt_mask(0b0011)).unwrap();
+        assert_eq!(sim.transfers().len(), 2);
+        sim.clear_transfers();
+        block_on(board.set_interrupt_mask(0b0110)).unwrap();
+        assert_eq!(sim.transfers().len(), 2);
+        assert_eq!(board.interrupt_mask(), 0b0110);
+
+        sim.turn(0, 1);
+        assert!(!sim.int_asserted());
+        sim.turn(2, 1);
+        assert!(sim.int_asserted());
+        assert_eq!(block_on(board.read_delta(2)).unwrap(), 1);
+        assert!(!sim.int_asserted());
+    }
+
+    #[test]
+    fn switches_read_and_interrupt() {
+        let sim = SimulatedBoard::new();
+        let mut board = sim.board();
+        block_on(board.configure_switches()).unwrap();
+        block_on(board.enable_switch_interrupts()).unwrap();
+
+        sim.set_switch(1, true);
+        assert!(sim.int_asserted());
+        assert_eq!(block_on(board.read_switches()).unwrap(), 0b0010);
+        assert!(block_on(board.read_switch(1)).unwrap());
+        assert!(!block_on(board.read_switch(0)).unwrap());
+
+        block_on(board.clear_interrupt_flags()).unwrap();
+        block_on(board.disable_switch_interrupts()).unwrap();
+        sim.set_switch(1, false);
+        assert!(!sim.int_asserted());
+    }
+
+    #[test]
+    fn show_sends_scaled_frame() {
+        let sim = SimulatedBoard::new();
+        let mut board = sim.board();
+        block_on(board.configure_pixels()).unwrap();
+        assert_eq!(sim.pixel_config(), (NEOPIXEL_DATA_PIN, 1));
+
+        board.set_pixel(1, Rgb::new(200, 100, 0)).unwrap();
+        board.set_brightness(128);
+        // Nothing reaches the pixels before show().
+        assert_eq!(sim.pixel(1), Rgb::OFF);
+        block_on(board.show()).unwrap();
+
+        assert_eq!(sim.show_count(), 1);
+        assert_eq!(sim.pixel(1), Rgb::new(200, 100, 0).scale(128));
+        assert_eq!(sim.pixel(0), Rgb::OFF);
+    }
+
+    #[test]
+    fn probe_identifies_board() {
+        let sim = SimulatedBoard::new();
+        let mut board = sim.board();
+        let info = block_on(board.probe()).unwrap();
+        assert_eq!(info.product, QUAD_ENCODER_PRODUCT_ID);
+    }
+
+    #[test]
+    fn software_reset_restores_power_on_state() {
+        let sim = SimulatedBoard::new();
+        let mut board = sim.board();
+        block_on(board.enable_all_interrupts()).unwrap();
+        sim.turn(0, 9);
+
+        let before = sim.now();
+        block_on(board.software_reset()).unwrap();
+        assert!(sim.now() > before);
+        assert_eq!(board.interrupt_mask(), 0);
+        assert!(!sim.interrupt_enabled(0));
+        assert_eq!(sim.position(0), 0);
+        assert!(!sim.int_asserted());
+    }
+
+    #[test]
+    fn show_is_a_buffer_write_then_show_command() {
+        let sim = SimulatedBoard::new();
+        let mut board = sim.board();
+        block_on(board.show()).unwrap();
+
+        let log = sim.transfers();
+        assert_eq!(log.len(), 2);
+        let TransferKind::Write(buf) = &log[0].kind else {
+            panic!("expected a write");
+        };
+        assert_eq!(buf.len(), 2 + 2 + PIXEL_BUF_LEN);
+        assert_eq!(log[1].kind, TransferKind::Write(std::vec![0x0E, 0x05]));
+    }
 }
@@ -0,0 +1,739 @@
+//! Reusable encoder monitoring loop.
+//!
+//! [`encoder_monitor`] is the interrupt-or-poll loop that firmware used to
+//! write by hand: wait for a [`WakeSource`], read the board's delta
+//! registers through a [`ConnectionSupervisor`], clear the interrupt,
+//! convert ticks into clicks with a [`DetentNormaliser`], scale them with
+//! an [`EncoderAccelerator`] and hand the result to an [`EncoderSink`].
+//! The sink's [`SinkResponse`] can change the interrupt mask and the knob
+//! pixels.
+//!
+//! [`ParameterValues`] implements [`EncoderSink`] directly, and any sink
+//! behind an Embassy [`Mutex`] does too, so firmware only wires it up.
+//! [`EncoderMonitorTask::step`] runs one wake-up with an explicit
+//! timestamp, which lets the loop be tested on the host. [`source_monitor`]
+//! feeds the same sinks from any [`EncoderSource`] — a script or a
+//! recording instead of a board.
+//!
+//! Enabled by the **`task`** feature.
+
+use core::future::Future;
+
+use embassy_futures::select::select;
+use embassy_sync::blocking_mutex::raw::RawMutex;
+use embassy_sync::mutex::Mutex;
+use embassy_time::{Duration, Instant, Timer};
+use embedded_hal_async::delay::DelayNs;
+use embedded_hal_async::i2c::I2c;
+use spirant::parameter_values::ParameterValues;
+
+use crate::acceleration::{AccelerationCurve, EncoderAccelerator};
+use crate::detent::{DetentConfig, DetentNormaliser};
+use crate::encoder_board::QuadEncoderBoard;
+use crate::monitor::{EncoderMonitor, MonitorMode};
+use crate::neopixel::Rgb;
+use crate::recovery::{ConnectionEvent, ConnectionSupervisor, RecoveryPolicy};
+use crate::registers::ENCODER_COUNT;
+use crate::source::EncoderSource;
+
+pub use crate::source::{InterruptPin, PollInterval, WakeSource};
+
+// ---------------------------------------------------------------------------
+// Sink
+// ---------------------------------------------------------------------------
+
+/// Everything read from the board on one wake-up.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct EncoderUpdate {
+    /// When the board was read.
+    pub now: Instant,
+    /// Movement in clicks, after detent normalisation and acceleration.
+    pub deltas: [i32; ENCODER_COUNT],
+    /// Movement in ticks, as read from the delta registers.
+    pub raw_deltas: [i32; ENCODER_COUNT],
+    /// Pressed switches (bit `i` = encoder `i`), or `None` if switches are
+    /// not read or the read failed.
+    pub switches: Option<u8>,
+    /// Encoders whose interrupt the sink has masked (bit `i` = encoder
+    /// `i`). The board still counts their turns, so their deltas may be
+    /// non-zero; sinks should ignore them (see
+    /// [`unmasked_deltas()`](Self::unmasked_deltas)).
+    pub masked: u8,
+}
+
+impl EncoderUpdate {
+    /// [`deltas`](Self::deltas) with masked encoders set to zero.
+    pub fn unmasked_deltas(&self) -> [i32; ENCODER_COUNT] {
+        let mut deltas = self.deltas;
+        for (encoder, delta) in deltas.iter_mut().enumerate() {
+            if self.masked & (1 << encoder) != 0 {
+                *delta = 0;
+            }
+        }
+        deltas
+    }
+
+    /// Returns `true` if any encoder moved.
+    pub fn moved(&self) -> bool {
+        self.raw_deltas.iter().any(|&d| d != 0)
+    }
+
+    /// Bitmask of encoders that moved (bit `i` = encoder `i`).
+    pub fn moved_mask(&self) -> u32 {
+        self.raw_deltas
+            .iter()
+            .enumerate()
+            .filter(|(_, &d)| d != 0)
+            .fold(0, |mask, (i, _)| mask | (1 << i))
+    }
+}
+
+/// Board changes requested by an [`EncoderSink`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SinkResponse {
+    /// New encoder interrupt mask (bit `i` = encoder `i`).
+    pub interrupt_mask: Option<u8>,
+    /// Flash every pixel in this colour for this long before `pixels`
+    /// are shown (e.g. on a page change).
+    pub flash: Option<(Rgb, Duration)>,
+    /// Colours to show on the knob pixels.
+    pub pixels: Option<[Rgb; ENCODER_COUNT]>,
+    /// Forget acceleration history (e.g. after a page change).
+    pub reset_acceleration: bool,
+}
+
+/// Encoders left out of an interrupt mask.
+fn masked_by(interrupt_mask: u8) -> u8 {
+    !interrupt_mask & ((1 << ENCODER_COUNT) - 1) as u8
+}
+
+/// Receiver of encoder movement from [`encoder_monitor`].
+pub trait EncoderSink {
+    /// Called once before the first wake-up, to set up the board.
+    fn start(&mut self) -> impl Future<Output = SinkResponse> {
+        async { SinkResponse::default() }
+    }
+
+    /// Called after every wake-up whose reads succeeded, including
+    /// wake-ups without movement.
+    fn update(&mut self, update: &EncoderUpdate) -> impl Future<Output = SinkResponse>;
+
+    /// Called when the board is lost or reconnected.
+    fn connection_changed(&mut self, _event: ConnectionEvent) -> impl Future<Output = ()> {
+        async {}
+    }
+
+    /// Time at which the sink wants an update even without a wake-up
+    /// (e.g. a long-press deadline).
+    fn next_deadline(&self) -> impl Future<Output = Option<Instant>> {
+        async { None }
+    }
+}
+
+/// Applies each encoder's movement to the parameter in the same slot of
+/// the active page, and masks the interrupts of empty slots on page
+/// changes. Movement of masked encoders is ignored: it was made on a page
+/// where they controlled nothing.
+impl<const N_PAGES: usize, const PARAMS_PER_PAGE: usize> EncoderSink
+    for ParameterValues<N_PAGES, PARAMS_PER_PAGE>
+{
+    async fn start(&mut self) -> SinkResponse {
+        SinkResponse {
+            interrupt_mask: self.take_encoder_mask().map(|mask| mask as u8),
+            ..SinkResponse::default()
+        }
+    }
+
+    async fn update(&mut self, update: &EncoderUpdate) -> SinkResponse {
+        for (encoder, delta) in update.unmasked_deltas().into_iter().enumerate() {
+            if delta != 0 {
+                self.update_from_encoder(encoder, delta);
+            }
+        }
+        SinkResponse {
+            interrupt_mask: self.take_encoder_mask().map(|mask| mask as u8),
+            ..SinkResponse::default()
+        }
+    }
+}
+
+/// Locks the mutex for the duration of each call.
+impl<M: RawMutex, S: EncoderSink> EncoderSink for &Mutex<M, S> {
+    async fn start(&mut self) -> SinkResponse {
+        self.lock().await.start().await
+    }
+
+    async fn update(&mut self, update: &EncoderUpdate) -> SinkResponse {
+        self.lock().await.update(update).await
+    }
+
+    async fn connection_changed(&mut self, event: ConnectionEvent) {
+        self.lock().await.connection_changed(event).await;
+    }
+
+    async fn next_deadline(&self) -> Option<Instant> {
+        self.lock().await.next_deadline().await
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Task
+// ---------------------------------------------------------------------------
+
+/// Settings for [`encoder_monitor`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MonitorConfig {
+    /// Ticks per click, direction and scale of each encoder.
+    pub detents: [DetentConfig; ENCODER_COUNT],
+    /// Speed-dependent scaling of deltas; `None` passes them through.
+    pub acceleration: Option<AccelerationCurve>,
+    /// Read the push switches on every wake-up and report them in
+    /// [`EncoderUpdate::switches`].
+    pub read_switches: bool,
+    /// Retry and reconnect timings.
+    pub recovery: RecoveryPolicy,
+}
+
+impl Default for MonitorConfig {
+    fn default() -> Self {
+        Self {
+            detents: [DetentConfig::IDENTITY; ENCODER_COUNT],
+            acceleration: Some(AccelerationCurve::default()),
+            read_switches: false,
+            recovery: RecoveryPolicy::default(),
+        }
+    }
+}
+
+/// State of the [`encoder_monitor`] loop.
+pub struct EncoderMonitorTask<I2C, D, S> {
+    board: QuadEncoderBoard<I2C, D>,
+    sink: S,
+    monitor: EncoderMonitor,
+    detents: DetentNormaliser<ENCODER_COUNT>,
+    accelerator: Option<EncoderAccelerator<ENCODER_COUNT>>,
+    supervisor: ConnectionSupervisor,
+    read_switches: bool,
+    /// Encoders masked by the last [`SinkResponse::interrupt_mask`].
+    masked: u8,
+}
+
+impl<I2C, D, S> EncoderMonitorTask<I2C, D, S>
+where
+    I2C: I2c,
+    D: DelayNs,
+    S: EncoderSink,
+{
+    /// Set up the loop for a configured board.
+    pub fn new(board: QuadEncoderBoard<I2C, D>, sink: S, config: MonitorConfig) -> Self {
+        Self {
+            board,
+            sink,
+            // Delta registers reset on read, so there is no baseline to
+            // establish and ticks read before a failed read are carried
+            // over to the next poll.
+            monitor: EncoderMonitor::new(MonitorMode::Delta),
+            detents: DetentNormaliser::with_configs(config.detents),
+            accelerator: config.acceleration.map(EncoderAccelerator::new),
+            supervisor: ConnectionSupervisor::new(config.recovery),
+            read_switches: config.read_switches,
+            masked: 0,
+        }
+    }
+
+    /// Returns the board, e.g. for extra configuration before running.
+    pub fn board_mut(&mut self) -> &mut QuadEncoderBoard<I2C, D> {
+        &mut self.board
+    }
+
+    /// Returns the sink.
+    pub fn sink_mut(&mut self) -> &mut S {
+        &mut self.sink
+    }
+
+    /// Returns `true` unless the board has been declared lost.
+    pub fn is_connected(&self) -> bool {
+        self.supervisor.is_connected()
+    }
+
+    /// Apply the sink's initial response.
+    pub async fn start(&mut self) {
+        let response = self.sink.start().await;
+        self.apply(response).await;
+    }
+
+    /// Handle one wake-up at time `now`.
+    ///
+    /// Reads switches and deltas, clears the interrupt, and reports to the
+    /// sink. While the board is lost, attempts a reconnect instead.
+    pub async fn step(&mut self, now: Instant) {
+        if !self.supervisor.is_connected() {
+            let reconnected = self
+                .supervisor
+                .reconnect(&mut self.board, &mut self.monitor)
+                .await;
+            // The knobs may have moved while the board was away; a
+            // part-click carried from before is meaningless now.
+            if reconnected.is_ok() {
+                self.detents.reset();
+            }
+            self.report_connection().await;
+            return;
+        }
+
+        let switches = if self.read_switches {
+            self.supervisor
+                .run(&mut self.board, async |b| b.read_switches().await)
+                .await
+                .ok()
+        } else {
+            None
+        };
+
+        let monitor = &mut self.monitor;
+        let polled = self
+            .supervisor
+            .run(&mut self.board, async |b| monitor.poll(b).await)
+            .await;
+
+        // Clear AFTER reading deltas — drives INT back HIGH. Clearing
+        // before reading would risk missing a rapid second movement that
+        // arrives during the read. Also cleared on error, so the next
+        // movement raises a fresh interrupt instead of a tight error loop.
+        if self.supervisor.is_connected() && self.board.clear_interrupt_flags().await.is_err() {
+            #[cfg(feature = "defmt")]
+            defmt::warn!("Failed to clear interrupt flags");
+        }
+
+        let raw_deltas = match polled {
+            Ok(deltas) => deltas,
+            Err(_) => {
+                #[cfg(feature = "defmt")]
+                defmt::error!("Encoder read failed");
+                self.report_connection().await;
+                return;
+            }
+        };
+
+        let clicks = self.detents.apply_all(raw_deltas);
+        let deltas = match &mut self.accelerator {
+            Some(accelerator) => accelerator.apply_all(clicks, now),
+            None => clicks,
+        };
+        let update = EncoderUpdate {
+            now,
+            deltas,
+            raw_deltas,
+            switches,
+            masked: self.masked,
+        };
+        let response = self.sink.update(&update).await;
+        self.apply(response).await;
+    }
+
+    /// Run forever, waking on `wake` or the sink's next deadline.
+    pub async fn run<W: WakeSource>(mut self, mut wake: W) -> ! {
+        self.start().await;
+        loop {
+            // A lost board no longer drives INT, so poll for its return.
+            if !self.supervisor.is_connected() {
+                Timer::after(self.supervisor.policy().reprobe_interval).await;
+            } else {
+                match self.sink.next_deadline().await {
+                    Some(deadline) => {
+                        select(wake.wait(), Timer::at(deadline)).await;
+                    }
+                    None => wake.wait().await,
+                }
+            }
+            self.step(Instant::now()).await;
+        }
+    }
+
+    /// Pass a pending connection event to the sink.
+    async fn report_connection(&mut self) {
+        if let Some(event) = self.supervisor.take_event() {
+            self.sink.connection_changed(event).await;
+        }
+    }
+
+    /// Carry out a sink response. Failures are logged and otherwise
+    /// ignored: the mask is retried on the next response that sets it and
+    /// lighting is cosmetic.
+    ///
+    /// A new mask also discards the turns the board counted on encoders
+    /// masked before or after it, so a knob turned while it controlled
+    /// nothing does not move the parameter it maps to on the new page.
+    async fn apply(&mut self, response: SinkResponse) {
+        if response.reset_acceleration {
+            if let Some(accelerator) = &mut self.accelerator {
+                accelerator.reset();
+            }
+        }
+
+        if let Some(mask) = response.interrupt_mask {
+            let masked = masked_by(mask);
+            if self
+                .monitor
+                .discard(&mut self.board, self.masked | masked)
+                .await
+                .is_err()
+            {
+                #[cfg(feature = "defmt")]
+                defmt::warn!("Failed to discard masked encoder movement");
+            }
+            self.masked = masked;
+            if self.board.set_interrupt_mask(mask).await.is_err() {
+                #[cfg(feature = "defmt")]
+                defmt::warn!("Failed to update encoder interrupt mask");
+            }
+        }
+
+        if let Some((colour, duration)) = response.flash {
+            self.board.set_all_pixels(colour);
+            if self.board.show().await.is_ok() {
+                self.board
+                    .seesaw_mut()
+                    .delay_ms(duration.as_millis() as u32)
+                    .await;
+            }
+        }
+
+        if let Some(colours) = response.pixels {
+            for (encoder, &colour) in colours.iter().enumerate() {
+                let _ = self.board.set_pixel(encoder as u8, colour);
+            }
+            if self.board.show().await.is_err() {
+                #[cfg(feature = "defmt")]
+                defmt::warn!("Failed to update encoder pixels");
+            }
+        }
+    }
+}
+
+/// Interrupt- or poll-driven encoder monitoring loop.
+///
+/// This is a regular `async fn` — **not** an Embassy `#[task]`. Callers
+/// should create a thin, concrete task wrapper that calls this function,
+/// since Embassy tasks cannot be generic:
+///
+/// ```ignore
+/// #[embassy_executor::task]
+/// async fn encoder_task(
+///     board: QuadEncoderBoard<MyConcreteI2cType>,
+///     int_pin: Input<'static>,
+///     params: &'static Mutex<CriticalSectionRawMutex, ParameterValues>,
+/// ) {
+///     encoder_monitor(board, InterruptPin(int_pin), params, MonitorConfig::default()).await
+/// }
+/// ```
+///
+/// The board should be configured (interrupts, switches, pixels) before
+/// the loop starts; after a reconnect that configuration is replayed
+/// automatically. See [`EncoderMonitorTask`] for the steps of each
+/// wake-up.
+pub async fn encoder_monitor<I2C, D, W, S>(
+    board: QuadEncoderBoard<I2C, D>,
+    wake: W,
+    sink: S,
+    config: MonitorConfig,
+) -> !
+where
+    I2C: I2c,
+    D: DelayNs,
+    W: WakeSource,
+    S: EncoderSink,
+{
+    EncoderMonitorTask::new(board, sink, config).run(wake).await
+}
+
+/// Drive a sink from any [`EncoderSource`] instead of a board.
+///
+/// Each report is accelerated and passed to `sink` like a board wake-up.
+/// Switches are not reported and the board changes a [`SinkResponse`]
+/// asks for are ignored, apart from resetting acceleration and the
+/// interrupt mask, which is reported back in [`EncoderUpdate::masked`].
+/// With a [`ScriptedSource`](crate::ScriptedSource) or
+/// [`ReplaySource`](crate::ReplaySource) and Embassy's `std` time driver,
+/// this runs the parameter and display pipeline on a Linux machine.
+pub async fn source_monitor<E, S>(
+    mut source: E,
+    mut sink: S,
+    acceleration: Option<AccelerationCurve>,
+) -> !
+where
+    E: EncoderSource<ENCODER_COUNT>,
+    S: EncoderSink,
+{
+    let mut accelerator = acceleration.map(EncoderAccelerator::new);
+    let mut masked = sink.start().await.interrupt_mask.map_or(0, masked_by);
+    loop {
+        let raw_deltas = source.wait_deltas().await;
+        let now = Instant::now();
+        let deltas = match &mut accelerator {
+            Some(accelerator) => accelerator.apply_all(raw_deltas, now),
+            None => raw_deltas,
+        };
+        let update = EncoderUpdate {
+            now,
+            deltas,
+            raw_deltas,
+            switches: None,
+            masked,
+        };
+        let response = sink.update(&update).await;
+        if response.reset_acceleration {
+            if let Some(accelerator) = &mut accelerator {
+                accelerator.reset();
+            }
+        }
+        masked = response.interrupt_mask.map_or(masked, masked_by);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use core::pin::pin;
+    use std::vec::Vec;
+
+    use embassy_futures::poll_once;
+    use embassy_sync::blocking_mutex::raw::NoopRawMutex;
+
+    use super::*;
+    use crate::sim::{block_on, SimDelay, SimI2c, SimulatedBoard};
+
+    fn at(ms: u64) -> Instant {
+        Instant::from_millis(ms)
+    }
+
+    fn no_acceleration() -> MonitorConfig {
+        MonitorConfig {
+            acceleration: None,
+            ..MonitorConfig::default()
+        }
+    }
+
+    /// Records updates and replies with a canned response.
+    #[derive(Default)]
+    struct Recorder {
+        updates: Vec<EncoderUpdate>,
+        events: Vec<ConnectionEvent>,
+        reply: SinkResponse,
+    }
+
+    impl EncoderSink for Recorder {
+        async fn update(&mut self, update: &EncoderUpdate) -> SinkResponse {
+            self.updates.push(*update);
+            self.reply
+        }
+
+        async fn connection_changed(&mut self, event: ConnectionEvent) {
+            self.events.push(event);
+        }
+    }
+
+    fn task<S: EncoderSink>(
+        sim: &SimulatedBoard,
+        sink: S,
+    ) -> EncoderMonitorTask<SimI2c, SimDelay, S> {
+        EncoderMonitorTask::new(sim.board(), sink, no_acceleration())
+    }
+
+    #[test]
+    fn parameter_values_receive_deltas() {
+        let sim = SimulatedBoard::new();
+        let mut task = task(&sim, ParameterValues::new());
+        block_on(task.start());
+        // Page 0 maps all four encoders.
+        assert!(sim.interrupt_enabled(0) && sim.interrupt_enabled(3));
+
+        sim.turn(0, 10);
+        block_on(task.step(at(0)));
+        assert_eq!(
+            task.sink_mut().pages[0].params[0].as_ref().unwrap().value,
+            10
+        );
+    }
+
+    #[test]
+    fn page_change_updates_interrupt_mask() {
+        let sim = SimulatedBoard::new();
+        let mut task = task(&sim, ParameterValues::new());
+        block_on(task.start());
+
+        // Page 2 has a null slot for encoder 3.
+        task.sink_mut().set_active_page(2).unwrap();
+        block_on(task.step(at(0)));
+        assert!(!sim.interrupt_enabled(3));
+    }
+
+    #[test]
+    fn turns_on_masked_encoder_do_not_follow_page_change() {
+        let sim = SimulatedBoard::new();
+        let mut task = task(&sim, ParameterValues::new());
+        block_on(task.start());
+
+        // Page 3 leaves encoder 3 unmapped; its interrupt is masked.
+        task.sink_mut().set_active_page(3).unwrap();
+        block_on(task.step(at(0)));
+        assert!(!sim.interrupt_enabled(3));
+        let before = task.sink_mut().pages[0].params[3].as_ref().unwrap().value;
+
+        // The board keeps counting the masked encoder.
+        sim.turn(3, 20);
+        task.sink_mut().set_active_page(0).unwrap();
+        block_on(task.step(at(10)));
+        assert!(sim.interrupt_enabled(3));
+        assert_eq!(
+            task.sink_mut().pages[0].params[3].as_ref().unwrap().value,
+            before
+        );
+
+        // Turns made after unmasking still count.
+        sim.turn(3, 2);
+        block_on(task.step(at(20)));
+        assert_eq!(
+            task.sink_mut().pages[0].params[3].as_ref().unwrap().value,
+            before + 2
+        );
+    }
+
+    #[test]
+    fn deltas_are_normalised_to_clicks() {
+        let sim = SimulatedBoard::new();
+        let mut detents = [DetentConfig::IDENTITY; ENCODER_COUNT];
+        detents[0] = DetentConfig {
+            ticks_per_detent: 4,
+            inverted: true,
+            ..DetentConfig::IDENTITY
+        };
+        let config = MonitorConfig {
+            detents,
+            ..no_acceleration()
+        };
+        let mut task = EncoderMonitorTask::new(sim.board(), Recorder::default(), config);
+
+        sim.turn(0, 6);
+        sim.turn(1, 2);
+        block_on(task.step(at(0)));
+        sim.turn(0, 2);
+        block_on(task.step(at(10)));
+
+        let updates = &task.sink_mut().updates;
+        assert_eq!(updates[0].deltas, [-1, 2, 0, 0]);
+        assert_eq!(updates[0].raw_deltas, [6, 2, 0, 0]);
+        assert_eq!(updates[1].deltas, [-1, 0, 0, 0]);
+    }
+
+    #[test]
+    fn step_clears_interrupt_and_reports_switches() {
+        let sim = SimulatedBoard::new();
+        let mut task = EncoderMonitorTask::new(
+            sim.board(),
+            Recorder::default(),
+            MonitorConfig {
+                read_switches: true,
+                ..no_acceleration()
+            },
+        );
+        block_on(task.board_mut().enable_all_interrupts()).unwrap();
+
+        sim.turn(1, -2);
+        sim.set_switch(2, true);
+        block_on(task.step(at(5)));
+
+        assert!(!sim.int_asserted());
+        let update = task.sink_mut().updates[0];
+        assert_eq!(update.raw_deltas, [0, -2, 0, 0]);
+        assert_eq!(update.switches, Some(0b0100));
+        assert_eq!(update.moved_mask(), 0b0010);
+        assert_eq!(update.now, at(5));
+    }
+
+    #[test]
+    fn sink_response_drives_pixels() {
+        let sim = SimulatedBoard::new();
+        let sink = Recorder {
+            reply: SinkResponse {
+                flash: Some((Rgb::WHITE, Duration::from_millis(60))),
+                pixels: Some([Rgb::new(1, 2, 3); ENCODER_COUNT]),
+                ..SinkResponse::default()
+            },
+            ..Recorder::default()
+        };
+        let mut task = task(&sim, sink);
+        block_on(task.board_mut().configure_pixels()).unwrap();
+
+        let before = sim.now();
+        block_on(task.step(at(0)));
+        assert_eq!(sim.show_count(), 2);
+        assert_eq!(sim.pixel(0), Rgb::new(1, 2, 3));
+        assert!(sim.now() - before >= core::time::Duration::from_millis(60));
+    }
+
+    #[test]
+    fn lost_board_is_reported_and_reconnected() {
+        let sim = SimulatedBoard::new();
+        let mut task = task(&sim, Recorder::default());
+
+        sim.unplug();
+        block_on(task.step(at(0)));
+        assert!(!task.is_connected());
+        assert_eq!(task.sink_mut().events, [ConnectionEvent::Lost]);
+        assert!(task.sink_mut().updates.is_empty());
+
+        sim.plug_in();
+        block_on(task.step(at(500)));
+        assert!(task.is_connected());
+        assert!(matches!(
+            task.sink_mut().events[1],
+            ConnectionEvent::Reconnected(_)
+        ));
+    }
+
+    #[test]
+    fn mutex_sink_locks_per_call() {
+        let sim = SimulatedBoard::new();
+        let params: Mutex<NoopRawMutex, ParameterValues> = Mutex::new(ParameterValues::new());
+        let value = || {
+            block_on(params.lock()).pages[0].params[2]
+                .as_ref()
+                .unwrap()
+                .value
+        };
+        let before = value();
+        let mut task = task(&sim, &params);
+
+        sim.turn(2, 1);
+        block_on(task.step(at(0)));
+        assert_ne!(value(), before);
+    }
+
+    /// Wants an update at a fixed time.
+    struct Deadline(Instant);
+
+    impl EncoderSink for Deadline {
+        async fn update(&mut self, _update: &EncoderUpdate) -> SinkResponse {
+            SinkResponse::default()
+        }
+
+        async fn next_deadline(&self) -> Option<Instant> {
+            Some(self.0)
+        }
+    }
+
+    #[test]
+    fn mutex_sink_deadline_waits_for_the_lock() {
+        let sink: Mutex<NoopRawMutex, Deadline> = Mutex::new(Deadline(at(610)));
+        let shared = &sink;
+        let guard = block_on(sink.lock());
+        let mut deadline = pin!(shared.next_deadline());
+        // Another task holds the sink: the deadline is not lost, only late.
+        assert!(poll_once(deadline.as_mut()).is_pending());
+        drop(guard);
+        assert_eq!(block_on(deadline), Some(at(610)));
+    }
+}
@@ -8,8 +8,31 @@ pub enum EncoderError<E> {
     /// Underlying I2C bus error.
     I2c(E),
 
+    /// Error reading an encoder's GPIO pin (the
+    /// [`gpio`](crate::gpio) backend).
+    Pin(E),
+
     /// Encoder index out of valid range (must be 0–3).
     InvalidEncoder,
+
+    /// The device at the configured address is not a quad encoder board.
+    WrongDevice {
+        /// HW_ID reported by the device.
+        hw_id: u8,
+        /// Product number reported in its VERSION register.
+        product: u16,
+    },
+
+    /// The device's Seesaw firmware does not report a product code and is
+    /// too old to be identified.
+    UnsupportedFirmware {
+        /// Build date code from the VERSION register.
+        date_code: u16,
+    },
+
+    /// The board was declared lost after repeated failures and has not
+    /// been reconnected yet (see [`ConnectionSupervisor`](crate::ConnectionSupervisor)).
+    Disconnected,
 }
 
 // Allow ergonomic `?` propagation from raw I2C errors.
@@ -23,7 +46,19 @@ impl<E: fmt::Debug> fmt::Display for EncoderError<E> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
             EncoderError::I2c(e) => write!(f, "I2C error: {:?}", e),
+            EncoderError::Pin(e) => write!(f, "Pin error: {:?}", e),
             EncoderError::InvalidEncoder => write!(f, "Invalid encoder index (must be 0-3)"),
+            EncoderError::WrongDevice { hw_id, product } => write!(
+                f,
+                "Wrong device (HW_ID {:#04x}, product {})",
+                hw_id, product
+            ),
+            EncoderError::UnsupportedFirmware { date_code } => write!(
+                f,
+                "Unsupported Seesaw firmware (date code {:#06x})",
+                date_code
+            ),
+            EncoderError::Disconnected => write!(f, "Encoder board disconnected"),
         }
     }
 }
@@ -33,7 +68,15 @@ impl<E: defmt::Format> defmt::Format for EncoderError<E> {
     fn format(&self, f: defmt::Formatter) {
         match self {
             EncoderError::I2c(e) => defmt::write!(f, "I2C error: {}", e),
+            EncoderError::Pin(e) => defmt::write!(f, "Pin error: {}", e),
             EncoderError::InvalidEncoder => defmt::write!(f, "Invalid encoder index"),
+            EncoderError::WrongDevice { hw_id, product } => {
+                defmt::write!(f, "Wrong device (HW_ID {=u8:#x}, product {})", hw_id, product)
+            }
+            EncoderError::UnsupportedFirmware { date_code } => {
+                defmt::write!(f, "Unsupported Seesaw firmware (date code {=u16:#x})", date_code)
+            }
+            EncoderError::Disconnected => defmt::write!(f, "Encoder board disconnected"),
         }
     }
 }
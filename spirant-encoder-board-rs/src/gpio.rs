@@ -0,0 +1,438 @@
+//! Encoders wired straight to GPIO pins.
+//!
+//! [`GpioEncoders`] decodes `N` encoders whose A and B lines are connected
+//! to edge-triggered inputs (e.g. `embassy_rp::gpio::Input`), using a
+//! [`QuadratureDecoder`] per encoder. It offers the same position and delta
+//! methods as [`QuadEncoderBoard`](crate::QuadEncoderBoard) and implements
+//! [`EncoderSource`], so code written against the board runs unchanged on
+//! panels without a Seesaw breakout.
+//!
+//! Enabled by the **`gpio`** feature.
+
+use embassy_futures::select::{select, select_array};
+use embassy_time::Delay;
+use embedded_hal::digital::InputPin;
+use embedded_hal_async::delay::DelayNs;
+use embedded_hal_async::digital::Wait;
+
+use crate::error::EncoderError;
+use crate::quadrature::{QuadratureDecoder, StepMode};
+use crate::source::EncoderSource;
+
+/// Pause in milliseconds before sampling again after a pin read failed in
+/// [`wait_deltas`](EncoderSource::wait_deltas).
+const PIN_RETRY_DELAY_MS: u32 = 10;
+
+/// `N` quadrature encoders read from GPIO pins.
+///
+/// Every wake-up samples all pins and feeds the decoders, so an edge on
+/// any encoder brings every decoder up to date. Edges that arrive while
+/// the pins are being sampled are caught by the sample itself; if an
+/// encoder moves through a whole state in that window, its decoder
+/// rejects the jump as an illegal transition rather than miscounting.
+/// Run [`wait_for_edge`](Self::wait_for_edge) (or
+/// [`wait_deltas`](EncoderSource::wait_deltas)) from its own task so the
+/// pins are watched continuously.
+///
+/// Pin read errors are returned as [`EncoderError::Pin`]. Pins on most
+/// HALs cannot fail; `D` paces the retries of one that does.
+///
+/// # Example
+///
+/// ```no_run
+/// use encoder_driver::gpio::GpioEncoders;
+/// use encoder_driver::StepMode;
+///
+/// // `a0`..`b1` implement `InputPin` and `Wait` (e.g. `embassy_rp::gpio::Input`)
+/// let mut encoders = GpioEncoders::new([(a0, b0), (a1, b1)], StepMode::Full)?;
+///
+/// encoders.wait_for_edge().await?;
+/// let deltas = encoders.read_all_deltas().await?;
+/// ```
+pub struct GpioEncoders<P, const N: usize, D = Delay> {
+    /// `(A, B)` pins of each encoder.
+    pins: [(P, P); N],
+    decoders: [QuadratureDecoder; N],
+    delay: D,
+}
+
+impl<P, const N: usize> GpioEncoders<P, N>
+where
+    P: InputPin + Wait,
+{
+    /// Take ownership of the `(A, B)` pins of each encoder, using the
+    /// Embassy [`Delay`].
+    ///
+    /// The current line levels are taken as each encoder's detent state,
+    /// so the knobs must be at rest.
+    ///
+    /// # Errors
+    /// * [`EncoderError::Pin`] if a pin cannot be read
+    pub fn new(pins: [(P, P); N], mode: StepMode) -> Result<Self, EncoderError<P::Error>> {
+        Self::with_delay(pins, Delay, mode)
+    }
+}
+
+impl<P, const N: usize, D> GpioEncoders<P, N, D>
+where
+    P: InputPin + Wait,
+    D: DelayNs,
+{
+    /// Take ownership of the `(A, B)` pins of each encoder, with a custom
+    /// delay provider.
+    ///
+    /// # Errors
+    /// * [`EncoderError::Pin`] if a pin cannot be read
+    pub fn with_delay(
+        mut pins: [(P, P); N],
+        delay: D,
+        mode: StepMode,
+    ) -> Result<Self, EncoderError<P::Error>> {
+        let mut decoders = [QuadratureDecoder::new(mode, true, true); N];
+        for ((a, b), decoder) in pins.iter_mut().zip(&mut decoders) {
+            *decoder = QuadratureDecoder::new(mode, level(a)?, level(b)?);
+        }
+        Ok(Self {
+            pins,
+            decoders,
+            delay,
+        })
+    }
+
+    /// Give back the pins.
+    pub fn release(self) -> [(P, P); N] {
+        self.pins
+    }
+
+    /// Returns the decoder of each encoder, e.g. to check
+    /// [`illegal_transitions`](QuadratureDecoder::illegal_transitions).
+    pub fn decoders(&self) -> &[QuadratureDecoder; N] {
+        &self.decoders
+    }
+
+    /// Wait for an edge on any pin, then sample every pin.
+    ///
+    /// A pin wait error is treated as a wake-up; the sample that follows
+    /// is harmless if nothing moved.
+    ///
+    /// # Errors
+    /// * [`EncoderError::Pin`] if a pin cannot be read
+    pub async fn wait_for_edge(&mut self) -> Result<(), EncoderError<P::Error>> {
+        let edges = self
+            .pins
+            .each_mut()
+            .map(|(a, b)| select(a.wait_for_any_edge(), b.wait_for_any_edge()));
+        select_array(edges).await;
+        self.sample()
+    }
+
+    /// Sample every pin and feed the decoders.
+    ///
+    /// # Errors
+    /// * [`EncoderError::Pin`] if a pin cannot be read
+    pub fn sample(&mut self) -> Result<(), EncoderError<P::Error>> {
+        for ((a, b), decoder) in self.pins.iter_mut().zip(&mut self.decoders) {
+            decoder.update(level(a)?, level(b)?);
+        }
+        Ok(())
+    }
+
+    /// Read the absolute position of a specific encoder, in detents.
+    ///
+    /// # Errors
+    /// * [`EncoderError::InvalidEncoder`] if `encoder >= N`
+    /// * [`EncoderError::Pin`] if a pin cannot be read
+    pub async fn read_position(&mut self, encoder: u8) -> Result<i32, EncoderError<P::Error>> {
+        self.sample()?;
+        Ok(self.decoder(encoder)?.position())
+    }
+
+    /// Read the absolute positions of all encoders.
+    ///
+    /// # Errors
+    /// * [`EncoderError::Pin`] if a pin cannot be read
+    pub async fn read_all_positions(&mut self) -> Result<[i32; N], EncoderError<P::Error>> {
+        self.sample()?;
+        Ok(self.decoders.each_ref().map(QuadratureDecoder::position))
+    }
+
+    /// Read the movement of a specific encoder since its previous delta
+    /// read, and reset it.
+    ///
+    /// # Errors
+    /// * [`EncoderError::InvalidEncoder`] if `encoder >= N`
+    /// * [`EncoderError::Pin`] if a pin cannot be read
+    pub async fn read_delta(&mut self, encoder: u8) -> Result<i32, EncoderError<P::Error>> {
+        self.sample()?;
+        Ok(self.decoder(encoder)?.take_delta())
+    }
+
+    /// Read and reset the deltas of all encoders.
+    ///
+    /// # Errors
+    /// * [`EncoderError::Pin`] if a pin cannot be read
+    pub async fn read_all_deltas(&mut self) -> Result<[i32; N], EncoderError<P::Error>> {
+        self.sample()?;
+        Ok(self.decoders.each_mut().map(QuadratureDecoder::take_delta))
+    }
+
+    /// Set the absolute position of a specific encoder.
+    ///
+    /// # Errors
+    /// * [`EncoderError::InvalidEncoder`] if `encoder >= N`
+    pub async fn set_position(
+        &mut self,
+        encoder: u8,
+        value: i32,
+    ) -> Result<(), EncoderError<P::Error>> {
+        self.decoder(encoder)?.set_position(value);
+        Ok(())
+    }
+
+    fn decoder(&mut self, encoder: u8) -> Result<&mut QuadratureDecoder, EncoderError<P::Error>> {
+        self.decoders
+            .get_mut(encoder as usize)
+            .ok_or(EncoderError::InvalidEncoder)
+    }
+}
+
+impl<P, const N: usize, D> EncoderSource<N> for GpioEncoders<P, N, D>
+where
+    P: InputPin + Wait,
+    D: DelayNs,
+{
+    async fn wait_deltas(&mut self) -> [i32; N] {
+        loop {
+            if self.wait_for_edge().await.is_err() {
+                #[cfg(feature = "defmt")]
+                defmt::warn!("Encoder pin read failed");
+                // A pin that keeps failing must not spin the executor.
+                self.delay.delay_ms(PIN_RETRY_DELAY_MS).await;
+                continue;
+            }
+            let deltas = self.decoders.each_mut().map(QuadratureDecoder::take_delta);
+            if deltas.iter().any(|&d| d != 0) {
+                return deltas;
+            }
+        }
+    }
+}
+
+/// Level of `pin`, with a read error reported as [`EncoderError::Pin`].
+fn level<P: InputPin>(pin: &mut P) -> Result<bool, EncoderError<P::Error>> {
+    pin.is_high().map_err(EncoderError::Pin)
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use core::cell::Cell;
+    use core::convert::Infallible;
+    use std::rc::Rc;
+
+    use core::pin::pin;
+
+    use embassy_futures::poll_once;
+    use embedded_hal::digital::ErrorKind;
+
+    use super::*;
+    use crate::sim::block_on;
+
+    /// Input whose level is set by the test. Every wait completes at
+    /// once, as if the edge had already happened.
+    #[derive(Clone)]
+    struct TestPin(Rc<Cell<bool>>);
+
+    impl embedded_hal::digital::ErrorType for TestPin {
+        type Error = Infallible;
+    }
+
+    impl InputPin for TestPin {
+        fn is_high(&mut self) -> Result<bool, Infallible> {
+            Ok(self.0.get())
+        }
+
+        fn is_low(&mut self) -> Result<bool, Infallible> {
+            Ok(!self.0.get())
+        }
+    }
+
+    impl Wait for TestPin {
+        async fn wait_for_high(&mut self) -> Result<(), Infallible> {
+            Ok(())
+        }
+
+        async fn wait_for_low(&mut self) -> Result<(), Infallible> {
+            Ok(())
+        }
+
+        async fn wait_for_rising_edge(&mut self) -> Result<(), Infallible> {
+            Ok(())
+        }
+
+        async fn wait_for_falling_edge(&mut self) -> Result<(), Infallible> {
+            Ok(())
+        }
+
+        async fn wait_for_any_edge(&mut self) -> Result<(), Infallible> {
+            Ok(())
+        }
+    }
+
+    /// Input resting high that fails every read while `broken` is set.
+    #[derive(Clone)]
+    struct FlakyPin(Rc<Cell<bool>>);
+
+    impl FlakyPin {
+        fn read(&self) -> Result<bool, ErrorKind> {
+            if self.0.get() {
+                Err(ErrorKind::Other)
+            } else {
+                Ok(true)
+            }
+        }
+    }
+
+    impl embedded_hal::digital::ErrorType for FlakyPin {
+        type Error = ErrorKind;
+    }
+
+    impl InputPin for FlakyPin {
+        fn is_high(&mut self) -> Result<bool, ErrorKind> {
+            self.read()
+        }
+
+        fn is_low(&mut self) -> Result<bool, ErrorKind> {
+            self.read().map(|high| !high)
+        }
+    }
+
+    impl Wait for FlakyPin {
+        async fn wait_for_high(&mut self) -> Result<(), ErrorKind> {
+            self.read().map(drop)
+        }
+
+        async fn wait_for_low(&mut self) -> Result<(), ErrorKind> {
+            self.read().map(drop)
+        }
+
+        async fn wait_for_rising_edge(&mut self) -> Result<(), ErrorKind> {
+            self.read().map(drop)
+        }
+
+        async fn wait_for_falling_edge(&mut self) -> Result<(), ErrorKind> {
+            self.read().map(drop)
+        }
+
+        async fn wait_for_any_edge(&mut self) -> Result<(), ErrorKind> {
+            self.read().map(drop)
+        }
+    }
+
+    /// Delay that counts its calls and never completes.
+    #[derive(Clone, Default)]
+    struct TestDelay(Rc<Cell<u32>>);
+
+    impl DelayNs for TestDelay {
+        async fn delay_ns(&mut self, _ns: u32) {
+            self.0.set(self.0.get() + 1);
+            core::future::pending().await
+        }
+    }
+
+    /// Two encoders resting high, with handles to drive their lines.
+    fn encoders() -> (GpioEncoders<TestPin, 2, TestDelay>, [Rc<Cell<bool>>; 4]) {
+        let lines: [Rc<Cell<bool>>; 4] = core::array::from_fn(|_| Rc::new(Cell::new(true)));
+        let pin = |i: usize| TestPin(lines[i].clone());
+        let encoders = GpioEncoders::with_delay(
+            [(pin(0), pin(1)), (pin(2), pin(3))],
+            TestDelay::default(),
+            StepMode::Full,
+        )
+        .unwrap();
+        (encoders, lines)
+    }
+
+    /// Step encoder `e` through one full clockwise cycle, sampling each
+    /// state.
+    fn turn(
+        encoders: &mut GpioEncoders<TestPin, 2, TestDelay>,
+        lines: &[Rc<Cell<bool>>; 4],
+        e: usize,
+    ) {
+        for (a, b) in [(false, true), (false, false), (true, false), (true, true)] {
+            lines[2 * e].set(a);
+            lines[2 * e + 1].set(b);
+            block_on(encoders.wait_for_edge()).unwrap();
+        }
+    }
+
+    #[test]
+    fn deltas_and_positions_match_board_api() {
+        let (mut encoders, lines) = encoders();
+        turn(&mut encoders, &lines, 1);
+        turn(&mut encoders, &lines, 1);
+
+        assert_eq!(block_on(encoders.read_all_positions()).unwrap(), [0, 2]);
+        assert_eq!(block_on(encoders.read_delta(1)).unwrap(), 2);
+        assert_eq!(block_on(encoders.read_delta(1)).unwrap(), 0);
+        block_on(encoders.set_position(0, 7)).unwrap();
+        assert_eq!(block_on(encoders.read_position(0)).unwrap(), 7);
+        assert!(matches!(
+            block_on(encoders.read_delta(2)),
+            Err(EncoderError::InvalidEncoder)
+        ));
+    }
+
+    #[test]
+    fn pin_errors_are_not_reported_as_i2c() {
+        let pin = FlakyPin(Rc::new(Cell::new(true)));
+        let result =
+            GpioEncoders::with_delay([(pin.clone(), pin)], TestDelay::default(), StepMode::Full);
+        assert!(matches!(result, Err(EncoderError::Pin(ErrorKind::Other))));
+    }
+
+    #[test]
+    fn failing_pin_backs_off_instead_of_spinning() {
+        let broken = Rc::new(Cell::new(false));
+        let pin = FlakyPin(broken.clone());
+        let delay = TestDelay::default();
+        let mut encoders =
+            GpioEncoders::with_delay([(pin.clone(), pin)], delay.clone(), StepMode::Full).unwrap();
+
+        broken.set(true);
+        let wait = pin!(encoders.wait_deltas());
+        assert!(poll_once(wait).is_pending());
+        assert_eq!(delay.0.get(), 1);
+    }
+
+    #[test]
+    fn wait_deltas_reports_movement() {
+        let (mut encoders, lines) = encoders();
+        // Half a cycle does not reach a detent yet.
+        lines[0].set(false);
+        block_on(encoders.wait_for_edge()).unwrap();
+        lines[1].set(false);
+        block_on(encoders.wait_for_edge()).unwrap();
+        lines[0].set(true);
+        lines[1].set(true);
+        // Jumping 00 -> 11 skips a state and is rejected.
+        block_on(encoders.wait_for_edge()).unwrap();
+        assert_eq!(encoders.decoders()[0].illegal_transitions(), 1);
+
+        turn(&mut encoders, &lines, 0);
+        assert_eq!(block_on(encoders.read_all_deltas()).unwrap(), [1, 0]);
+
+        // Nothing pending: `wait_deltas` samples until the cycle completes.
+        lines[2].set(false);
+        lines[3].set(true);
+        block_on(encoders.wait_for_edge()).unwrap();
+        lines[3].set(false);
+        block_on(encoders.wait_for_edge()).unwrap();
+        lines[2].set(true);
+        block_on(encoders.wait_for_edge()).unwrap();
+        lines[3].set(true);
+        assert_eq!(block_on(encoders.wait_deltas()), [0, 1]);
+    }
+}
@@ -5,20 +5,45 @@
 //!
 //! # Architecture
 //!
-//! The crate is split into two layers:
+//! The driver is split into layers:
 //!
-//! - **`driver`** (crate-private) — Low-level Seesaw protocol primitives that
-//!   handle I2C timing, endianness, and register addressing.
+//! - **`seesaw-driver`** (separate crate) — Seesaw protocol primitives that
+//!   handle I2C timing, endianness, and register addressing. Shared with
+//!   drivers for other Seesaw breakouts.
 //! - **[`QuadEncoderBoard`]** (public) — Validated, high-level API for reading
-//!   and writing encoder positions.
+//!   and writing encoder positions, reading the push switches and driving
+//!   the per-encoder NeoPixels ([`Rgb`]).
+//! - **[`EncoderMonitor`]** (public) — Turns successive board reads into
+//!   per-encoder movement without losing ticks across read failures.
+//! - **[`EncoderBank`]** (public) — Several boards on a shared INT line,
+//!   addressed by bank-wide encoder index and mapped to parameter slots.
+//! - **[`ConnectionSupervisor`]** (public) — Retries failed operations,
+//!   declares an unresponsive board lost and reconnects it once it answers
+//!   again, reporting each transition as a [`ConnectionEvent`].
+//! - **[`EncoderSource`]** (public) — Waits for movement and returns the
+//!   deltas, whether they come from a board ([`BoardSource`]), a script
+//!   ([`ScriptedSource`]) or a recording ([`ReplaySource`]), so the code
+//!   downstream can run without hardware.
+//!
+//! Alongside the board driver, [`DetentNormaliser`] converts raw ticks
+//! into one step per click, per encoder, and [`EncoderAccelerator`] scales
+//! the result by turning speed. Both are pure functions of deltas and
+//! timestamps, so they can be unit-tested on the host.
+//! [`ButtonEventDetector`] does the same for the push switches, turning
+//! sampled switch bits into debounced [`ButtonEvent`]s.
+//! [`QuadratureDecoder`] decodes encoders wired straight to GPIOs from
+//! their A/B line samples, again without any I/O.
 //!
 //! # Quick start
 //!
 //! ```no_run
-//! use encoder_driver::QuadEncoderBoard;
+//! use encoder_driver::{QuadEncoderBoard, DEFAULT_ADDRESS};
 //!
 //! // Construct with any `embedded-hal-async` I2C implementation
-//! let mut board = QuadEncoderBoard::new(i2c, 0x36);
+//! let mut board = QuadEncoderBoard::new(i2c, DEFAULT_ADDRESS);
+//!
+//! // Confirm a quad encoder board is really at that address
+//! board.probe().await?;
 //!
 //! // Read all four encoder positions
 //! let positions = board.read_all_positions().await?;
@@ -28,14 +53,75 @@
 //!
 //! - **`defmt`** — Enable [`defmt::Format`] implementations on error types
 //!   for embedded logging.
+//! - **`time`** (default) — Depend on `embassy-time`: `embassy_time::Delay`
+//!   becomes the default delay of [`QuadEncoderBoard`], and the modules
+//!   that keep time (acceleration, push-switch gestures, connection
+//!   supervision and encoder sources) are built.
+//! - **`blocking`** — Enable the [`blocking`] module: the same
+//!   [`QuadEncoderBoard`] API over the blocking `embedded-hal` 1.0 traits.
+//!   Build with `default-features = false` to leave Embassy out entirely.
+//! - **`gpio`** — Enable the [`gpio`] module: encoders on GPIO pins,
+//!   decoded with [`QuadratureDecoder`] and read through the same delta
+//!   API as [`QuadEncoderBoard`].
+//! - **`task`** — Enable the [`encoder_monitor`] module: a reusable
+//!   interrupt- or poll-driven monitoring loop reporting to an
+//!   [`EncoderSink`](encoder_monitor::EncoderSink), implemented for
+//!   `ParameterValues`.
+//! - **`sim`** — Enable the [`sim`] module: a `std`-only simulated board
+//!   implementing `embedded_hal_async::i2c::I2c`, for host-side tests.
 
 #![no_std]
 
+#[cfg(feature = "time")]
+pub use acceleration::{AccelerationCurve, EncoderAccelerator};
+pub use bank::{BankTurn, EncoderBank};
+#[cfg(feature = "time")]
+pub use buttons::{ButtonEvent, ButtonEventDetector, ButtonTimings};
+pub use detent::{DetentConfig, DetentNormaliser};
 pub use encoder_board::QuadEncoderBoard;
 pub use error::EncoderError;
-pub use registers::{DEFAULT_ADDRESS, ENCODER_COUNT};
+pub use monitor::{EncoderMonitor, MonitorMode};
+pub use neopixel::Rgb;
+pub use quadrature::{QuadratureDecoder, StepMode};
+#[cfg(feature = "time")]
+pub use recovery::{
+    BusRecovery, ConnectionEvent, ConnectionState, ConnectionSupervisor, NoBusRecovery,
+    RecoveryPolicy,
+};
+pub use registers::{DEFAULT_ADDRESS, ENCODER_COUNT, QUAD_ENCODER_PRODUCT_ID, SWITCH_PINS};
+#[cfg(feature = "time")]
+pub use source::{
+    BoardSource, EncoderSource, InterruptPin, PollInterval, ReplayError, ReplaySource, ScriptStep,
+    ScriptedSource, WakeSource,
+};
+pub use status::DeviceInfo;
 
-mod driver;
+#[cfg(feature = "task")]
+pub use encoder_monitor::{encoder_monitor, source_monitor};
+
+#[cfg(feature = "time")]
+mod acceleration;
+mod bank;
+#[cfg(feature = "time")]
+mod buttons;
+mod detent;
 mod encoder_board;
 mod error;
+mod monitor;
+mod neopixel;
+mod quadrature;
+#[cfg(feature = "time")]
+mod recovery;
 mod registers;
+#[cfg(feature = "time")]
+mod source;
+mod status;
+
+#[cfg(feature = "blocking")]
+pub mod blocking;
+#[cfg(feature = "gpio")]
+pub mod gpio;
+#[cfg(feature = "task")]
+pub mod encoder_monitor;
+#[cfg(any(test, feature = "sim"))]
+pub mod sim;
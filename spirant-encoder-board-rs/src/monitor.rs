@@ -0,0 +1,260 @@
+//! Loss-free movement tracking on top of [`QuadEncoderBoard`].
+//!
+//! [`EncoderMonitor`] turns successive board reads into per-encoder
+//! movement, either from the device-side delta registers or by differencing
+//! absolute positions against a baseline.
+
+use embedded_hal_async::delay::DelayNs;
+use embedded_hal_async::i2c::I2c;
+
+use crate::encoder_board::QuadEncoderBoard;
+use crate::error::EncoderError;
+use crate::registers::ENCODER_COUNT;
+
+/// How [`EncoderMonitor`] measures movement.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum MonitorMode {
+    /// Read the `ENCODER_DELTA` registers, which reset on read. Movement
+    /// survives read failures and board resets.
+    #[default]
+    Delta,
+    /// Read absolute positions and difference them against the previous
+    /// read. The first read only establishes the baseline.
+    Position,
+}
+
+/// Per-encoder movement tracker for one [`QuadEncoderBoard`].
+///
+/// In [`MonitorMode::Delta`] each encoder's delta is moved into a local
+/// pending total as soon as it is read. If a later read in the same
+/// [`poll()`](Self::poll) fails, the pending totals are kept and returned
+/// by the next successful poll, so movement is never lost or counted twice.
+///
+/// # Example
+///
+/// ```no_run
+/// use encoder_driver::{EncoderMonitor, MonitorMode};
+///
+/// let mut monitor = EncoderMonitor::new(MonitorMode::Delta);
+/// let deltas = monitor.poll(&mut board).await?;
+/// ```
+#[derive(Debug, Clone)]
+pub struct EncoderMonitor {
+    mode: MonitorMode,
+    /// Last absolute positions ([`MonitorMode::Position`] only).
+    baseline: Option<[i32; ENCODER_COUNT]>,
+    /// Movement read from the device but not yet returned.
+    pending: [i32; ENCODER_COUNT],
+}
+
+impl EncoderMonitor {
+    /// Create a monitor with no pending movement.
+    pub const fn new(mode: MonitorMode) -> Self {
+        Self {
+            mode,
+            baseline: None,
+            pending: [0; ENCODER_COUNT],
+        }
+    }
+
+    /// Returns the measurement mode.
+    pub fn mode(&self) -> MonitorMode {
+        self.mode
+    }
+
+    /// Forget the position baseline, e.g. after the board was reset.
+    ///
+    /// The next poll in [`MonitorMode::Position`] re-establishes it and
+    /// reports no movement. Has no effect in [`MonitorMode::Delta`].
+    pub fn resync(&mut self) {
+        self.baseline = None;
+    }
+
+    /// Read the board and return the movement of each encoder since the
+    /// previous successful poll.
+    ///
+    /// # Errors
+    /// Returns the first I2C error encountered. Movement already read is
+    /// retained and reported by the next successful poll.
+    pub async fn poll<I2C: I2c, D: DelayNs>(
+        &mut self,
+        board: &mut QuadEncoderBoard<I2C, D>,
+    ) -> Result<[i32; ENCODER_COUNT], EncoderError<I2C::Error>> {
+        match self.mode {
+            MonitorMode::Delta => {
+                for encoder in 0..ENCODER_COUNT {
+                    let delta = board.read_delta(encoder as u8).await?;
+                    self.record_delta(encoder, delta);
+                }
+            }
+            MonitorMode::Position => {
+                let positions = board.read_all_positions().await?;
+                self.record_positions(positions);
+            }
+        }
+        Ok(self.take_pending())
+    }
+
+    /// Add a delta read from the device to the pending total.
+    fn record_delta(&mut self, encoder: usize, delta: i32) {
+        self.pending[encoder] = self.pending[encoder].saturating_add(delta);
+    }
+
+    /// Difference `positions` against the baseline into the pending totals
+    /// and make them the new baseline.
+    fn record_positions(&mut self, positions: [i32; ENCODER_COUNT]) {
+        if let Some(baseline) = self.baseline {
+            for (i, pending) in self.pending.iter_mut().enumerate() {
+                *pending = pending.saturating_add(positions[i].wrapping_sub(baseline[i]));
+            }
+        }
+        self.baseline = Some(positions);
+    }
+
+    /// Read and drop the movement of the encoders in `mask` (bit `i` =
+    /// encoder `i`), including any carried over from a failed poll.
+    ///
+    /// The board keeps counting encoders whose interrupt is disabled, so
+    /// call this when masking them changes what they control; otherwise
+    /// the stored turns are reported by the next poll.
+    ///
+    /// # Errors
+    /// Returns the first I2C error encountered. Encoders not yet read keep
+    /// their movement on the device.
+    pub async fn discard<I2C: I2c, D: DelayNs>(
+        &mut self,
+        board: &mut QuadEncoderBoard<I2C, D>,
+        mask: u8,
+    ) -> Result<(), EncoderError<I2C::Error>> {
+        let masked = |encoder: usize| mask & (1 << encoder) != 0;
+        match self.mode {
+            MonitorMode::Delta => {
+                for encoder in (0..ENCODER_COUNT).filter(|&e| masked(e)) {
+                    self.pending[encoder] = 0;
+                    board.read_delta(encoder as u8).await?;
+                }
+            }
+            MonitorMode::Position => {
+                let positions = board.read_all_positions().await?;
+                // Unmasked movement stays pending for the next poll.
+                self.record_positions(positions);
+                for encoder in (0..ENCODER_COUNT).filter(|&e| masked(e)) {
+                    self.pending[encoder] = 0;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Return and clear the pending totals.
+    fn take_pending(&mut self) -> [i32; ENCODER_COUNT] {
+        core::mem::take(&mut self.pending)
+    }
+}
+
+impl Default for EncoderMonitor {
+    fn default() -> Self {
+        Self::new(MonitorMode::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sim::{block_on, SimulatedBoard};
+
+    #[test]
+    fn partial_delta_reads_are_carried_over() {
+        let mut monitor = EncoderMonitor::new(MonitorMode::Delta);
+        // First poll reads encoders 0 and 1, then fails on 2.
+        monitor.record_delta(0, 3);
+        monitor.record_delta(1, -1);
+        // Next poll succeeds for all four.
+        monitor.record_delta(0, 1);
+        monitor.record_delta(1, 0);
+        monitor.record_delta(2, 2);
+        monitor.record_delta(3, 0);
+        assert_eq!(monitor.take_pending(), [4, -1, 2, 0]);
+        assert_eq!(monitor.take_pending(), [0; 4]);
+    }
+
+    #[test]
+    fn first_position_read_only_sets_baseline() {
+        let mut monitor = EncoderMonitor::new(MonitorMode::Position);
+        monitor.record_positions([100, -5, 0, 7]);
+        assert_eq!(monitor.take_pending(), [0; 4]);
+        monitor.record_positions([102, -6, 0, 7]);
+        assert_eq!(monitor.take_pending(), [2, -1, 0, 0]);
+    }
+
+    #[test]
+    fn resync_discards_baseline() {
+        let mut monitor = EncoderMonitor::new(MonitorMode::Position);
+        monitor.record_positions([500, 500, 500, 500]);
+        // Board reset: positions jump back to zero.
+        monitor.resync();
+        monitor.record_positions([0, 0, 0, 0]);
+        assert_eq!(monitor.take_pending(), [0; 4]);
+    }
+
+    #[test]
+    fn position_wraparound_is_a_small_step() {
+        let mut monitor = EncoderMonitor::new(MonitorMode::Position);
+        monitor.record_positions([i32::MAX, 0, 0, 0]);
+        monitor.record_positions([i32::MIN, 0, 0, 0]);
+        assert_eq!(monitor.take_pending(), [1, 0, 0, 0]);
+    }
+
+    #[test]
+    fn delta_poll_survives_bus_failure() {
+        let sim = SimulatedBoard::new();
+        let mut board = sim.board();
+        let mut monitor = EncoderMonitor::new(MonitorMode::Delta);
+
+        sim.turn(0, 2);
+        sim.turn(3, -1);
+        // Encoders 0 and 1 read fine (two transactions each), then the
+        // address phase for encoder 2 fails.
+        sim.fail_transactions(4, 1);
+        assert!(block_on(monitor.poll(&mut board)).is_err());
+        assert_eq!(sim.pending_delta(0), 0);
+
+        sim.turn(0, 1);
+        assert_eq!(block_on(monitor.poll(&mut board)).unwrap(), [3, 0, 0, -1]);
+        assert_eq!(block_on(monitor.poll(&mut board)).unwrap(), [0; 4]);
+    }
+
+    #[test]
+    fn discard_drops_only_masked_movement() {
+        for mode in [MonitorMode::Delta, MonitorMode::Position] {
+            let sim = SimulatedBoard::new();
+            let mut board = sim.board();
+            let mut monitor = EncoderMonitor::new(mode);
+            block_on(monitor.poll(&mut board)).unwrap();
+
+            sim.turn(1, 4);
+            sim.turn(3, 20);
+            block_on(monitor.discard(&mut board, 0b1000)).unwrap();
+            sim.turn(2, -1);
+            assert_eq!(
+                block_on(monitor.poll(&mut board)).unwrap(),
+                [0, 4, -1, 0],
+                "{mode:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn position_poll_tracks_movement_after_baseline() {
+        let sim = SimulatedBoard::new();
+        let mut board = sim.board();
+        let mut monitor = EncoderMonitor::new(MonitorMode::Position);
+
+        sim.turn(1, 50);
+        assert_eq!(block_on(monitor.poll(&mut board)).unwrap(), [0; 4]);
+        sim.turn(1, -3);
+        sim.turn(2, 1);
+        assert_eq!(block_on(monitor.poll(&mut board)).unwrap(), [0, -3, 1, 0]);
+    }
+}
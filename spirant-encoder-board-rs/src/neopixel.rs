@@ -0,0 +1,90 @@
+//! Colour type for the per-encoder NeoPixels.
+//!
+//! The board's NeoPixels are driven through the Seesaw NeoPixel module;
+//! [`QuadEncoderBoard`](crate::QuadEncoderBoard) keeps a local frame of
+//! [`Rgb`] values and pushes it to the chip on
+//! [`show()`](crate::QuadEncoderBoard::show). Colour arithmetic lives here
+//! so it can be tested on the host.
+
+/// 8-bit-per-channel RGB colour.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Rgb {
+    /// Red channel.
+    pub r: u8,
+    /// Green channel.
+    pub g: u8,
+    /// Blue channel.
+    pub b: u8,
+}
+
+impl Rgb {
+    /// All channels off.
+    pub const OFF: Self = Self::new(0, 0, 0);
+    /// All channels at full intensity.
+    pub const WHITE: Self = Self::new(255, 255, 255);
+
+    /// Create a colour from its channels.
+    pub const fn new(r: u8, g: u8, b: u8) -> Self {
+        Self { r, g, b }
+    }
+
+    /// Scale every channel by `brightness / 255`.
+    ///
+    /// A brightness of 255 returns the colour unchanged; 0 turns it off.
+    pub const fn scale(self, brightness: u8) -> Self {
+        let k = brightness as u16 + 1;
+        Self {
+            r: ((self.r as u16 * k) >> 8) as u8,
+            g: ((self.g as u16 * k) >> 8) as u8,
+            b: ((self.b as u16 * k) >> 8) as u8,
+        }
+    }
+
+    /// Linear blend from `self` (at `t = 0`) to `other` (at `t = 255`).
+    ///
+    /// Handy for colouring a knob by its parameter's position in range.
+    pub const fn blend(self, other: Self, t: u8) -> Self {
+        const fn mix(a: u8, b: u8, t: u8) -> u8 {
+            let t = t as i32;
+            (a as i32 + (b as i32 - a as i32) * t / 255) as u8
+        }
+        Self {
+            r: mix(self.r, other.r, t),
+            g: mix(self.g, other.g, t),
+            b: mix(self.b, other.b, t),
+        }
+    }
+
+    /// Wire order expected by the board's NeoPixels (GRB).
+    pub(crate) const fn to_grb(self) -> [u8; 3] {
+        [self.g, self.r, self.b]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scale_bounds() {
+        let c = Rgb::new(200, 100, 1);
+        assert_eq!(c.scale(255), c);
+        assert_eq!(c.scale(0), Rgb::OFF);
+        assert_eq!(c.scale(127), Rgb::new(100, 50, 0));
+    }
+
+    #[test]
+    fn blend_endpoints_and_midpoint() {
+        let a = Rgb::new(0, 0, 255);
+        let b = Rgb::new(255, 0, 0);
+        assert_eq!(a.blend(b, 0), a);
+        assert_eq!(a.blend(b, 255), b);
+        assert_eq!(a.blend(b, 51), Rgb::new(51, 0, 204));
+    }
+
+    #[test]
+    fn wire_order_is_grb() {
+        assert_eq!(Rgb::new(1, 2, 3).to_grb(), [2, 1, 3]);
+    }
+}
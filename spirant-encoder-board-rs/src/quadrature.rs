@@ -0,0 +1,242 @@
+//! Quadrature decoding for encoders wired straight to GPIOs.
+//!
+//! [`QuadratureDecoder`] turns successive samples of an encoder's A and B
+//! lines into detents. It is a pure state machine — no pins, no timers —
+//! so it can be tested on the host against recorded A/B sequences. The
+//! async pin backend is `gpio::GpioEncoders` (feature **`gpio`**).
+
+/// Where an encoder's detents fall in its quadrature cycle.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum StepMode {
+    /// One detent per full cycle of four transitions (most mechanical
+    /// encoders).
+    #[default]
+    Full,
+    /// One detent every two transitions, at the rest state and at its
+    /// complement.
+    Half,
+}
+
+impl StepMode {
+    /// Transitions between detents.
+    const fn transitions(self) -> i8 {
+        match self {
+            StepMode::Full => 4,
+            StepMode::Half => 2,
+        }
+    }
+}
+
+/// Line levels packed as `(a << 1) | b`.
+const fn levels(a: bool, b: bool) -> u8 {
+    ((a as u8) << 1) | b as u8
+}
+
+/// Direction of a transition between two line states: `Some(1)` when A
+/// leads B, `Some(-1)` when B leads A, `Some(0)` for no change, and `None`
+/// when both lines changed at once.
+const fn direction(from: u8, to: u8) -> Option<i8> {
+    match (from, to) {
+        _ if from == to => Some(0),
+        (0b00, 0b10) | (0b10, 0b11) | (0b11, 0b01) | (0b01, 0b00) => Some(1),
+        (0b00, 0b01) | (0b01, 0b11) | (0b11, 0b10) | (0b10, 0b00) => Some(-1),
+        _ => None,
+    }
+}
+
+/// Quadrature state machine for one encoder.
+///
+/// Transitions are accumulated until the lines come back to a detent
+/// state, and a detent is counted only if a full detent's worth of
+/// transitions in one direction was seen on the way. Contact bounce
+/// (a line flipping back and forth) therefore cancels out, and a sample in
+/// which both lines changed — a missed state — is counted as an illegal
+/// transition and discards the partial step instead of guessing its
+/// direction.
+///
+/// Counted detents are added both to the absolute position and to a
+/// pending delta, which [`take_delta`](Self::take_delta) returns and
+/// resets like the board's delta register.
+///
+/// # Example
+///
+/// ```
+/// use encoder_driver::{QuadratureDecoder, StepMode};
+///
+/// // Both lines high at rest (pull-ups, contacts open).
+/// let mut decoder = QuadratureDecoder::new(StepMode::Full, true, true);
+/// for (a, b) in [(false, true), (false, false), (true, false), (true, true)] {
+///     decoder.update(a, b);
+/// }
+/// assert_eq!(decoder.take_delta(), 1);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct QuadratureDecoder {
+    mode: StepMode,
+    /// Line state at a detent.
+    rest: u8,
+    /// Line state of the previous sample.
+    state: u8,
+    /// Transitions since the last detent state (positive = A leads).
+    phase: i8,
+    position: i32,
+    delta: i32,
+    illegal: u32,
+}
+
+impl QuadratureDecoder {
+    /// Create a decoder for an encoder resting at a detent with its lines
+    /// at levels `a` and `b`.
+    pub const fn new(mode: StepMode, a: bool, b: bool) -> Self {
+        let rest = levels(a, b);
+        Self {
+            mode,
+            rest,
+            state: rest,
+            phase: 0,
+            position: 0,
+            delta: 0,
+            illegal: 0,
+        }
+    }
+
+    /// Returns the step mode.
+    pub fn mode(&self) -> StepMode {
+        self.mode
+    }
+
+    /// Feed one sample of the A and B lines.
+    ///
+    /// Returns the detents counted by this sample: `1` (A leads B), `-1`
+    /// or `0`.
+    pub fn update(&mut self, a: bool, b: bool) -> i32 {
+        let next = levels(a, b);
+        let Some(dir) = direction(self.state, next) else {
+            self.illegal = self.illegal.saturating_add(1);
+            self.state = next;
+            self.phase = 0;
+            return 0;
+        };
+        self.state = next;
+        if dir == 0 {
+            return 0;
+        }
+        self.phase = self.phase.saturating_add(dir);
+
+        if !self.at_detent() {
+            return 0;
+        }
+        let steps = self.mode.transitions();
+        let detent = if self.phase >= steps {
+            1
+        } else if self.phase <= -steps {
+            -1
+        } else {
+            0
+        };
+        self.phase = 0;
+        self.position = self.position.wrapping_add(detent);
+        self.delta = self.delta.saturating_add(detent);
+        detent
+    }
+
+    /// Absolute position in detents.
+    pub fn position(&self) -> i32 {
+        self.position
+    }
+
+    /// Set the absolute position without touching the pending delta.
+    pub fn set_position(&mut self, position: i32) {
+        self.position = position;
+    }
+
+    /// Return the detents counted since the previous call and reset them.
+    pub fn take_delta(&mut self) -> i32 {
+        core::mem::take(&mut self.delta)
+    }
+
+    /// Number of samples rejected because both lines changed at once.
+    pub fn illegal_transitions(&self) -> u32 {
+        self.illegal
+    }
+
+    /// Returns `true` if the lines are in a detent state.
+    fn at_detent(&self) -> bool {
+        match self.mode {
+            StepMode::Full => self.state == self.rest,
+            StepMode::Half => self.state == self.rest || self.state == self.rest ^ 0b11,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Feed a recorded sequence such as `"11 01 00 10 11"` (A then B per
+    /// sample) and return the total counted.
+    fn feed(decoder: &mut QuadratureDecoder, recording: &str) -> i32 {
+        recording
+            .split_whitespace()
+            .map(|s| {
+                let mut bits = s.chars().map(|c| c == '1');
+                decoder.update(bits.next().unwrap(), bits.next().unwrap())
+            })
+            .sum()
+    }
+
+    fn full() -> QuadratureDecoder {
+        QuadratureDecoder::new(StepMode::Full, true, true)
+    }
+
+    #[test]
+    fn full_step_counts_one_per_cycle() {
+        let mut decoder = full();
+        assert_eq!(feed(&mut decoder, "01 00 10 11 01 00 10 11"), 2);
+        assert_eq!(feed(&mut decoder, "10 00 01 11"), -1);
+        assert_eq!(decoder.position(), 1);
+        assert_eq!(decoder.take_delta(), 1);
+        assert_eq!(decoder.take_delta(), 0);
+    }
+
+    #[test]
+    fn half_step_counts_at_both_rest_states() {
+        let mut decoder = QuadratureDecoder::new(StepMode::Half, true, true);
+        assert_eq!(feed(&mut decoder, "01 00 10 11"), 2);
+        assert_eq!(feed(&mut decoder, "10 00"), -1);
+        assert_eq!(decoder.position(), 1);
+    }
+
+    #[test]
+    fn contact_bounce_cancels_out() {
+        let mut decoder = full();
+        // The lines chatter leaving the detent, mid-cycle and arriving.
+        assert_eq!(feed(&mut decoder, "01 11 01 11 01 00 10 00 10 11 10 11"), 1);
+        // A partial turn that springs back to the same detent.
+        assert_eq!(feed(&mut decoder, "01 00 01 11"), 0);
+        assert_eq!(decoder.illegal_transitions(), 0);
+    }
+
+    #[test]
+    fn illegal_transitions_are_rejected() {
+        let mut decoder = full();
+        // 01 -> 10 skips a state; the partial step is dropped.
+        assert_eq!(feed(&mut decoder, "01 10 11"), 0);
+        assert_eq!(decoder.illegal_transitions(), 1);
+        // The decoder realigns at the detent and counts the next cycle.
+        assert_eq!(feed(&mut decoder, "01 00 10 11"), 1);
+    }
+
+    #[test]
+    fn low_rest_state_and_position() {
+        // Encoders whose lines rest low have their detents at 00.
+        let mut decoder = QuadratureDecoder::new(StepMode::Full, false, false);
+        assert_eq!(feed(&mut decoder, "10 11 01 00"), 1);
+        decoder.set_position(-20);
+        assert_eq!(feed(&mut decoder, "01 11 10 00"), -1);
+        assert_eq!(decoder.position(), -21);
+        assert_eq!(decoder.take_delta(), 0);
+    }
+}
@@ -0,0 +1,346 @@
+//! I2C fault recovery and hot-plug handling.
+//!
+//! [`ConnectionSupervisor`] wraps board operations with bounded retries
+//! and exponential backoff, calls a [`BusRecovery`] hook between attempts,
+//! and declares the board lost once the retries are exhausted. A lost
+//! board is brought back with [`reconnect`](ConnectionSupervisor::reconnect),
+//! which re-probes it and replays its configuration. Both transitions are
+//! reported as [`ConnectionEvent`]s for the firmware to display.
+
+use core::future::Future;
+
+use embassy_time::Duration;
+use embedded_hal_async::delay::DelayNs;
+use embedded_hal_async::i2c::I2c;
+
+use crate::encoder_board::QuadEncoderBoard;
+use crate::error::EncoderError;
+use crate::monitor::EncoderMonitor;
+use crate::status::DeviceInfo;
+
+/// Retry and reconnect timings for [`ConnectionSupervisor`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct RecoveryPolicy {
+    /// Retries after the first failed attempt before the board is declared
+    /// lost.
+    pub max_retries: u8,
+    /// Pause before the first retry; doubled for each further retry.
+    pub initial_backoff: Duration,
+    /// Upper bound for the pause between retries.
+    pub max_backoff: Duration,
+    /// Suggested interval between [`reconnect`](ConnectionSupervisor::reconnect)
+    /// attempts while the board is lost. The supervisor does not wait by
+    /// itself; the caller schedules the attempts.
+    pub reprobe_interval: Duration,
+}
+
+impl Default for RecoveryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 3,
+            initial_backoff: Duration::from_millis(2),
+            max_backoff: Duration::from_millis(50),
+            reprobe_interval: Duration::from_millis(500),
+        }
+    }
+}
+
+/// Hook that tries to free a stuck I2C bus between retries.
+///
+/// A typical implementation reconfigures SCL as a GPIO and clocks it until
+/// the device holding SDA LOW releases it, then issues a STOP condition.
+pub trait BusRecovery {
+    /// Attempt to return the bus to idle.
+    fn recover_bus(&mut self) -> impl Future<Output = ()>;
+}
+
+/// [`BusRecovery`] that does nothing, for buses that recover on their own.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NoBusRecovery;
+
+impl BusRecovery for NoBusRecovery {
+    async fn recover_bus(&mut self) {}
+}
+
+/// Whether the supervised board is usable.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum ConnectionState {
+    /// Operations are passed through to the board.
+    Connected,
+    /// Retries were exhausted; operations fail with
+    /// [`EncoderError::Disconnected`] until a reconnect succeeds.
+    Lost,
+}
+
+/// A connection-state transition, returned by
+/// [`ConnectionSupervisor::take_event`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum ConnectionEvent {
+    /// The board stopped responding.
+    Lost,
+    /// The board answered a re-probe and was reconfigured.
+    Reconnected(DeviceInfo),
+}
+
+/// Retry, loss detection and reconnection for one [`QuadEncoderBoard`].
+///
+/// # Example
+///
+/// ```no_run
+/// use encoder_driver::{ConnectionSupervisor, RecoveryPolicy};
+///
+/// let mut supervisor = ConnectionSupervisor::new(RecoveryPolicy::default());
+///
+/// let deltas = supervisor
+///     .run(&mut board, async |b| monitor.poll(b).await)
+///     .await;
+/// if let Some(event) = supervisor.take_event() {
+///     // Show "encoders lost" / "encoders back" on the display.
+/// }
+/// ```
+#[derive(Debug)]
+pub struct ConnectionSupervisor<R = NoBusRecovery> {
+    policy: RecoveryPolicy,
+    recovery: R,
+    state: ConnectionState,
+    /// Latest transition not yet taken by the caller.
+    event: Option<ConnectionEvent>,
+}
+
+impl ConnectionSupervisor {
+    /// Create a supervisor without a bus-recovery hook.
+    pub fn new(policy: RecoveryPolicy) -> Self {
+        Self::with_recovery(policy, NoBusRecovery)
+    }
+}
+
+impl<R: BusRecovery> ConnectionSupervisor<R> {
+    /// Create a supervisor that calls `recovery` before every retry and
+    /// reconnect attempt.
+    pub fn with_recovery(policy: RecoveryPolicy, recovery: R) -> Self {
+        Self {
+            policy,
+            recovery,
+            state: ConnectionState::Connected,
+            event: None,
+        }
+    }
+
+    /// Returns the retry and reconnect timings.
+    pub fn policy(&self) -> &RecoveryPolicy {
+        &self.policy
+    }
+
+    /// Returns the current connection state.
+    pub fn state(&self) -> ConnectionState {
+        self.state
+    }
+
+    /// Returns `true` unless the board has been declared lost.
+    pub fn is_connected(&self) -> bool {
+        self.state == ConnectionState::Connected
+    }
+
+    /// Return and clear the latest connection-state transition.
+    pub fn take_event(&mut self) -> Option<ConnectionEvent> {
+        self.event.take()
+    }
+
+    /// Run `op` against `board`, retrying I2C failures.
+    ///
+    /// Up to [`max_retries`](RecoveryPolicy::max_retries) retries follow a
+    /// failed attempt, each preceded by the bus-recovery hook and a
+    /// doubling backoff (timed by the board's delay provider). Errors other
+    /// than [`EncoderError::I2c`] are returned at once, since retrying
+    /// cannot fix them.
+    ///
+    /// # Errors
+    /// * [`EncoderError::Disconnected`] without touching the bus while the
+    ///   board is lost
+    /// * The last error once retries are exhausted; the board is then
+    ///   declared lost and [`ConnectionEvent::Lost`] is raised
+    pub async fn run<I2C, D, T>(
+        &mut self,
+        board: &mut QuadEncoderBoard<I2C, D>,
+        mut op: impl AsyncFnMut(&mut QuadEncoderBoard<I2C, D>) -> Result<T, EncoderError<I2C::Error>>,
+    ) -> Result<T, EncoderError<I2C::Error>>
+    where
+        I2C: I2c,
+        D: DelayNs,
+    {
+        if self.state == ConnectionState::Lost {
+            return Err(EncoderError::Disconnected);
+        }
+
+        let mut backoff = self.policy.initial_backoff;
+        let mut retries = 0;
+        loop {
+            match op(board).await {
+                Ok(value) => return Ok(value),
+                Err(EncoderError::I2c(e)) if retries >= self.policy.max_retries => {
+                    self.state = ConnectionState::Lost;
+                    self.event = Some(ConnectionEvent::Lost);
+                    return Err(EncoderError::I2c(e));
+                }
+                Err(EncoderError::I2c(_)) => {}
+                Err(e) => return Err(e),
+            }
+
+            retries += 1;
+            self.recovery.recover_bus().await;
+            board
+                .seesaw_mut()
+                .delay_ms(backoff.as_millis() as u32)
+                .await;
+            backoff = (backoff * 2).min(self.policy.max_backoff);
+        }
+    }
+
+    /// Try to bring the board back after it was lost (or unplugged).
+    ///
+    /// Runs the bus-recovery hook, re-probes the board, resets it and
+    /// replays its configuration with
+    /// [`QuadEncoderBoard::reinitialise`], and resyncs `monitor` so the
+    /// restarted positions are not mistaken for movement. On success the
+    /// board is connected again and [`ConnectionEvent::Reconnected`] is
+    /// raised.
+    ///
+    /// # Errors
+    /// Any probe or configuration error; the state is left unchanged, so
+    /// call again after [`reprobe_interval`](RecoveryPolicy::reprobe_interval).
+    pub async fn reconnect<I2C, D>(
+        &mut self,
+        board: &mut QuadEncoderBoard<I2C, D>,
+        monitor: &mut EncoderMonitor,
+    ) -> Result<DeviceInfo, EncoderError<I2C::Error>>
+    where
+        I2C: I2c,
+        D: DelayNs,
+    {
+        self.recovery.recover_bus().await;
+        let info = board.probe().await?;
+        board.reinitialise().await?;
+        monitor.resync();
+
+        self.state = ConnectionState::Connected;
+        self.event = Some(ConnectionEvent::Reconnected(info));
+        Ok(info)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::monitor::MonitorMode;
+    use crate::sim::{block_on, SimulatedBoard};
+    use crate::Rgb;
+
+    /// Counts hook invocations.
+    #[derive(Default)]
+    struct CountingRecovery(usize);
+
+    impl BusRecovery for CountingRecovery {
+        async fn recover_bus(&mut self) {
+            self.0 += 1;
+        }
+    }
+
+    #[test]
+    fn transient_failures_are_retried_with_backoff() {
+        let sim = SimulatedBoard::new();
+        let mut board = sim.board();
+        let mut supervisor = ConnectionSupervisor::with_recovery(
+            RecoveryPolicy::default(),
+            CountingRecovery::default(),
+        );
+
+        sim.turn(0, 1);
+        sim.fail_transactions(0, 2);
+        let before = sim.now();
+        let position = block_on(supervisor.run(&mut board, async |b| b.read_position(0).await));
+
+        assert_eq!(position.unwrap(), 1);
+        assert!(supervisor.is_connected());
+        assert_eq!(supervisor.take_event(), None);
+        assert_eq!(supervisor.recovery.0, 2);
+        // 2 ms + 4 ms of backoff, plus the read delay.
+        assert!(sim.now() - before >= core::time::Duration::from_millis(6));
+    }
+
+    #[test]
+    fn exhausted_retries_declare_board_lost() {
+        let sim = SimulatedBoard::new();
+        let mut board = sim.board();
+        let mut supervisor = ConnectionSupervisor::new(RecoveryPolicy::default());
+
+        sim.unplug();
+        let result = block_on(supervisor.run(&mut board, async |b| b.read_position(0).await));
+        assert!(matches!(result, Err(EncoderError::I2c(_))));
+        assert_eq!(supervisor.state(), ConnectionState::Lost);
+        assert_eq!(supervisor.take_event(), Some(ConnectionEvent::Lost));
+
+        // No bus traffic while lost.
+        sim.plug_in();
+        sim.clear_transfers();
+        let result = block_on(supervisor.run(&mut board, async |b| b.read_position(0).await));
+        assert!(matches!(result, Err(EncoderError::Disconnected)));
+        assert!(sim.transfers().is_empty());
+    }
+
+    #[test]
+    fn invalid_encoder_is_not_retried() {
+        let sim = SimulatedBoard::new();
+        let mut board = sim.board();
+        let mut supervisor = ConnectionSupervisor::new(RecoveryPolicy::default());
+
+        let result = block_on(supervisor.run(&mut board, async |b| b.read_position(9).await));
+        assert!(matches!(result, Err(EncoderError::InvalidEncoder)));
+        assert!(supervisor.is_connected());
+    }
+
+    #[test]
+    fn reconnect_restores_configuration() {
+        let sim = SimulatedBoard::new();
+        let mut board = sim.board();
+        let mut monitor = EncoderMonitor::new(MonitorMode::Position);
+        let mut supervisor = ConnectionSupervisor::new(RecoveryPolicy::default());
+
+        block_on(async {
+            board.configure_switches().await.unwrap();
+            board.enable_switch_interrupts().await.unwrap();
+            board.configure_pixels().await.unwrap();
+            board.set_pixel(2, Rgb::new(0, 0, 255)).unwrap();
+            board.show().await.unwrap();
+            board.set_interrupt_mask(0b0101).await.unwrap();
+        });
+        sim.turn(0, 40);
+        block_on(monitor.poll(&mut board)).unwrap();
+
+        sim.unplug();
+        let _ = block_on(supervisor.run(&mut board, async |b| monitor.poll(b).await));
+        assert_eq!(supervisor.take_event(), Some(ConnectionEvent::Lost));
+        assert!(block_on(supervisor.reconnect(&mut board, &mut monitor)).is_err());
+        assert!(!supervisor.is_connected());
+
+        sim.plug_in();
+        let info = block_on(supervisor.reconnect(&mut board, &mut monitor)).unwrap();
+        assert_eq!(
+            supervisor.take_event(),
+            Some(ConnectionEvent::Reconnected(info))
+        );
+        assert!(sim.interrupt_enabled(0) && sim.interrupt_enabled(2));
+        assert!(!sim.interrupt_enabled(1));
+        assert_ne!(sim.gpio_interrupts(), 0);
+        assert_eq!(sim.pixel(2), Rgb::new(0, 0, 255));
+
+        // Positions restarted at zero; that is not movement.
+        let deltas = block_on(supervisor.run(&mut board, async |b| monitor.poll(b).await));
+        assert_eq!(deltas.unwrap(), [0; 4]);
+        sim.turn(0, 1);
+        let deltas = block_on(supervisor.run(&mut board, async |b| monitor.poll(b).await));
+        assert_eq!(deltas.unwrap(), [1, 0, 0, 0]);
+    }
+}
@@ -1,60 +1,44 @@
-//! Seesaw register address constants for the Adafruit Quad Rotary Encoder Breakout.
+//! Board constants for the Adafruit Quad Rotary Encoder Breakout.
 //!
-//! The Seesaw firmware uses a two-byte register addressing scheme:
-//! - Byte 1: Module ID
-//! - Byte 2: Register offset within the module
-//!
-//! For encoder-specific registers, the encoder index (0–3) is OR'd with
-//! the base register offset: `[MODULE_ID, BASE_REGISTER | encoder_index]`.
+//! Seesaw module IDs and register offsets live in
+//! [`seesaw_driver::registers`]; this module only holds what is specific
+//! to this board: its address, product number and pin assignments.
 
 // ---------------------------------------------------------------------------
-// Module IDs
+// Identification
 // ---------------------------------------------------------------------------
 
-/// Seesaw status module identifier.
-pub const MODULE_GPIO: u8 = 0x01;
-
-/// Seesaw encoder module identifier.
-pub const MODULE_ENCODER: u8 = 0x11;
-
-// ---------------------------------------------------------------------------
-// Status module registers
-// ---------------------------------------------------------------------------
+/// Default I2C address for the Adafruit Quad Rotary Encoder Breakout.
+///
+/// Many other Seesaw breakouts default to 0x36; this board does not.
+/// [`QuadEncoderBoard::probe`](crate::QuadEncoderBoard::probe) confirms
+/// which device answers at an address.
+pub const DEFAULT_ADDRESS: u8 = 0x49;
 
-/// Interrupt flag register (32-bit, read-only).
-/// Reading this register clears all interrupt flags and resets the INT pin.
-pub const STATUS_INTFLAG: u8 = 0x0A;
+/// Adafruit product number reported in the VERSION register.
+pub const QUAD_ENCODER_PRODUCT_ID: u16 = 5752;
 
 // ---------------------------------------------------------------------------
-// Encoder module registers (base addresses)
+// Encoders and switches
 // ---------------------------------------------------------------------------
 
-/// Base register for reading/writing absolute encoder position (32-bit signed).
-/// Per-encoder address: `ENCODER_POSITION | encoder_index`.
-pub const ENCODER_POSITION: u8 = 0x30;
-
-/// Base register for reading encoder delta since last read (not used in v1).
-#[allow(dead_code)]
-pub const ENCODER_DELTA: u8 = 0x40;
+/// Number of rotary encoders on the board.
+pub const ENCODER_COUNT: usize = 4;
 
-/// Register for enabling per-encoder interrupts.
-/// Per-encoder address: `ENCODER_INT_SET | encoder_index`.
-pub const ENCODER_INT_SET: u8 = 0x10;
+/// Seesaw GPIO pin of each encoder's push switch, indexed by encoder.
+/// Switches are active-low and need the internal pull-up.
+pub const SWITCH_PINS: [u8; ENCODER_COUNT] = [12, 14, 17, 9];
 
-/// Register for disabling per-encoder interrupts (not used in v1).
-#[allow(dead_code)]
-pub const ENCODER_INT_CLR: u8 = 0x20;
+/// Bitmask of all switch pins in the GPIO bulk registers.
+pub const SWITCH_PIN_MASK: u32 =
+    (1 << SWITCH_PINS[0]) | (1 << SWITCH_PINS[1]) | (1 << SWITCH_PINS[2]) | (1 << SWITCH_PINS[3]);
 
 // ---------------------------------------------------------------------------
-// Protocol constants
+// NeoPixels
 // ---------------------------------------------------------------------------
 
-/// Required delay in microseconds between I2C write and read operations
-/// per Seesaw firmware specification.
-pub const SEESAW_DELAY_US: u64 = 125;
+/// Seesaw GPIO pin connected to the board's NeoPixel chain.
+pub const NEOPIXEL_DATA_PIN: u8 = 18;
 
-/// Default I2C address for the Adafruit Quad Rotary Encoder Breakout.
-pub const DEFAULT_ADDRESS: u8 = 0x49;
-
-/// Number of rotary encoders on the board.
-pub const ENCODER_COUNT: usize = 4;
+/// Bytes per NeoPixel (GRB, no white channel).
+pub const NEOPIXEL_BYTES_PER_PIXEL: usize = 3;
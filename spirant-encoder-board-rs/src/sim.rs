@@ -0,0 +1,694 @@
+//! Host-side simulation of the quad encoder board.
+//!
+//! [`SimulatedBoard`] models the Seesaw register map of the Adafruit Quad
+//! Rotary Encoder Breakout in memory: encoder positions and deltas,
+//! per-encoder interrupt enables, the push-switch GPIOs with their
+//! self-clearing INTFLAG register, the NeoPixel buffer and the status module. Its
+//! [`SimI2c`] bus implements [`embedded_hal_async::i2c::I2c`], so a real
+//! [`QuadEncoderBoard`] can be driven against it on Linux.
+//!
+//! Time is virtual: [`SimDelay`] advances the simulation clock instead of
+//! sleeping, and every bus transfer is stamped with it so tests can check
+//! the Seesaw read delay was respected.
+//!
+//! Enabled by the **`sim`** feature (requires `std`) and always available
+//! to this crate's own unit tests. With the `blocking` feature the bus and
+//! delay also implement the blocking `embedded-hal` traits.
+
+extern crate std;
+
+use core::time::Duration;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::vec::Vec;
+
+use embedded_hal_async::delay::DelayNs;
+use embedded_hal_async::i2c::{ErrorKind, ErrorType, I2c, NoAcknowledgeSource, Operation};
+use seesaw_driver::registers::{
+    EncoderRegister, GpioRegister, Module, NeopixelRegister, Register, StatusRegister,
+};
+
+use crate::encoder_board::QuadEncoderBoard;
+use crate::neopixel::Rgb;
+use crate::registers::{
+    DEFAULT_ADDRESS, ENCODER_COUNT, NEOPIXEL_BYTES_PER_PIXEL, QUAD_ENCODER_PRODUCT_ID, SWITCH_PINS,
+};
+
+/// Minimum pause between addressing a register and reading it.
+const READ_DELAY: Duration = Duration::from_micros(125);
+
+/// HW_ID reported by the simulated chip (ATtiny817).
+const SIM_HW_ID: u8 = 0x87;
+
+/// Build date code reported in the simulated VERSION register.
+const SIM_DATE_CODE: u16 = 0x2A1C;
+
+/// Everything in the simulation completes without waiting, so a busy poll
+/// is enough to drive the async driver API from a synchronous `#[test]`.
+pub use embassy_futures::block_on;
+
+// ---------------------------------------------------------------------------
+// Transaction log
+// ---------------------------------------------------------------------------
+
+/// Direction of a logged bus transfer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TransferKind {
+    /// Bytes written: register address followed by any payload.
+    Write(Vec<u8>),
+    /// Number of bytes read from the previously addressed register.
+    Read(usize),
+}
+
+/// One I2C transaction seen by the simulated device.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Transfer {
+    /// Virtual time at which the transaction happened.
+    pub at: Duration,
+    /// What was transferred.
+    pub kind: TransferKind,
+    /// `false` if the transaction was failed by fault injection.
+    pub ok: bool,
+}
+
+// ---------------------------------------------------------------------------
+// Device state
+// ---------------------------------------------------------------------------
+
+/// Register-level state of the simulated board.
+#[derive(Debug, Clone)]
+struct SimState {
+    address: u8,
+    /// `false` while unplugged: every transaction is NACKed.
+    connected: bool,
+    now: Duration,
+    positions: [i32; ENCODER_COUNT],
+    deltas: [i32; ENCODER_COUNT],
+    encoder_int: [bool; ENCODER_COUNT],
+    pressed: [bool; ENCODER_COUNT],
+    gpio_int_enabled: u32,
+    /// Pending GPIO pin-change flags (bit N = Seesaw pin N).
+    gpio_flags: u32,
+    /// Pending encoder movement interrupts (bit N = encoder N). As on the
+    /// real firmware they hold INT LOW without showing in INTFLAG, and are
+    /// released by reading the encoder's delta.
+    encoder_flags: u8,
+    pixel_pin: u8,
+    pixel_speed: u8,
+    pixel_buf: Vec<u8>,
+    shown: Vec<u8>,
+    show_count: usize,
+    /// Register addressed by the last write, and when.
+    addressed: Option<([u8; 2], Duration)>,
+    log: Vec<Transfer>,
+    timing_violations: usize,
+    fail_after: usize,
+    fail_count: usize,
+}
+
+impl SimState {
+    fn new() -> Self {
+        Self {
+            address: DEFAULT_ADDRESS,
+            connected: true,
+            now: Duration::ZERO,
+            positions: [0; ENCODER_COUNT],
+            deltas: [0; ENCODER_COUNT],
+            encoder_int: [false; ENCODER_COUNT],
+            pressed: [false; ENCODER_COUNT],
+            gpio_int_enabled: 0,
+            gpio_flags: 0,
+            encoder_flags: 0,
+            pixel_pin: 0,
+            pixel_speed: 0,
+            pixel_buf: Vec::new(),
+            shown: Vec::new(),
+            show_count: 0,
+            addressed: None,
+            log: Vec::new(),
+            timing_violations: 0,
+            fail_after: 0,
+            fail_count: 0,
+        }
+    }
+
+    /// Power-on state after a software reset. The bus-side bookkeeping
+    /// (clock, log, fault injection) survives.
+    fn reset(&mut self) {
+        *self = Self {
+            address: self.address,
+            now: self.now,
+            pressed: self.pressed,
+            log: core::mem::take(&mut self.log),
+            timing_violations: self.timing_violations,
+            fail_after: self.fail_after,
+            fail_count: self.fail_count,
+            ..Self::new()
+        };
+    }
+
+    /// Pin levels as seen by GPIO_BULK (switches are active-low).
+    fn gpio_levels(&self) -> u32 {
+        let mut levels = u32::MAX;
+        for (encoder, &pin) in SWITCH_PINS.iter().enumerate() {
+            if self.pressed[encoder] {
+                levels &= !(1 << pin);
+            }
+        }
+        levels
+    }
+
+    fn int_asserted(&self) -> bool {
+        self.encoder_flags != 0 || self.gpio_flags != 0
+    }
+
+    /// Consume one unit of fault injection; returns `true` if this
+    /// transaction must fail.
+    fn inject_fault(&mut self) -> bool {
+        if self.fail_after > 0 {
+            self.fail_after -= 1;
+            false
+        } else if self.fail_count > 0 {
+            self.fail_count -= 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn handle_write(&mut self, bytes: &[u8]) {
+        if bytes.len() < 2 {
+            return;
+        }
+        let register = [bytes[0], bytes[1]];
+        let data = &bytes[2..];
+
+        if data.is_empty() && register != NeopixelRegister::Show.address().to_bytes() {
+            // Address phase of a read.
+            self.addressed = Some((register, self.now));
+            return;
+        }
+        self.addressed = None;
+
+        let [module, offset] = register;
+        if module == Module::Status as u8 {
+            if offset == StatusRegister::SwRst.offset() {
+                self.reset();
+            }
+        } else if module == Module::Gpio as u8 {
+            let mask = be_u32(data);
+            match offset {
+                o if o == GpioRegister::IntEnSet.offset() => self.gpio_int_enabled |= mask,
+                o if o == GpioRegister::IntEnClr.offset() => self.gpio_int_enabled &= !mask,
+                // Direction, pull and latch writes only configure the
+                // switch inputs, which the model treats as always pulled up.
+                _ => {}
+            }
+        } else if module == Module::Encoder as u8 {
+            let encoder = (offset & 0x0F) as usize;
+            if encoder >= ENCODER_COUNT {
+                return;
+            }
+            match offset & 0xF0 {
+                o if o == EncoderRegister::IntSet.offset() => self.encoder_int[encoder] = true,
+                o if o == EncoderRegister::IntClr.offset() => self.encoder_int[encoder] = false,
+                o if o == EncoderRegister::Position.offset() => {
+                    self.positions[encoder] = be_u32(data) as i32;
+                }
+                _ => {}
+            }
+        } else if module == Module::Neopixel as u8 {
+            match offset {
+                o if o == NeopixelRegister::Pin.offset() => self.pixel_pin = data[0],
+                o if o == NeopixelRegister::Speed.offset() => self.pixel_speed = data[0],
+                o if o == NeopixelRegister::BufLength.offset() && data.len() >= 2 => {
+                    let len = u16::from_be_bytes([data[0], data[1]]) as usize;
+                    self.pixel_buf = std::vec![0; len];
+                }
+                o if o == NeopixelRegister::Buf.offset() && data.len() >= 2 => {
+                    let start = u16::from_be_bytes([data[0], data[1]]) as usize;
+                    for (i, &b) in data[2..].iter().enumerate() {
+                        if let Some(slot) = self.pixel_buf.get_mut(start + i) {
+                            *slot = b;
+                        }
+                    }
+                }
+                o if o == NeopixelRegister::Show.offset() => {
+                    self.shown = self.pixel_buf.clone();
+                    self.show_count += 1;
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn handle_read(&mut self, buf: &mut [u8]) {
+        let Some((register, addressed_at)) = self.addressed.take() else {
+            buf.fill(0);
+            return;
+        };
+        if self.now.saturating_sub(addressed_at) < READ_DELAY {
+            self.timing_violations += 1;
+        }
+
+        let [module, offset] = register;
+        let value: Option<u32> = if module == Module::Status as u8 {
+            match offset {
+                o if o == StatusRegister::HwId.offset() => {
+                    buf.fill(0);
+                    if let Some(b) = buf.first_mut() {
+                        *b = SIM_HW_ID;
+                    }
+                    return;
+                }
+                o if o == StatusRegister::Version.offset() => {
+                    Some(((QUAD_ENCODER_PRODUCT_ID as u32) << 16) | SIM_DATE_CODE as u32)
+                }
+                o if o == StatusRegister::Options.offset() => Some(
+                    (1 << Module::Status as u32)
+                        | (1 << Module::Gpio as u32)
+                        | (1 << Module::Neopixel as u32)
+                        | (1 << Module::Encoder as u32),
+                ),
+                _ => None,
+            }
+        } else if module == Module::Gpio as u8 {
+            match offset {
+                o if o == GpioRegister::Bulk.offset() => Some(self.gpio_levels()),
+                o if o == GpioRegister::IntFlag.offset() => {
+                    // Self-clearing: reading releases INT for the switches.
+                    Some(core::mem::take(&mut self.gpio_flags))
+                }
+                _ => None,
+            }
+        } else if module == Module::Encoder as u8 {
+            let encoder = (offset & 0x0F) as usize;
+            match offset & 0xF0 {
+                o if o == EncoderRegister::Position.offset() && encoder < ENCODER_COUNT => {
+                    Some(self.positions[encoder] as u32)
+                }
+                o if o == EncoderRegister::Delta.offset() && encoder < ENCODER_COUNT => {
+                    self.encoder_flags &= !(1 << encoder);
+                    Some(core::mem::take(&mut self.deltas[encoder]) as u32)
+                }
+                _ => None,
+            }
+        } else {
+            None
+        };
+
+        let bytes = value.unwrap_or(0).to_be_bytes();
+        for (i, b) in buf.iter_mut().enumerate() {
+            *b = bytes.get(i).copied().unwrap_or(0);
+        }
+    }
+}
+
+fn be_u32(data: &[u8]) -> u32 {
+    let mut bytes = [0u8; 4];
+    let n = data.len().min(4);
+    bytes[..n].copy_from_slice(&data[..n]);
+    u32::from_be_bytes(bytes)
+}
+
+// ---------------------------------------------------------------------------
+// Public handles
+// ---------------------------------------------------------------------------
+
+/// A simulated quad encoder board.
+///
+/// Cloning yields another handle to the same device, so a test can keep
+/// one to turn knobs while a [`QuadEncoderBoard`] owns the [`SimI2c`] bus.
+///
+/// # Example
+///
+/// ```
+/// use encoder_driver::sim::{block_on, SimulatedBoard};
+///
+/// let sim = SimulatedBoard::new();
+/// let mut board = sim.board();
+///
+/// sim.turn(2, 5);
+/// assert_eq!(block_on(board.read_position(2)).unwrap(), 5);
+/// ```
+#[derive(Debug, Clone)]
+pub struct SimulatedBoard {
+    state: Arc<Mutex<SimState>>,
+}
+
+impl Default for SimulatedBoard {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SimulatedBoard {
+    /// A powered-on board at [`DEFAULT_ADDRESS`] with all knobs at zero.
+    pub fn new() -> Self {
+        Self {
+            state: Arc::new(Mutex::new(SimState::new())),
+        }
+    }
+
+    fn state(&self) -> MutexGuard<'_, SimState> {
+        self.state.lock().unwrap()
+    }
+
+    /// An I2C bus with this board attached.
+    pub fn bus(&self) -> SimI2c {
+        SimI2c {
+            state: self.state.clone(),
+        }
+    }
+
+    /// A delay provider that advances this board's virtual clock.
+    pub fn delay(&self) -> SimDelay {
+        SimDelay {
+            state: self.state.clone(),
+        }
+    }
+
+    /// A [`QuadEncoderBoard`] driving this simulation.
+    pub fn board(&self) -> QuadEncoderBoard<SimI2c, SimDelay> {
+        QuadEncoderBoard::with_delay(self.bus(), self.delay(), self.address())
+    }
+
+    /// Address the board answers on.
+    pub fn address(&self) -> u8 {
+        self.state().address
+    }
+
+    /// Move the board to another I2C address (e.g. to test probing).
+    pub fn set_address(&self, address: u8) {
+        self.state().address = address;
+    }
+
+    // -- Physical interaction ----------------------------------------------
+
+    /// Turn `encoder` by `ticks` (positive = clockwise).
+    ///
+    /// Updates position and delta and raises INT if the encoder's
+    /// interrupt is enabled, until the delta is read.
+    pub fn turn(&self, encoder: usize, ticks: i32) {
+        let mut s = self.state();
+        s.positions[encoder] = s.positions[encoder].wrapping_add(ticks);
+        s.deltas[encoder] = s.deltas[encoder].wrapping_add(ticks);
+        if ticks != 0 && s.encoder_int[encoder] {
+            s.encoder_flags |= 1 << encoder;
+        }
+    }
+
+    /// Press or release `encoder`'s push switch.
+    ///
+    /// Raises INT on a level change if the switch pin's interrupt is
+    /// enabled.
+    pub fn set_switch(&self, encoder: usize, pressed: bool) {
+        let mut s = self.state();
+        if s.pressed[encoder] == pressed {
+            return;
+        }
+        s.pressed[encoder] = pressed;
+        let pin_bit = 1 << SWITCH_PINS[encoder];
+        if s.gpio_int_enabled & pin_bit != 0 {
+            s.gpio_flags |= pin_bit;
+        }
+    }
+
+    /// Disconnect the board: it stops acknowledging its address.
+    pub fn unplug(&self) {
+        self.state().connected = false;
+    }
+
+    /// Reconnect the board. It comes back in its power-on state, as after
+    /// a real hot-plug.
+    pub fn plug_in(&self) {
+        let mut s = self.state();
+        s.reset();
+        s.connected = true;
+    }
+
+    // -- Observation -------------------------------------------------------
+
+    /// `true` while the active-low INT line is pulled LOW.
+    pub fn int_asserted(&self) -> bool {
+        self.state().int_asserted()
+    }
+
+    /// Current absolute position of `encoder`.
+    pub fn position(&self, encoder: usize) -> i32 {
+        self.state().positions[encoder]
+    }
+
+    /// Movement of `encoder` not yet read from its delta register.
+    pub fn pending_delta(&self, encoder: usize) -> i32 {
+        self.state().deltas[encoder]
+    }
+
+    /// Whether `encoder`'s interrupt is enabled on the device.
+    pub fn interrupt_enabled(&self, encoder: usize) -> bool {
+        self.state().encoder_int[encoder]
+    }
+
+    /// Bitmask of GPIO pins with pin-change interrupts enabled.
+    pub fn gpio_interrupts(&self) -> u32 {
+        self.state().gpio_int_enabled
+    }
+
+    /// Colour last latched onto `encoder`'s NeoPixel by a SHOW command.
+    pub fn pixel(&self, encoder: usize) -> Rgb {
+        let s = self.state();
+        let start = encoder * NEOPIXEL_BYTES_PER_PIXEL;
+        match s.shown.get(start..start + NEOPIXEL_BYTES_PER_PIXEL) {
+            // Wire order is GRB.
+            Some(&[g, r, b]) => Rgb::new(r, g, b),
+            _ => Rgb::OFF,
+        }
+    }
+
+    /// NeoPixel pin and speed as configured by the driver.
+    pub fn pixel_config(&self) -> (u8, u8) {
+        let s = self.state();
+        (s.pixel_pin, s.pixel_speed)
+    }
+
+    /// Number of SHOW commands received.
+    pub fn show_count(&self) -> usize {
+        self.state().show_count
+    }
+
+    // -- Timing and transaction log -----------------------------------------
+
+    /// Current virtual time.
+    pub fn now(&self) -> Duration {
+        self.state().now
+    }
+
+    /// Advance the virtual clock (e.g. to model time between interrupts).
+    pub fn advance(&self, by: Duration) {
+        self.state().now += by;
+    }
+
+    /// Every transaction since creation or the last
+    /// [`clear_transfers`](Self::clear_transfers).
+    pub fn transfers(&self) -> Vec<Transfer> {
+        self.state().log.clone()
+    }
+
+    /// Forget the transaction log.
+    pub fn clear_transfers(&self) {
+        self.state().log.clear();
+    }
+
+    /// Number of reads issued sooner than 125 µs after their address
+    /// phase.
+    pub fn timing_violations(&self) -> usize {
+        self.state().timing_violations
+    }
+
+    /// Let `after` transactions succeed, then fail the next `count` with
+    /// a bus error.
+    pub fn fail_transactions(&self, after: usize, count: usize) {
+        let mut s = self.state();
+        s.fail_after = after;
+        s.fail_count = count;
+    }
+}
+
+/// I2C bus with a [`SimulatedBoard`] attached.
+#[derive(Debug, Clone)]
+pub struct SimI2c {
+    state: Arc<Mutex<SimState>>,
+}
+
+impl ErrorType for SimI2c {
+    type Error = ErrorKind;
+}
+
+impl I2c for SimI2c {
+    async fn transaction(
+        &mut self,
+        address: u8,
+        operations: &mut [Operation<'_>],
+    ) -> Result<(), Self::Error> {
+        let mut s = self.state.lock().unwrap();
+        if !s.connected || address != s.address {
+            return Err(ErrorKind::NoAcknowledge(NoAcknowledgeSource::Address));
+        }
+
+        let failed = s.inject_fault();
+
+        // Writes (the address phase) take effect before any reads in the
+        // same transaction; reads form one contiguous byte stream.
+        let mut written = Vec::new();
+        let mut read = 0;
+        for op in operations.iter() {
+            match op {
+                Operation::Write(data) => written.extend_from_slice(data),
+                Operation::Read(buf) => read += buf.len(),
+            }
+        }
+        if !failed {
+            s.handle_write(&written);
+            if read > 0 {
+                let mut stream = std::vec![0u8; read];
+                s.handle_read(&mut stream);
+                let mut rest = stream.as_slice();
+                for op in operations.iter_mut() {
+                    if let Operation::Read(buf) = op {
+                        let (chunk, tail) = rest.split_at(buf.len());
+                        buf.copy_from_slice(chunk);
+                        rest = tail;
+                    }
+                }
+            }
+        }
+
+        let at = s.now;
+        if !written.is_empty() {
+            s.log.push(Transfer {
+                at,
+                kind: TransferKind::Write(written),
+                ok: !failed,
+            });
+        }
+        if read > 0 {
+            s.log.push(Transfer {
+                at,
+                kind: TransferKind::Read(read),
+                ok: !failed,
+            });
+        }
+
+        if failed {
+            Err(ErrorKind::Other)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// With the `blocking` feature the same bus serves the blocking driver.
+#[cfg(feature = "blocking")]
+impl embedded_hal::i2c::I2c for SimI2c {
+    fn transaction(
+        &mut self,
+        address: u8,
+        operations: &mut [Operation<'_>],
+    ) -> Result<(), Self::Error> {
+        block_on(I2c::transaction(self, address, operations))
+    }
+}
+
+/// Delay provider that advances a [`SimulatedBoard`]'s virtual clock.
+#[derive(Debug, Clone)]
+pub struct SimDelay {
+    state: Arc<Mutex<SimState>>,
+}
+
+impl DelayNs for SimDelay {
+    async fn delay_ns(&mut self, ns: u32) {
+        self.state.lock().unwrap().now += Duration::from_nanos(ns as u64);
+    }
+}
+
+#[cfg(feature = "blocking")]
+impl embedded_hal::delay::DelayNs for SimDelay {
+    fn delay_ns(&mut self, ns: u32) {
+        self.state.lock().unwrap().now += Duration::from_nanos(ns as u64);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn intflag_read_clears_interrupt() {
+        let sim = SimulatedBoard::new();
+        let mut board = sim.board();
+        block_on(board.enable_switch_interrupts()).unwrap();
+
+        sim.set_switch(2, true);
+        assert!(sim.int_asserted());
+        assert_eq!(
+            block_on(board.read_interrupt_flags()).unwrap(),
+            1 << SWITCH_PINS[2]
+        );
+        assert!(!sim.int_asserted());
+    }
+
+    #[test]
+    fn delta_read_clears_encoder_interrupt() {
+        let sim = SimulatedBoard::new();
+        let mut board = sim.board();
+        block_on(board.enable_interrupt(1)).unwrap();
+
+        sim.turn(1, 1);
+        assert!(sim.int_asserted());
+        // Encoder movement is not a GPIO event.
+        assert_eq!(block_on(board.read_interrupt_flags()).unwrap(), 0);
+        assert!(sim.int_asserted());
+        assert_eq!(block_on(board.read_delta(1)).unwrap(), 1);
+        assert!(!sim.int_asserted());
+    }
+
+    #[test]
+    fn disabled_encoders_do_not_interrupt() {
+        let sim = SimulatedBoard::new();
+        sim.turn(0, 3);
+        assert!(!sim.int_asserted());
+        assert_eq!(sim.position(0), 3);
+    }
+
+    #[test]
+    fn wrong_address_is_not_acknowledged() {
+        let sim = SimulatedBoard::new();
+        sim.set_address(0x36);
+        let mut board = QuadEncoderBoard::with_delay(sim.bus(), sim.delay(), DEFAULT_ADDRESS);
+        assert!(block_on(board.read_position(0)).is_err());
+    }
+
+    #[test]
+    fn transfers_are_timestamped() {
+        let sim = SimulatedBoard::new();
+        let mut board = sim.board();
+        block_on(board.read_position(0)).unwrap();
+
+        let log = sim.transfers();
+        assert_eq!(log.len(), 2);
+        assert_eq!(log[0].kind, TransferKind::Write(std::vec![0x11, 0x30]));
+        assert_eq!(log[1].kind, TransferKind::Read(4));
+        assert_eq!(log[1].at - log[0].at, READ_DELAY);
+        assert_eq!(sim.timing_violations(), 0);
+    }
+
+    #[test]
+    fn injected_faults_fail_then_recover() {
+        let sim = SimulatedBoard::new();
+        let mut board = sim.board();
+        sim.fail_transactions(1, 1);
+        // Address phase succeeds, data phase fails.
+        assert!(block_on(board.read_position(0)).is_err());
+        assert!(block_on(board.read_position(0)).is_ok());
+        assert!(!sim.transfers()[1].ok);
+    }
+}
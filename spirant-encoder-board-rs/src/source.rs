@@ -0,0 +1,407 @@
+//! Sources of encoder movement.
+//!
+//! [`EncoderSource`] is the one thing everything downstream of the board
+//! needs: wait until some knobs moved and return how far. Three sources
+//! are provided:
+//!
+//! - [`BoardSource`] — a real [`QuadEncoderBoard`], read whenever its
+//!   [`WakeSource`] fires.
+//! - [`ScriptedSource`] — a fixed list of [`ScriptStep`]s, for host tests
+//!   and demos.
+//! - [`ReplaySource`] — a text recording of timestamped deltas, so a
+//!   captured session can be played back through the parameter and display
+//!   pipeline on a Linux machine.
+//!
+//! The scripted and replay sources pace themselves with any
+//! `embedded-hal-async` [`DelayNs`]: `embassy_time::Delay` in real time,
+//! or the simulation's virtual clock in tests.
+
+use core::fmt;
+use core::future::Future;
+use core::str::Lines;
+
+use embassy_time::{Duration, Timer};
+use embedded_hal_async::delay::DelayNs;
+use embedded_hal_async::digital::Wait;
+use embedded_hal_async::i2c::I2c;
+
+use crate::encoder_board::QuadEncoderBoard;
+use crate::monitor::{EncoderMonitor, MonitorMode};
+use crate::registers::ENCODER_COUNT;
+
+// ---------------------------------------------------------------------------
+// Traits
+// ---------------------------------------------------------------------------
+
+/// Anything that reports movement of `N` encoders.
+pub trait EncoderSource<const N: usize> {
+    /// Wait until at least one encoder moved and return the movement of
+    /// each since the previous call.
+    ///
+    /// Never returns all zeroes. A source that has nothing more to report
+    /// (e.g. a finished script) waits forever.
+    fn wait_deltas(&mut self) -> impl Future<Output = [i32; N]>;
+}
+
+/// What wakes a board reader.
+pub trait WakeSource {
+    /// Wait until the board may have something to report.
+    fn wait(&mut self) -> impl Future<Output = ()>;
+}
+
+/// Wake when the board's active-low INT pin is LOW.
+///
+/// `wait_for_low()` is used rather than a falling edge: it was the reliable
+/// choice with this board in hardware testing, and a line still held LOW
+/// after a failed read wakes the loop again instead of hanging it.
+#[derive(Debug)]
+pub struct InterruptPin<P>(pub P);
+
+impl<P: Wait> WakeSource for InterruptPin<P> {
+    async fn wait(&mut self) {
+        // A pin error is treated as a wake-up; the read that follows is
+        // harmless if nothing moved.
+        let _ = self.0.wait_for_low().await;
+    }
+}
+
+/// Wake at a fixed interval, for wiring without an INT line.
+#[derive(Debug, Clone, Copy)]
+pub struct PollInterval(pub Duration);
+
+impl WakeSource for PollInterval {
+    async fn wait(&mut self) {
+        Timer::after(self.0).await;
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Board
+// ---------------------------------------------------------------------------
+
+/// Movement read from a [`QuadEncoderBoard`].
+///
+/// Each wake-up reads the delta registers and clears the interrupt. Reads
+/// that fail or find no movement are not reported; ticks read before a
+/// failure are carried over to the next report. Connection supervision is
+/// left to the caller — see [`ConnectionSupervisor`](crate::ConnectionSupervisor).
+pub struct BoardSource<I2C, D, W> {
+    board: QuadEncoderBoard<I2C, D>,
+    wake: W,
+    monitor: EncoderMonitor,
+}
+
+impl<I2C, D, W> BoardSource<I2C, D, W>
+where
+    I2C: I2c,
+    D: DelayNs,
+    W: WakeSource,
+{
+    /// Read `board` whenever `wake` fires.
+    ///
+    /// The board's interrupts should already be enabled if `wake` is an
+    /// [`InterruptPin`].
+    pub fn new(board: QuadEncoderBoard<I2C, D>, wake: W) -> Self {
+        Self {
+            board,
+            wake,
+            monitor: EncoderMonitor::new(MonitorMode::Delta),
+        }
+    }
+
+    /// Returns the board, e.g. to drive its pixels between reads.
+    pub fn board_mut(&mut self) -> &mut QuadEncoderBoard<I2C, D> {
+        &mut self.board
+    }
+
+    /// Give back the board and wake source.
+    pub fn release(self) -> (QuadEncoderBoard<I2C, D>, W) {
+        (self.board, self.wake)
+    }
+}
+
+impl<I2C, D, W> EncoderSource<ENCODER_COUNT> for BoardSource<I2C, D, W>
+where
+    I2C: I2c,
+    D: DelayNs,
+    W: WakeSource,
+{
+    async fn wait_deltas(&mut self) -> [i32; ENCODER_COUNT] {
+        loop {
+            self.wake.wait().await;
+            let polled = self.monitor.poll(&mut self.board).await;
+            // Cleared after the read, and on error too, so INT goes back
+            // HIGH instead of waking the loop again straight away.
+            let _ = self.board.clear_interrupt_flags().await;
+            match polled {
+                Ok(deltas) if deltas.iter().any(|&d| d != 0) => return deltas,
+                Ok(_) => {}
+                Err(_) => {
+                    #[cfg(feature = "defmt")]
+                    defmt::warn!("Encoder read failed");
+                }
+            }
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Script
+// ---------------------------------------------------------------------------
+
+/// One report of a [`ScriptedSource`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ScriptStep<const N: usize> {
+    /// Pause before this report, counted from the previous one.
+    pub after: Duration,
+    /// Movement to report.
+    pub deltas: [i32; N],
+}
+
+/// Plays back a fixed list of reports.
+///
+/// Steps whose deltas are all zero only contribute their pause. Once the
+/// script is finished the source waits forever.
+pub struct ScriptedSource<'a, D, const N: usize> {
+    steps: &'a [ScriptStep<N>],
+    next: usize,
+    delay: D,
+}
+
+impl<'a, D: DelayNs, const N: usize> ScriptedSource<'a, D, N> {
+    /// Play `steps`, pacing them with `delay`.
+    pub fn new(steps: &'a [ScriptStep<N>], delay: D) -> Self {
+        Self {
+            steps,
+            next: 0,
+            delay,
+        }
+    }
+
+    /// Returns `true` once every step has been played.
+    pub fn is_finished(&self) -> bool {
+        self.next >= self.steps.len()
+    }
+}
+
+impl<D: DelayNs, const N: usize> EncoderSource<N> for ScriptedSource<'_, D, N> {
+    async fn wait_deltas(&mut self) -> [i32; N] {
+        while let Some(step) = self.steps.get(self.next) {
+            self.next += 1;
+            // Whole milliseconds first: `delay_us` alone overflows after
+            // about 71 minutes.
+            let us = step.after.as_micros();
+            let ms = u32::try_from(us / 1000).unwrap_or(u32::MAX);
+            self.delay.delay_ms(ms).await;
+            self.delay.delay_us((us % 1000) as u32).await;
+            if step.deltas.iter().any(|&d| d != 0) {
+                return step.deltas;
+            }
+        }
+        core::future::pending().await
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Replay
+// ---------------------------------------------------------------------------
+
+/// A recording that [`ReplaySource`] could not parse.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReplayError {
+    /// 1-based number of the offending line.
+    pub line: usize,
+}
+
+impl fmt::Display for ReplayError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Malformed replay line {}", self.line)
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for ReplayError {
+    fn format(&self, f: defmt::Formatter) {
+        defmt::write!(f, "Malformed replay line {}", self.line)
+    }
+}
+
+/// Plays back a text recording of encoder movement.
+///
+/// Each line holds a timestamp in milliseconds since the start of the
+/// recording followed by one delta per encoder, separated by whitespace:
+///
+/// ```text
+/// # t_ms  e0  e1  e2  e3
+/// 0       1   0   0   0
+/// 12      2   0   0  -1
+/// ```
+///
+/// Blank lines and lines starting with `#` are ignored, and timestamps
+/// must not decrease. The text is borrowed, so on Linux a file read with
+/// `std::fs::read_to_string` can be replayed as it is.
+pub struct ReplaySource<'a, D, const N: usize> {
+    lines: Lines<'a>,
+    last_ms: u64,
+    delay: D,
+}
+
+impl<'a, D: DelayNs, const N: usize> ReplaySource<'a, D, N> {
+    /// Check `recording` and prepare to play it, pacing it with `delay`.
+    pub fn new(recording: &'a str, delay: D) -> Result<Self, ReplayError> {
+        let mut last_ms = 0;
+        for (index, line) in recording.lines().enumerate() {
+            match parse_line::<N>(line) {
+                Ok(Some((ms, _))) if ms >= last_ms => last_ms = ms,
+                Ok(None) => {}
+                _ => return Err(ReplayError { line: index + 1 }),
+            }
+        }
+        Ok(Self {
+            lines: recording.lines(),
+            last_ms: 0,
+            delay,
+        })
+    }
+}
+
+impl<D: DelayNs, const N: usize> EncoderSource<N> for ReplaySource<'_, D, N> {
+    async fn wait_deltas(&mut self) -> [i32; N] {
+        for line in self.lines.by_ref() {
+            // Validated in `new`, so malformed lines cannot occur here.
+            let Ok(Some((ms, deltas))) = parse_line::<N>(line) else {
+                continue;
+            };
+            let pause = ms - self.last_ms;
+            self.last_ms = ms;
+            self.delay.delay_ms(pause.min(u32::MAX as u64) as u32).await;
+            if deltas.iter().any(|&d| d != 0) {
+                return deltas;
+            }
+        }
+        core::future::pending().await
+    }
+}
+
+/// Parse one recording line: `Ok(None)` for blank and comment lines,
+/// `Err(())` if it does not hold a timestamp and exactly `N` deltas.
+fn parse_line<const N: usize>(line: &str) -> Result<Option<(u64, [i32; N])>, ()> {
+    let line = line.trim();
+    if line.is_empty() || line.starts_with('#') {
+        return Ok(None);
+    }
+
+    let mut fields = line.split_whitespace();
+    let ms = fields.next().and_then(|f| f.parse().ok()).ok_or(())?;
+    let mut deltas = [0; N];
+    for delta in &mut deltas {
+        *delta = fields.next().and_then(|f| f.parse().ok()).ok_or(())?;
+    }
+    if fields.next().is_some() {
+        return Err(());
+    }
+    Ok(Some((ms, deltas)))
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::*;
+    use crate::sim::{block_on, SimulatedBoard};
+
+    /// Wakes immediately, like an INT line that is already LOW.
+    struct Always;
+
+    impl WakeSource for Always {
+        async fn wait(&mut self) {}
+    }
+
+    #[test]
+    fn board_source_reports_movement_and_clears_interrupt() {
+        let sim = SimulatedBoard::new();
+        let mut board = sim.board();
+        block_on(board.enable_all_interrupts()).unwrap();
+        let mut source = BoardSource::new(board, Always);
+
+        sim.turn(2, -3);
+        assert_eq!(block_on(source.wait_deltas()), [0, 0, -3, 0]);
+        assert!(!sim.int_asserted());
+    }
+
+    #[test]
+    fn board_source_carries_ticks_over_failed_reads() {
+        let sim = SimulatedBoard::new();
+        let mut source = BoardSource::new(sim.board(), Always);
+
+        sim.turn(0, 4);
+        // Fail one transfer part-way through the first poll; the ticks
+        // are still reported once a later poll succeeds.
+        sim.fail_transactions(1, 1);
+        assert_eq!(block_on(source.wait_deltas()), [4, 0, 0, 0]);
+    }
+
+    #[test]
+    fn scripted_source_skips_idle_steps_and_keeps_time() {
+        let sim = SimulatedBoard::new();
+        let steps = [
+            ScriptStep {
+                after: Duration::from_millis(10),
+                deltas: [1, 0],
+            },
+            ScriptStep {
+                after: Duration::from_millis(5),
+                deltas: [0, 0],
+            },
+            ScriptStep {
+                after: Duration::from_millis(5),
+                deltas: [0, -1],
+            },
+        ];
+        let mut source = ScriptedSource::new(&steps, sim.delay());
+
+        assert_eq!(block_on(source.wait_deltas()), [1, 0]);
+        assert_eq!(sim.now(), core::time::Duration::from_millis(10));
+        assert_eq!(block_on(source.wait_deltas()), [0, -1]);
+        assert_eq!(sim.now(), core::time::Duration::from_millis(20));
+        assert!(source.is_finished());
+    }
+
+    #[test]
+    fn scripted_source_waits_out_long_steps() {
+        let sim = SimulatedBoard::new();
+        let steps = [ScriptStep {
+            after: Duration::from_secs(2 * 3600) + Duration::from_micros(250),
+            deltas: [1],
+        }];
+        let mut source = ScriptedSource::new(&steps, sim.delay());
+
+        assert_eq!(block_on(source.wait_deltas()), [1]);
+        assert_eq!(
+            sim.now(),
+            core::time::Duration::from_secs(2 * 3600) + core::time::Duration::from_micros(250)
+        );
+    }
+
+    #[test]
+    fn replay_source_plays_recording() {
+        let sim = SimulatedBoard::new();
+        let recording = "# t_ms e0 e1 e2 e3\n\n3 1 0 0 0\n3 0 0 0 0\n10 0 2 0 -1\n";
+        let mut source: ReplaySource<_, 4> = ReplaySource::new(recording, sim.delay()).unwrap();
+
+        assert_eq!(block_on(source.wait_deltas()), [1, 0, 0, 0]);
+        assert_eq!(sim.now(), core::time::Duration::from_millis(3));
+        assert_eq!(block_on(source.wait_deltas()), [0, 2, 0, -1]);
+        assert_eq!(sim.now(), core::time::Duration::from_millis(10));
+    }
+
+    #[test]
+    fn replay_source_rejects_malformed_lines() {
+        let new = |text| ReplaySource::<_, 2>::new(text, SimulatedBoard::new().delay());
+        assert_eq!(new("0 1\n").err(), Some(ReplayError { line: 1 }));
+        assert_eq!(new("0 1 2 3\n").err(), Some(ReplayError { line: 1 }));
+        assert_eq!(new("# ok\n5 1 x\n").err(), Some(ReplayError { line: 2 }));
+        assert_eq!(new("5 1 0\n4 0 1\n").err(), Some(ReplayError { line: 2 }));
+        assert!(new("0 0 0\n").is_ok());
+    }
+}
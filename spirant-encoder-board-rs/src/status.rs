@@ -0,0 +1,108 @@
+//! Seesaw status-module identification.
+//!
+//! [`DeviceInfo`] decodes the HW_ID and VERSION registers read by
+//! [`QuadEncoderBoard::probe`](crate::QuadEncoderBoard::probe) and decides
+//! whether the device really is a quad encoder board. The decoding is
+//! pure, so it is tested on the host.
+
+use seesaw_driver::registers::SEESAW_HW_IDS;
+
+use crate::error::EncoderError;
+use crate::registers::QUAD_ENCODER_PRODUCT_ID;
+
+/// Identity reported by a Seesaw device's status module.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct DeviceInfo {
+    /// Chip identifier from HW_ID (e.g. `0x87` for an ATtiny817).
+    pub hw_id: u8,
+    /// Adafruit product number from the upper 16 bits of VERSION, or 0 if
+    /// the firmware predates product codes.
+    pub product: u16,
+    /// Firmware build date code from the lower 16 bits of VERSION.
+    pub date_code: u16,
+}
+
+impl DeviceInfo {
+    /// Decode the raw HW_ID and VERSION register values.
+    pub const fn from_registers(hw_id: u8, version: u32) -> Self {
+        Self {
+            hw_id,
+            product: (version >> 16) as u16,
+            date_code: version as u16,
+        }
+    }
+
+    /// Check that this is a quad encoder board with firmware this driver
+    /// can talk to.
+    ///
+    /// # Errors
+    /// * [`EncoderError::WrongDevice`] if the chip is not a Seesaw chip or
+    ///   the firmware reports a different product
+    /// * [`EncoderError::UnsupportedFirmware`] if the firmware does not
+    ///   report a product code at all
+    pub fn validate<E>(&self) -> Result<(), EncoderError<E>> {
+        if !SEESAW_HW_IDS.contains(&self.hw_id) {
+            return Err(EncoderError::WrongDevice {
+                hw_id: self.hw_id,
+                product: self.product,
+            });
+        }
+        match self.product {
+            QUAD_ENCODER_PRODUCT_ID => Ok(()),
+            0 => Err(EncoderError::UnsupportedFirmware {
+                date_code: self.date_code,
+            }),
+            _ => Err(EncoderError::WrongDevice {
+                hw_id: self.hw_id,
+                product: self.product,
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// VERSION as reported by a quad encoder board.
+    const QUAD_VERSION: u32 = (5752 << 16) | 0x2A1C;
+
+    #[test]
+    fn decodes_version_halves() {
+        let info = DeviceInfo::from_registers(0x87, QUAD_VERSION);
+        assert_eq!(info.product, 5752);
+        assert_eq!(info.date_code, 0x2A1C);
+    }
+
+    #[test]
+    fn accepts_quad_encoder() {
+        let info = DeviceInfo::from_registers(0x87, QUAD_VERSION);
+        assert!(info.validate::<()>().is_ok());
+    }
+
+    #[test]
+    fn rejects_other_chip_or_product() {
+        let not_seesaw = DeviceInfo::from_registers(0x60, QUAD_VERSION);
+        assert!(matches!(
+            not_seesaw.validate::<()>(),
+            Err(EncoderError::WrongDevice { hw_id: 0x60, .. })
+        ));
+
+        // A Seesaw NeoKey 1x4 (product 4980).
+        let neokey = DeviceInfo::from_registers(0x87, 4980 << 16);
+        assert!(matches!(
+            neokey.validate::<()>(),
+            Err(EncoderError::WrongDevice { product: 4980, .. })
+        ));
+    }
+
+    #[test]
+    fn rejects_firmware_without_product_code() {
+        let old = DeviceInfo::from_registers(0x55, 0x0000_1234);
+        assert!(matches!(
+            old.validate::<()>(),
+            Err(EncoderError::UnsupportedFirmware { date_code: 0x1234 })
+        ));
+    }
+}
@@ -6,13 +6,26 @@
 //!
 //! 1. A rotary encoder is turned.
 //! 2. The encoder board fires an interrupt on the INT pin.
-//! 3. The encoder monitor task reads the new position, calculates the delta,
-//!    and calls `update_from_encoder()` on the shared `ParameterValues` mutex.
+//! 3. The encoder task (`encoder_monitor`) reads each encoder's movement
+//!    from the board's delta registers, scales it by turning speed, and
+//!    calls `update_from_encoder()` on the shared `ParameterValues` mutex. Push
+//!    switch presses are debounced into gestures (`ButtonEventDetector`);
+//!    a double-click steps to the next page, a long press returns to the
+//!    first page. Each knob's NeoPixel shows its parameter's value (blue →
+//!    red), stays dark for `Null` slots, and all pixels flash on a page
+//!    change.
 //! 4. The OLED display task wakes on its 30 Hz timer, detects the
 //!    `changed_oled` flag, builds a new `DisplayState`, and flushes the
 //!    updated frame to the screen.
-//!
-//! No I2C communication with the Daisy Seed is implemented in this stage.
+//! 5. The Daisy link task (`link_sync_task`) runs the protocol's `Link`
+//!    with the Daisy Seed through an `I2cControllerTransport` on the same
+//!    I2C bus: a `Hello` handshake comparing schema hashes, a full dump of
+//!    the Pico's values after either board resets, then each parameter
+//!    with the `changed_i2c` flag as a `SetParam` frame, and a heartbeat.
+//!    Frames from the Daisy are collected by polling it and go through the
+//!    same `Link`: its parameter changes are only applied once the
+//!    handshake has matched the schemas, with `update_from_i2c()`, so the
+//!    display follows without echoing the value back.
 
 #![no_std]
 #![no_main]
@@ -27,12 +40,23 @@ use embassy_rp::peripherals::I2C0;
 use embassy_sync::blocking_mutex::raw::CriticalSectionRawMutex;
 use embassy_sync::mutex::Mutex;
 use embassy_embedded_hal::shared_bus::asynch::i2c::I2cDevice;
+use embassy_time::{Delay, Duration, Instant};
 use static_cell::StaticCell;
 use {defmt_rtt as _, panic_probe as _};
 
-use encoder_driver::{QuadEncoderBoard, DEFAULT_ADDRESS};
-use spirant::parameter_values::ParameterValues;
+use encoder_driver::encoder_monitor::{
+    EncoderSink, EncoderUpdate, InterruptPin, MonitorConfig, SinkResponse,
+};
+use encoder_driver::{
+    encoder_monitor, ButtonEvent, ButtonEventDetector, ButtonTimings, ConnectionEvent,
+    DetentConfig, QuadEncoderBoard, Rgb, DEFAULT_ADDRESS, ENCODER_COUNT,
+};
+use spirant::parameter_values::{ParameterSlot, ParameterValues, DEFAULT_SCHEMA};
 use spirant_oled_display_rs::{display_update_task, DisplayConfig, OledDriver};
+use spirant_protocol::{
+    link_sync_task, schema_hash, Authority, I2cControllerConfig, I2cControllerTransport,
+    LinkConfig,
+};
 
 // ---------------------------------------------------------------------------
 // Boot block and interrupt binding
@@ -52,14 +76,14 @@ bind_interrupts!(struct Irqs {
 // Static storage
 // ---------------------------------------------------------------------------
 
-/// Shared I2C0 bus — both the encoder board and the OLED display access it
-/// through I2cDevice wrappers that serialise transactions.
+/// Shared I2C0 bus — the encoder board, the OLED display and the Daisy Seed
+/// link access it through I2cDevice wrappers that serialise transactions.
 static I2C_BUS: StaticCell<
     Mutex<CriticalSectionRawMutex, I2c<'static, I2C0, i2c::Async>>,
 > = StaticCell::new();
 
 /// Shared synthesizer parameter state — written by the encoder task,
-/// read by the OLED display task.
+/// read by the OLED display and Daisy tasks.
 static PARAM_VALUES: StaticCell<
     Mutex<CriticalSectionRawMutex, ParameterValues>,
 > = StaticCell::new();
@@ -82,6 +106,145 @@ type EncoderI2c = I2cDevice<
     I2c<'static, I2C0, i2c::Async>,
 >;
 
+/// Concrete I2C type for the Daisy Seed link, sharing I2C_BUS.
+type DaisyI2c = I2cDevice<
+    'static,
+    CriticalSectionRawMutex,
+    I2c<'static, I2C0, i2c::Async>,
+>;
+
+/// I2C address the Daisy Seed firmware listens on as a target.
+const DAISY_ADDRESS: u8 = 0x42;
+
+// ---------------------------------------------------------------------------
+// Knob lighting
+// ---------------------------------------------------------------------------
+
+/// Pixel colour at the bottom of a parameter's range.
+const PIXEL_MIN: Rgb = Rgb::new(0, 0, 255);
+/// Pixel colour at the top of a parameter's range.
+const PIXEL_MAX: Rgb = Rgb::new(255, 0, 0);
+/// How long all pixels flash white after a page change.
+const PAGE_FLASH: Duration = Duration::from_millis(60);
+/// Ticks per click, direction and scale of each knob, so parameters move
+/// exactly one step per click. Change per knob for other encoder parts or
+/// knobs mounted reversed.
+const ENCODER_DETENTS: [DetentConfig; ENCODER_COUNT] = [DetentConfig::IDENTITY; ENCODER_COUNT];
+
+/// Colour for each knob on the active page: blended by value position for
+/// active slots, off for `Null` slots.
+fn page_colours(params: &ParameterValues) -> [Rgb; ENCODER_COUNT] {
+    let page = params.get_active_page();
+    core::array::from_fn(|i| match page.params.get(i) {
+        Some(ParameterSlot::Active(p)) => {
+            let span = (p.max_value as i64 - p.min_value as i64).max(1);
+            let t = (p.value as i64 - p.min_value as i64) * 255 / span;
+            PIXEL_MIN.blend(PIXEL_MAX, t as u8)
+        }
+        _ => Rgb::OFF,
+    })
+}
+
+// ---------------------------------------------------------------------------
+// Encoder sink
+// ---------------------------------------------------------------------------
+
+/// Front-panel behaviour on top of the shared `ParameterValues`.
+///
+/// Receives each wake-up from `encoder_monitor`: switch states are fed to a
+/// `ButtonEventDetector` (a double-click steps to the next page, a long
+/// press returns to the first page), movement is written into the page it
+/// was made on before any page change, and the knob NeoPixels are
+/// recoloured after every change, with a white flash on a page change. The
+/// mutex is held only during the in-memory update — never during I2C
+/// operations, which the monitor performs after `update()` returns.
+struct Panel {
+    params: &'static Mutex<CriticalSectionRawMutex, ParameterValues>,
+    buttons: ButtonEventDetector<ENCODER_COUNT>,
+    /// Last sampled switch bits; a failed read keeps the last known state
+    /// rather than reporting a spurious release.
+    switches: u8,
+}
+
+impl EncoderSink for Panel {
+    async fn start(&mut self) -> SinkResponse {
+        SinkResponse {
+            pixels: Some(page_colours(&*self.params.lock().await)),
+            ..SinkResponse::default()
+        }
+    }
+
+    async fn update(&mut self, update: &EncoderUpdate) -> SinkResponse {
+        if let Some(switches) = update.switches {
+            self.switches = switches;
+        }
+
+        let mut page_step: Option<bool> = None;
+        self.buttons.update(
+            update.now,
+            self.switches as u32,
+            update.moved_mask(),
+            |encoder_idx, event| {
+                debug!("Switch {}: {}", encoder_idx, event);
+                match event {
+                    ButtonEvent::DoubleClick => page_step = Some(true),
+                    ButtonEvent::LongPress => page_step = Some(false),
+                    _ => {}
+                }
+            },
+        );
+
+        if !update.moved() && page_step.is_none() {
+            return SinkResponse::default();
+        }
+
+        let mut params = self.params.lock().await;
+        // Movement read on this wake-up was made on the current page, so
+        // apply it before a double-click or long press switches away.
+        // Masked encoders were turned where they controlled nothing.
+        for (encoder_idx, delta) in update.unmasked_deltas().into_iter().enumerate() {
+            if delta != 0 {
+                params.update_from_encoder(encoder_idx, delta);
+                debug!(
+                    "Encoder {}: delta={} (raw {})",
+                    encoder_idx, delta, update.raw_deltas[encoder_idx]
+                );
+            }
+        }
+        let mut page_changed = false;
+        if let Some(next) = page_step {
+            let n_pages = params.schema().n_pages();
+            let page = if next { (params.current_page() + 1) % n_pages } else { 0 };
+            if params.set_active_page(page).is_ok() {
+                page_changed = true;
+                info!("Page {}", page);
+            }
+        }
+
+        // Unmapped encoders on the new page stop raising interrupts, which
+        // saves wake-ups and traffic on the bus shared with the OLED.
+        SinkResponse {
+            interrupt_mask: params.take_encoder_mask().map(|mask| mask as u8),
+            flash: page_changed.then_some((Rgb::WHITE, PAGE_FLASH)),
+            pixels: Some(page_colours(&params)),
+            reset_acceleration: page_changed,
+        }
+    }
+
+    async fn connection_changed(&mut self, event: ConnectionEvent) {
+        match event {
+            ConnectionEvent::Lost => error!("Encoder board lost; re-probing"),
+            ConnectionEvent::Reconnected(info) => {
+                info!("Encoder board reconnected (firmware {=u16:#x})", info.date_code)
+            }
+        }
+    }
+
+    async fn next_deadline(&self) -> Option<Instant> {
+        self.buttons.next_deadline()
+    }
+}
+
 // ---------------------------------------------------------------------------
 // Tasks
 // ---------------------------------------------------------------------------
@@ -97,79 +260,50 @@ async fn oled_task(
     display_update_task(driver, params, config).await;
 }
 
-/// Interrupt-driven encoder monitoring task.
+/// Thin wrapper that monomorphises the generic `link_sync_task` loop.
+///
+/// Keeps the Daisy Seed in step over I2C0: handshake and full dump after
+/// either board resets (the panel's values win), then changes in both
+/// directions with a heartbeat. NACKed writes are repeated, and a change
+/// is only marked as sent once its write succeeded.
+#[embassy_executor::task]
+async fn daisy_task(
+    i2c: DaisyI2c,
+    params: &'static Mutex<CriticalSectionRawMutex, ParameterValues>,
+) {
+    info!("Daisy link task started");
+    let transport =
+        I2cControllerTransport::new(i2c, Delay, I2cControllerConfig::new(DAISY_ADDRESS));
+    let config = LinkConfig::new(schema_hash(&DEFAULT_SCHEMA), Authority::Pico);
+    link_sync_task(transport, params, config, None).await
+}
+
+/// Thin wrapper that monomorphises the generic `encoder_monitor` loop.
 ///
-/// Waits for the INT pin to go LOW (active-low from the encoder board),
-/// reads all 4 encoder positions, computes deltas against the previous
-/// baseline, and writes non-zero deltas into `ParameterValues`. The mutex
-/// is held only during the in-memory update — never during I2C operations.
+/// Wakes on the INT pin (active-low from the encoder board) or at the
+/// `Panel`'s next button deadline, reads the encoders' delta registers and
+/// switches, scales movement by turning speed and reports it to the
+/// `Panel`. Failed reads are retried; if the board stops answering it is
+/// re-probed periodically and reconfigured once it is back.
 #[embassy_executor::task]
 async fn encoder_task(
-    mut int_pin: Input<'static>,
-    mut encoder_board: QuadEncoderBoard<EncoderI2c>,
+    int_pin: Input<'static>,
+    encoder_board: QuadEncoderBoard<EncoderI2c>,
     param_values: &'static Mutex<CriticalSectionRawMutex, ParameterValues>,
 ) {
     info!("Encoder monitor task started");
 
-    // Establish a baseline so the first delta calculation is correct.
-    // If this read fails we start from [0; 4]; the first interrupt may
-    // produce a spurious delta — harmless in practice.
-    let mut previous_positions = [0i32; 4];
-    match encoder_board.read_all_positions().await {
-        Ok(positions) => previous_positions = positions,
-        Err(_) => warn!("Could not read initial positions; starting from [0; 4]"),
-    }
-
-    loop {
-        // wait_for_low() is used instead of wait_for_falling_edge() — confirmed
-        // reliable with this encoder board during hardware testing.
-        int_pin.wait_for_low().await;
-
-        let positions = match encoder_board.read_all_positions().await {
-            Ok(p) => p,
-            Err(_) => {
-                error!("Encoder read failed");
-                // Clear interrupt flags even on error so INT returns HIGH and
-                // the next movement produces a fresh interrupt rather than
-                // causing the task to spin in a tight error loop.
-                let _ = encoder_board.clear_interrupt_flags().await;
-                continue;
-            }
-        };
-
-        // Clear AFTER reading positions — drives INT back HIGH.
-        // Clearing before reading would risk missing a rapid second movement
-        // that arrives during the I2C read.
-        if let Err(_) = encoder_board.clear_interrupt_flags().await {
-            warn!("Failed to clear interrupt flags");
-        }
-
-        let deltas: [i32; 4] = core::array::from_fn(|i| {
-            positions[i] - previous_positions[i]
-        });
-
-        // Update baseline unconditionally — tracks hardware state even when
-        // all deltas are zero (e.g. spurious power-on interrupt).
-        previous_positions = positions;
-
-        if deltas.iter().all(|&d| d == 0) {
-            continue;
-        }
-
-        // Mutex held only during in-memory updates — never during I2C.
-        {
-            let mut params = param_values.lock().await;
-            for (encoder_idx, &delta) in deltas.iter().enumerate() {
-                if delta != 0 {
-                    params.update_from_encoder(encoder_idx, delta);
-                    debug!(
-                        "Encoder {}: delta={}, position={}",
-                        encoder_idx, delta, positions[encoder_idx]
-                    );
-                }
-            }
-        } // mutex released here
-    }
+    let panel = Panel {
+        params: param_values,
+        buttons: ButtonEventDetector::new(ButtonTimings::default()),
+        switches: 0,
+    };
+    let config = MonitorConfig {
+        detents: ENCODER_DETENTS,
+        read_switches: true,
+        ..MonitorConfig::default()
+    };
+    encoder_monitor(encoder_board, InterruptPin(int_pin), panel, config).await
 }
 
 // ---------------------------------------------------------------------------
@@ -187,7 +321,8 @@ async fn main(spawner: Spawner) {
     // ENC_INT → GP19  (p.PIN_19)  active-low, pull-up enabled
     // ———————————————————————————————————————————————————————————————————————
 
-    // Initialise I2C0, shared between the encoder board and OLED display.
+    // Initialise I2C0, shared between the encoder board, the OLED display
+    // and the Daisy Seed.
     let i2c = I2c::new_async(
         p.I2C0,
         p.PIN_21, // SCL
@@ -204,9 +339,10 @@ async fn main(spawner: Spawner) {
     // bus access automatically.
     let i2c_encoder = I2cDevice::new(i2c_bus);
     let i2c_oled = I2cDevice::new(i2c_bus);
+    let i2c_daisy = I2cDevice::new(i2c_bus);
 
-    // Encoder board. DEFAULT_ADDRESS is 0x49 (confirmed by hardware testing;
-    // design documents show 0x36 which is incorrect).
+    // Encoder board. DEFAULT_ADDRESS is 0x49 (the generic Seesaw 0x36 does
+    // not apply to this board); probe() below confirms the device.
     let mut encoder_board = QuadEncoderBoard::new(i2c_encoder, DEFAULT_ADDRESS);
 
     // OLED display at the standard SSD1306 I2C address.
@@ -220,23 +356,51 @@ async fn main(spawner: Spawner) {
 
     // —— Encoder initialisation —————————————————————————————————————————————
 
-    // Read initial positions so the first delta calculation starts from the
-    // correct hardware baseline. On failure the encoder task uses [0; 4].
-    match encoder_board.read_all_positions().await {
-        Ok(positions) => info!(
-            "Initial encoder positions: [{}, {}, {}, {}]",
-            positions[0], positions[1], positions[2], positions[3]
+    // Identify the board before configuring it. A mismatch is logged but
+    // not fatal, so the display still comes up for diagnosis.
+    match encoder_board.probe().await {
+        Ok(info) => info!(
+            "Encoder board: product {}, HW_ID {=u8:#x}, firmware {=u16:#x}",
+            info.product, info.hw_id, info.date_code
         ),
-        Err(_) => warn!("Could not read initial encoder positions"),
+        Err(e) => error!("Encoder board probe failed: {}", e),
     }
 
-    // Enable hardware interrupts. Without this the INT pin never fires and
-    // the encoder task sleeps forever inside wait_for_low(). On failure we
-    // log an error and continue — encoder input simply will not work.
-    if let Err(_) = encoder_board.enable_all_interrupts().await {
+    // Drain the delta registers so movement from before boot is not
+    // applied to the freshly initialised parameters.
+    match encoder_board.read_all_deltas().await {
+        Ok(deltas) => info!(
+            "Discarded power-on encoder deltas: [{}, {}, {}, {}]",
+            deltas[0], deltas[1], deltas[2], deltas[3]
+        ),
+        Err(_) => warn!("Could not drain initial encoder deltas"),
+    }
+
+    // Enable hardware interrupts for the encoders mapped on the first page.
+    // Without this the INT pin never fires and the encoder task sleeps
+    // forever inside wait_for_low(). On failure we log an error and
+    // continue — encoder input simply will not work.
+    let initial_mask = param_values.lock().await.take_encoder_mask().unwrap_or(0b1111);
+    if let Err(_) = encoder_board.set_interrupt_mask(initial_mask as u8).await {
         error!("Failed to enable encoder interrupts");
     }
 
+    // Knob NeoPixels at quarter brightness; the encoder task paints the
+    // first frame once it starts.
+    if let Err(_) = encoder_board.configure_pixels().await {
+        error!("Failed to configure encoder pixels");
+    }
+    encoder_board.set_brightness(64);
+
+    // Push switches: inputs with pull-ups, and interrupts so presses also
+    // pulse the shared INT pin. Used for page navigation.
+    if let Err(_) = encoder_board.configure_switches().await {
+        error!("Failed to configure encoder switches");
+    }
+    if let Err(_) = encoder_board.enable_switch_interrupts().await {
+        warn!("Failed to enable switch interrupts");
+    }
+
     // Clear any stale interrupt flags that accumulated at power-on before
     // interrupts were enabled, so INT starts HIGH and clean.
     if let Err(_) = encoder_board.clear_interrupt_flags().await {
@@ -249,6 +413,7 @@ async fn main(spawner: Spawner) {
 
     spawner.spawn(oled_task(oled_driver, param_values, display_config)).unwrap();
     spawner.spawn(encoder_task(int_pin, encoder_board, param_values)).unwrap();
+    spawner.spawn(daisy_task(i2c_daisy, param_values)).unwrap();
 
     info!("All tasks spawned");
 }
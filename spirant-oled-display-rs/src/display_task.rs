@@ -10,7 +10,7 @@
 use embedded_hal_async::i2c::I2c;
 
 use spirant::parameter_values::{
-    ParameterSlot, ParameterValues, PARAMS_PER_PAGE, PARAM_NAMES, PAGE_NAMES,
+    ParamDescriptor, ParameterSlot, ParameterValues, PARAMS_PER_PAGE,
 };
 
 use crate::driver::OledDriver;
@@ -35,6 +35,12 @@ use crate::layout::{render_display, DisplayConfig, DisplayState};
 /// }
 /// ```
 ///
+/// The task is generic over the page count of the
+/// [`ParameterSchema`](spirant::parameter_values::ParameterSchema) that
+/// `param_values` was built from; page names, parameter names and value
+/// formatting (units, display format) are read from that schema. Each page
+/// must have [`PARAMS_PER_PAGE`] slots, one per display column.
+///
 /// # Control flow
 ///
 /// 1. Initialise the display hardware.
@@ -54,11 +60,11 @@ use crate::layout::{render_display, DisplayConfig, DisplayState};
 /// * Initialisation failure: logs the error and **returns** (task exits).
 /// * Render / flush failure: logs the error and continues to the next cycle.
 #[allow(clippy::needless_pass_by_value)] // config is small and consumed
-pub async fn display_update_task<I2C>(
+pub async fn display_update_task<I2C, const N_PAGES: usize>(
     mut driver: OledDriver<I2C>,
     param_values: &'static embassy_sync::mutex::Mutex<
         embassy_sync::blocking_mutex::raw::CriticalSectionRawMutex,
-        ParameterValues,
+        ParameterValues<N_PAGES, PARAMS_PER_PAGE>,
     >,
     config: DisplayConfig,
 ) where
@@ -82,34 +88,33 @@ pub async fn display_update_task<I2C>(
         embassy_time::Timer::after(period).await;
 
         // ── Step 1: read state (mutex held briefly) ──────────────────
-        let (page_name, param_names, param_values_snap, changed_flags) = {
+        let (page_name, slots, changed_flags) = {
             let params = param_values.lock().await;
             let page_idx = params.current_page();
-            let page_name: &str = PAGE_NAMES[page_idx];
+            let schema = params.schema();
+            let page_name: &str = schema.page_name(page_idx).unwrap_or("");
 
-            let mut names: [Option<&str>; 4] = [None; 4];
-            let mut values: [Option<i32>; 4] = [None; 4];
+            let mut slots: [Option<(&ParamDescriptor, i32)>; 4] = [None; 4];
             let mut flags: [bool; 4] = [false; 4];
 
             let page = &params.pages[page_idx];
             for i in 0..PARAMS_PER_PAGE {
-                match &page.params[i] {
-                    ParameterSlot::Active(param) => {
-                        names[i] = PARAM_NAMES[page_idx][i];
-                        values[i] = Some(param.value);
+                match (&page.params[i], schema.param(page_idx, i)) {
+                    (ParameterSlot::Active(param), Some(descriptor)) => {
+                        slots[i] = Some((descriptor, param.value));
                         flags[i] = param.changed_oled;
                     }
-                    ParameterSlot::Null => {
+                    _ => {
                         // Leave as None / false — blank column.
                     }
                 }
             }
 
-            (page_name, names, values, flags)
+            (page_name, slots, flags)
         }; // ← mutex released here, before any I2C work
 
         // ── Step 2: build new display state ──────────────────────────
-        let new_state = DisplayState::from_params(page_name, param_names, param_values_snap);
+        let new_state = DisplayState::from_descriptors(page_name, slots);
 
         // ── Step 3: skip if nothing changed ──────────────────────────
         if new_state == last_state {
@@ -13,7 +13,7 @@ use embedded_graphics::{
     text::{Alignment, Text},
 };
 use heapless::String;
-
+use spirant::parameter_values::ParamDescriptor;
 
 // ── DisplayConfig ────────────────────────────────────────────────────────
 
@@ -82,13 +82,19 @@ pub struct DisplayState {
     pub param_names: [[u8; 16]; 4],
     /// Parameter values. `None` indicates a null slot (blank column).
     pub param_values: [Option<i32>; 4],
+    /// Formatted value text (number plus unit, or the option label for
+    /// discrete parameters), null-padded UTF-8, one per column. Empty for
+    /// null slots.
+    pub param_text: [[u8; 16]; 4],
 }
 
 impl DisplayState {
     /// Construct from live parameter data.
     ///
     /// Strings are copied into fixed-size buffers and silently truncated
-    /// if longer than 15 characters.
+    /// if longer than 15 characters. Values are shown as plain integers;
+    /// use [`from_descriptors()`](Self::from_descriptors) to apply each
+    /// parameter's display format and unit.
     ///
     /// # Arguments
     ///
@@ -116,10 +122,59 @@ impl DisplayState {
             }
         }
 
+        // Format values as plain integers.
+        for (i, value) in param_values.iter().enumerate() {
+            if let Some(v) = value {
+                let mut buf: String<15> = String::new();
+                // core::fmt::Write — works in no_std without alloc.
+                let _ = write!(buf, "{}", v);
+                Self::copy_str(&mut state.param_text[i], buf.as_str());
+            }
+        }
+
         state.param_values = param_values;
         state
     }
 
+    /// Construct from parameter descriptors and their current values.
+    ///
+    /// Names come from each [`ParamDescriptor`] and value text is rendered
+    /// with [`ParamDescriptor::write_value()`], so units and the display
+    /// format hint are applied. Text longer than 15 characters is truncated.
+    ///
+    /// # Arguments
+    ///
+    /// * `page_name` — Name of the active page.
+    /// * `slots` — Descriptor and current value for each encoder slot
+    ///   (`None` = null slot, blank column).
+    pub fn from_descriptors(
+        page_name: &str,
+        slots: [Option<(&ParamDescriptor, i32)>; 4],
+    ) -> Self {
+        let names = slots.map(|s| s.map(|(d, _)| d.name));
+        let values = slots.map(|s| s.map(|(_, v)| v));
+        let mut state = Self::from_params(page_name, names, values);
+
+        for (i, slot) in slots.iter().enumerate() {
+            if let Some((descriptor, value)) = slot {
+                let mut buf: String<15> = String::new();
+                // A capacity error leaves the truncated prefix in `buf`.
+                let _ = descriptor.write_value(*value, &mut buf);
+                state.param_text[i] = [0; 16];
+                Self::copy_str(&mut state.param_text[i], buf.as_str());
+            }
+        }
+
+        state
+    }
+
+    /// Copy `s` into a null-padded buffer, truncating to 15 bytes.
+    fn copy_str(dest: &mut [u8; 16], s: &str) {
+        let bytes = s.as_bytes();
+        let len = bytes.len().min(15);
+        dest[..len].copy_from_slice(&bytes[..len]);
+    }
+
     /// Convert a fixed-size null-padded byte array back to a `&str`.
     ///
     /// Stops at the first null byte. Returns `""` if the first byte is
@@ -140,7 +195,7 @@ impl DisplayState {
 pub struct DisplayChanges {
     /// `true` if the page name differs.
     pub page_name_changed: bool,
-    /// Per-column flag: `true` if either the name or value differs.
+    /// Per-column flag: `true` if the name, value or value text differs.
     pub param_changed: [bool; 4],
 }
 
@@ -152,7 +207,8 @@ impl DisplayChanges {
         let mut param_changed = [false; 4];
         for (i, changed) in param_changed.iter_mut().enumerate() {
             *changed = old.param_names[i] != new.param_names[i]
-                || old.param_values[i] != new.param_values[i];
+                || old.param_values[i] != new.param_values[i]
+                || old.param_text[i] != new.param_text[i];
         }
 
         Self {
@@ -247,13 +303,11 @@ where
             .draw(display)?;
         }
 
-        // Parameter value
-        if let Some(v) = state.param_values[i] {
-            let mut buf: String<8> = String::new();
-            // core::fmt::Write — works in no_std without alloc.
-            let _ = write!(buf, "{}", v);
+        // Parameter value (pre-formatted text)
+        if state.param_values[i].is_some() {
+            let text = DisplayState::bytes_to_str(&state.param_text[i]);
             Text::with_alignment(
-                buf.as_str(),
+                text,
                 Point::new(centre_x, config.param_value_y),
                 text_style,
                 Alignment::Center,
@@ -325,6 +379,56 @@ mod tests {
         );
     }
 
+    #[test]
+    fn from_params_formats_plain_integers() {
+        let state = DisplayState::from_params("P", [Some("A"), None, None, None], [Some(-5), None, None, None]);
+        assert_eq!(DisplayState::bytes_to_str(&state.param_text[0]), "-5");
+        assert_eq!(DisplayState::bytes_to_str(&state.param_text[1]), "");
+    }
+
+    #[test]
+    fn from_descriptors_applies_format_and_unit() {
+        use spirant::parameter_values::{DisplayFormat, Unit};
+
+        let attack = ParamDescriptor::new("Attack").range(0, 5000).unit(Unit::Ms);
+        let sustain = ParamDescriptor::new("Sustain").format(DisplayFormat::Percent);
+        let state = DisplayState::from_descriptors(
+            "Envelope",
+            [Some((&attack, 250)), Some((&sustain, 127)), None, None],
+        );
+
+        assert_eq!(DisplayState::bytes_to_str(&state.param_names[0]), "Attack");
+        assert_eq!(DisplayState::bytes_to_str(&state.param_text[0]), "250ms");
+        assert_eq!(DisplayState::bytes_to_str(&state.param_text[1]), "100%");
+        assert_eq!(state.param_values[1], Some(127));
+        assert!(state.param_values[2].is_none());
+        assert_eq!(DisplayState::bytes_to_str(&state.param_text[2]), "");
+    }
+
+    #[test]
+    fn from_descriptors_shows_curve_mapped_value() {
+        use spirant::parameter_values::{Curve, Unit};
+
+        let cutoff = ParamDescriptor::new("Cutoff")
+            .range(0, 100)
+            .curve(Curve::Exponential(10))
+            .output_range(20, 20480)
+            .unit(Unit::Hz);
+        let state = DisplayState::from_descriptors("Filter", [Some((&cutoff, 100)), None, None, None]);
+
+        assert_eq!(DisplayState::bytes_to_str(&state.param_text[0]), "20480Hz");
+        assert_eq!(state.param_values[0], Some(100));
+    }
+
+    #[test]
+    fn from_descriptors_shows_discrete_label() {
+        let shape = ParamDescriptor::new("Shape").discrete(&["Sine", "Tri", "Saw", "Square"]);
+        let state = DisplayState::from_descriptors("LFO", [Some((&shape, 2)), None, None, None]);
+
+        assert_eq!(DisplayState::bytes_to_str(&state.param_text[0]), "Saw");
+        assert_eq!(state.param_values[0], Some(2));
+    }
+
     #[test]
     fn bytes_to_str_handles_null_padding() {
         let mut buf = [0u8; 16];
@@ -0,0 +1,268 @@
+/// Fixed-point one in Q16.16.
+const ONE: i64 = 1 << 16;
+
+/// `2^(i/16)` in Q16.16 for `i` in `0..=16`, used to interpolate the
+/// fractional part of [`exp2_q16`].
+const EXP2_FRAC: [i64; 17] = [
+    65536, 68438, 71468, 74632, 77936, 81386, 84990, 88752, 92682, 96785, 101070, 105545, 110218,
+    115098, 120194, 125515, 131072,
+];
+
+/// Response curve mapping a parameter's linear encoder position to its
+/// output value.
+///
+/// The encoder always moves the position linearly through
+/// `[min_value, max_value]`; the curve decides how that position is spread
+/// over the output range. All arithmetic is integer Q16.16 fixed-point, so
+/// curves are usable in `no_std` firmware without an FPU.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Curve {
+    /// Output is proportional to position.
+    #[default]
+    Linear,
+    /// Slow start, fast finish: `(2^(k·t) − 1) / (2^k − 1)`, where `k` is the
+    /// number of doublings across the range (at most 16).
+    ///
+    /// With `k = log2(out_max / out_min)` equal steps give equal frequency
+    /// ratios, e.g. `Exponential(10)` for 20 Hz – 20 kHz.
+    Exponential(u8),
+    /// Fast start, slow finish: the inverse of
+    /// [`Exponential`](Curve::Exponential) with the same `k`. Suits times
+    /// (attack, release) where detail matters near zero.
+    Logarithmic(u8),
+    /// Smoothstep `3t² − 2t³`: fine control at both ends, coarse in the
+    /// middle.
+    SCurve,
+    /// Custom lookup table of output values at evenly spaced positions,
+    /// linearly interpolated in between. The output range is ignored;
+    /// the table must be monotonic for [`unmap()`](Curve::unmap) to invert it.
+    Table(&'static [i32]),
+}
+
+impl Curve {
+    /// Map `position` in `[min, max]` to an output in `[out_min, out_max]`.
+    ///
+    /// `position` is clamped to `[min, max]` first.
+    pub fn map(&self, position: i32, min: i32, max: i32, out_min: i32, out_max: i32) -> i32 {
+        let t = normalise(position, min, max);
+
+        if let Curve::Table(table) = self {
+            return lookup(table, t, out_min);
+        }
+
+        let s = match *self {
+            Curve::Linear | Curve::Table(_) => t,
+            Curve::Exponential(k) => exp_shape(t, k),
+            Curve::Logarithmic(k) => log_shape(t, k),
+            Curve::SCurve => t * t / ONE * (3 * ONE - 2 * t) / ONE,
+        };
+
+        let span = out_max as i64 - out_min as i64;
+        (out_min as i64 + round_q16(s * span)) as i32
+    }
+
+    /// Find the position in `[min, max]` whose mapped output is closest to
+    /// `output`.
+    ///
+    /// This is the inverse of [`map()`](Self::map) for monotonic curves and
+    /// is used when a value arrives in output units (e.g. from the Daisy
+    /// Seed) and must be turned back into an encoder position.
+    pub fn unmap(&self, output: i32, min: i32, max: i32, out_min: i32, out_max: i32) -> i32 {
+        if max <= min {
+            return min;
+        }
+        let f = |p: i32| self.map(p, min, max, out_min, out_max);
+        let increasing = f(max) >= f(min);
+        let before = |p: i32| if increasing { f(p) < output } else { f(p) > output };
+
+        // Binary search for the first position that reaches `output`, in
+        // i64 so that `hi - lo` cannot overflow on wide ranges.
+        let (mut lo, mut hi) = (min as i64, max as i64);
+        while lo < hi {
+            let mid = lo + (hi - lo) / 2;
+            if before(mid as i32) {
+                lo = mid + 1;
+            } else {
+                hi = mid;
+            }
+        }
+        let lo = lo as i32;
+
+        // `lo` is at or past the target; the previous position may be closer.
+        let distance = |p: i32| (f(p) as i64 - output as i64).abs();
+        if lo > min && distance(lo - 1) <= distance(lo) {
+            lo - 1
+        } else {
+            lo
+        }
+    }
+}
+
+/// Position within `[min, max]` as Q16.16 in `[0, ONE]`.
+fn normalise(position: i32, min: i32, max: i32) -> i64 {
+    if max <= min {
+        return 0;
+    }
+    let p = position.clamp(min, max) as i64 - min as i64;
+    p * ONE / (max as i64 - min as i64)
+}
+
+/// Round a Q16.16 value to the nearest integer (half away from zero).
+fn round_q16(x: i64) -> i64 {
+    if x >= 0 {
+        (x + ONE / 2) >> 16
+    } else {
+        -((-x + ONE / 2) >> 16)
+    }
+}
+
+/// `2^x` for Q16.16 `x` in `[0, 16]`, result in Q16.16.
+fn exp2_q16(x: i64) -> i64 {
+    let int = x >> 16;
+    let frac = x & (ONE - 1);
+    let idx = (frac >> 12) as usize;
+    let rem = frac & 0xFFF;
+    let base = EXP2_FRAC[idx] + (((EXP2_FRAC[idx + 1] - EXP2_FRAC[idx]) * rem) >> 12);
+    base << int
+}
+
+/// Exponential shape `(2^(k·t) − 1) / (2^k − 1)` on Q16.16 `t`.
+fn exp_shape(t: i64, k: u8) -> i64 {
+    let k = k.min(16) as i64;
+    if k == 0 {
+        return t;
+    }
+    let full = exp2_q16(k * ONE) - ONE;
+    (exp2_q16(k * t) - ONE) * ONE / full
+}
+
+/// Logarithmic shape: the inverse of [`exp_shape`], found by bisection.
+fn log_shape(s: i64, k: u8) -> i64 {
+    let k = k.min(16) as i64;
+    if k == 0 {
+        return s;
+    }
+    // Compare unnormalised values so precision near zero is not lost to
+    // the division in `exp_shape`.
+    let target = s * (exp2_q16(k * ONE) - ONE);
+    let (mut lo, mut hi) = (0, ONE);
+    while hi - lo > 1 {
+        let mid = (lo + hi) / 2;
+        if (exp2_q16(k * mid) - ONE) * ONE <= target {
+            lo = mid;
+        } else {
+            hi = mid;
+        }
+    }
+    lo
+}
+
+/// Linear interpolation into an evenly spaced table at Q16.16 `t`.
+fn lookup(table: &[i32], t: i64, fallback: i32) -> i32 {
+    match table.len() {
+        0 => fallback,
+        1 => table[0],
+        n => {
+            let f = t * (n as i64 - 1);
+            let idx = ((f >> 16) as usize).min(n - 2);
+            let rem = f - ((idx as i64) << 16);
+            let a = table[idx] as i64;
+            let b = table[idx + 1] as i64;
+            (a + round_q16((b - a) * rem)) as i32
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn linear_scales_between_ranges() {
+        assert_eq!(Curve::Linear.map(0, 0, 127, 0, 127), 0);
+        assert_eq!(Curve::Linear.map(64, 0, 127, 0, 127), 64);
+        assert_eq!(Curve::Linear.map(50, 0, 100, 0, 1000), 500);
+        assert_eq!(Curve::Linear.map(500, 0, 100, 0, 1000), 1000); // clamped
+    }
+
+    #[test]
+    fn exponential_is_geometric_over_octaves() {
+        // 20 Hz .. 20480 Hz is exactly 10 doublings.
+        let c = Curve::Exponential(10);
+        assert_eq!(c.map(0, 0, 100, 20, 20480), 20);
+        assert_eq!(c.map(100, 0, 100, 20, 20480), 20480);
+        // Halfway is 5 doublings above 20 Hz (≈ 640 Hz); the shape is
+        // offset-normalised so it lands within a few Hz of that.
+        let mid = c.map(50, 0, 100, 20, 20480);
+        assert!((mid - 640).abs() <= 5, "mid = {}", mid);
+        // Low half of the knob covers far less than half the range.
+        assert!(c.map(25, 0, 100, 20, 20480) < 200);
+    }
+
+    #[test]
+    fn logarithmic_is_inverse_of_exponential() {
+        let e = Curve::Exponential(8);
+        let l = Curve::Logarithmic(8);
+        assert_eq!(l.map(0, 0, 127, 0, 5000), 0);
+        assert_eq!(l.map(127, 0, 127, 0, 5000), 5000);
+        assert!(l.map(32, 0, 127, 0, 5000) > 2500);
+        for p in [0, 10, 64, 100, 127] {
+            let s = e.map(p, 0, 127, 0, 65535);
+            let back = l.map(s, 0, 65535, 0, 127);
+            assert!((back - p).abs() <= 1, "p = {}, back = {}", p, back);
+        }
+    }
+
+    #[test]
+    fn s_curve_is_symmetric() {
+        let c = Curve::SCurve;
+        assert_eq!(c.map(0, 0, 100, 0, 1000), 0);
+        assert_eq!(c.map(50, 0, 100, 0, 1000), 500);
+        assert_eq!(c.map(100, 0, 100, 0, 1000), 1000);
+        let low = c.map(10, 0, 100, 0, 1000);
+        let high = c.map(90, 0, 100, 0, 1000);
+        assert_eq!(low, 1000 - high);
+        assert!(low < 100);
+    }
+
+    #[test]
+    fn table_interpolates() {
+        static TABLE: [i32; 3] = [0, 100, 1000];
+        let c = Curve::Table(&TABLE);
+        assert_eq!(c.map(0, 0, 10, 0, 0), 0);
+        assert_eq!(c.map(5, 0, 10, 0, 0), 100);
+        assert_eq!(c.map(10, 0, 10, 0, 0), 1000);
+        assert_eq!(c.map(7, 0, 10, 0, 0), 460);
+        assert_eq!(Curve::Table(&[]).map(3, 0, 10, -1, 5), -1);
+    }
+
+    #[test]
+    fn unmap_round_trips_positions() {
+        for c in [Curve::Linear, Curve::Exponential(10), Curve::Logarithmic(6), Curve::SCurve] {
+            for p in [0, 1, 17, 63, 64, 126, 127] {
+                let out = c.map(p, 0, 127, 20, 20480);
+                let back = c.unmap(out, 0, 127, 20, 20480);
+                assert_eq!(c.map(back, 0, 127, 20, 20480), out, "{:?} p = {}", c, p);
+            }
+        }
+    }
+
+    #[test]
+    fn unmap_handles_decreasing_and_out_of_range() {
+        let c = Curve::Linear;
+        assert_eq!(c.unmap(24, 0, 10, 100, 0), 8); // 100 - 8 * 10 = 20 is nearest
+        assert_eq!(c.unmap(-50, 0, 10, 0, 100), 0);
+        assert_eq!(c.unmap(500, 0, 10, 0, 100), 10);
+        assert_eq!(c.unmap(3, 5, 5, 0, 100), 5);
+    }
+
+    #[test]
+    fn unmap_spans_the_full_i32_range() {
+        let c = Curve::Linear;
+        let (lo, hi) = (i32::MIN, i32::MAX);
+        assert_eq!(c.unmap(lo, lo, hi, lo, hi), lo);
+        assert_eq!(c.unmap(hi, lo, hi, lo, hi), hi);
+        assert_eq!(c.unmap(0, lo, hi, lo, hi), 0);
+    }
+}
@@ -2,12 +2,12 @@
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum ParameterError {
-    /// Page index is out of bounds (must be < N_PAGES).
+    /// Page index is out of bounds (must be < the schema's page count).
     InvalidPageIndex,
-    /// Encoder index is out of bounds (must be < PARAMS_PER_PAGE).
+    /// Encoder index is out of bounds (must be < the schema's slots per page).
     InvalidEncoderIndex,
     /// Operation targeted a [`Null`](super::ParameterSlot::Null) slot.
     NullSlot,
-    /// Global parameter index is out of bounds (must be < N_PAGES * PARAMS_PER_PAGE).
+    /// Global parameter index is out of bounds (must be < the schema's total slot count).
     InvalidGlobalIndex,
 }
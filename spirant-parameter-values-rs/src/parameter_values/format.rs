@@ -0,0 +1,176 @@
+use core::fmt::{self, Write};
+
+/// Physical unit of a parameter value, appended as a suffix when displayed.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Unit {
+    /// Dimensionless value, no suffix.
+    #[default]
+    None,
+    /// Frequency in hertz (`"Hz"`).
+    Hz,
+    /// Time in milliseconds (`"ms"`).
+    Ms,
+    /// Percentage (`"%"`).
+    Percent,
+    /// Pitch offset in semitones (`"st"`).
+    Semitones,
+}
+
+impl Unit {
+    /// Display suffix for this unit (empty for [`Unit::None`]).
+    pub const fn suffix(self) -> &'static str {
+        match self {
+            Unit::None => "",
+            Unit::Hz => "Hz",
+            Unit::Ms => "ms",
+            Unit::Percent => "%",
+            Unit::Semitones => "st",
+        }
+    }
+}
+
+/// Hint describing how a raw integer value is turned into display text.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum DisplayFormat {
+    /// Plain signed integer, e.g. `"64"`.
+    #[default]
+    Integer,
+    /// Fixed-point decimal: the value is stored scaled by
+    /// `10^decimals`, e.g. `1250` with `decimals: 2` shows `"12.50"`.
+    Fixed {
+        /// Number of digits after the decimal point.
+        decimals: u8,
+    },
+    /// Position within `[min, max]` as a whole percentage, e.g. `"50%"`.
+    /// The unit suffix is not appended (the `%` sign replaces it).
+    Percent,
+    /// Signed offset from the centre of `[min, max]` with an explicit
+    /// sign, e.g. `"+12"`, `"-3"` or `"0"`.
+    Bipolar,
+}
+
+/// Write `value` to `w` according to `format`, followed by `unit`'s suffix.
+///
+/// `min` and `max` are the parameter's range, needed by the
+/// [`Percent`](DisplayFormat::Percent) and [`Bipolar`](DisplayFormat::Bipolar)
+/// formats.
+pub(crate) fn write_value<W: Write>(
+    w: &mut W,
+    value: i32,
+    min: i32,
+    max: i32,
+    format: DisplayFormat,
+    unit: Unit,
+) -> fmt::Result {
+    match format {
+        DisplayFormat::Integer => write!(w, "{}", value)?,
+        DisplayFormat::Fixed { decimals } => match 10i64.checked_pow(decimals as u32) {
+            Some(scale) => {
+                let v = value as i64;
+                let sign = if v < 0 { "-" } else { "" };
+                let whole = v.abs() / scale;
+                let frac = v.abs() % scale;
+                if decimals == 0 {
+                    write!(w, "{}{}", sign, whole)?;
+                } else {
+                    write!(w, "{}{}.{:0width$}", sign, whole, frac, width = decimals as usize)?;
+                }
+            }
+            // Past 18 decimals the scale no longer fits an i64 (and no
+            // i32 needs that many): show the stored integer instead.
+            None => write!(w, "{}", value)?,
+        },
+        DisplayFormat::Percent => {
+            let span = (max as i64 - min as i64).max(1);
+            let pct = (value as i64 - min as i64) * 100 / span;
+            // The percent sign stands in for the unit suffix.
+            return write!(w, "{}%", pct);
+        }
+        DisplayFormat::Bipolar => {
+            let centre = (min as i64 + max as i64) / 2;
+            let offset = value as i64 - centre;
+            if offset > 0 {
+                write!(w, "+{}", offset)?;
+            } else {
+                write!(w, "{}", offset)?;
+            }
+        }
+    }
+    w.write_str(unit.suffix())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Minimal fixed-capacity `fmt::Write` sink for tests.
+    struct Buf {
+        bytes: [u8; 32],
+        len: usize,
+    }
+
+    impl Buf {
+        fn new() -> Self {
+            Self { bytes: [0; 32], len: 0 }
+        }
+
+        fn as_str(&self) -> &str {
+            core::str::from_utf8(&self.bytes[..self.len]).unwrap()
+        }
+    }
+
+    impl Write for Buf {
+        fn write_str(&mut self, s: &str) -> fmt::Result {
+            let end = self.len + s.len();
+            if end > self.bytes.len() {
+                return Err(fmt::Error);
+            }
+            self.bytes[self.len..end].copy_from_slice(s.as_bytes());
+            self.len = end;
+            Ok(())
+        }
+    }
+
+    fn render(value: i32, min: i32, max: i32, format: DisplayFormat, unit: Unit) -> Buf {
+        let mut buf = Buf::new();
+        write_value(&mut buf, value, min, max, format, unit).unwrap();
+        buf
+    }
+
+    #[test]
+    fn integer_with_unit() {
+        assert_eq!(render(440, 20, 20000, DisplayFormat::Integer, Unit::Hz).as_str(), "440Hz");
+        assert_eq!(render(-7, -12, 12, DisplayFormat::Integer, Unit::Semitones).as_str(), "-7st");
+        assert_eq!(render(64, 0, 127, DisplayFormat::Integer, Unit::None).as_str(), "64");
+    }
+
+    #[test]
+    fn fixed_point_decimal() {
+        let two = DisplayFormat::Fixed { decimals: 2 };
+        assert_eq!(render(1250, 0, 10000, two, Unit::Ms).as_str(), "12.50ms");
+        assert_eq!(render(5, 0, 10000, two, Unit::None).as_str(), "0.05");
+        assert_eq!(render(-105, -1000, 1000, two, Unit::None).as_str(), "-1.05");
+        let zero = DisplayFormat::Fixed { decimals: 0 };
+        assert_eq!(render(42, 0, 100, zero, Unit::None).as_str(), "42");
+        let max = DisplayFormat::Fixed { decimals: 18 };
+        assert_eq!(render(-5, -10, 10, max, Unit::None).as_str(), "-0.000000000000000005");
+        let too_many = DisplayFormat::Fixed { decimals: 19 };
+        assert_eq!(render(1250, 0, 10000, too_many, Unit::Ms).as_str(), "1250ms");
+    }
+
+    #[test]
+    fn percent_of_range_ignores_unit() {
+        assert_eq!(render(0, 0, 127, DisplayFormat::Percent, Unit::None).as_str(), "0%");
+        assert_eq!(render(127, 0, 127, DisplayFormat::Percent, Unit::Percent).as_str(), "100%");
+        assert_eq!(render(50, 0, 200, DisplayFormat::Percent, Unit::Hz).as_str(), "25%");
+    }
+
+    #[test]
+    fn bipolar_around_centre() {
+        assert_eq!(render(64, 0, 128, DisplayFormat::Bipolar, Unit::None).as_str(), "0");
+        assert_eq!(render(76, 0, 128, DisplayFormat::Bipolar, Unit::None).as_str(), "+12");
+        assert_eq!(render(-3, -12, 12, DisplayFormat::Bipolar, Unit::Semitones).as_str(), "-3st");
+    }
+}
@@ -18,6 +18,9 @@
 //! Page 3 (Effects):  [Delay]  [Reverb]    [---Null---]  [---Null---]
 //! ```
 //!
+//! The layout above is [`DEFAULT_SCHEMA`]. Other engines supply their own
+//! static [`ParameterSchema`] table instead of editing this crate.
+//!
 //! # Change Tracking
 //!
 //! Each parameter carries two independent change flags:
@@ -35,61 +38,114 @@
 //! # `no_std` Compatibility
 //!
 //! This module uses no heap allocation. All storage is fixed-size arrays
-//! sized by the [`N_PAGES`] and [`PARAMS_PER_PAGE`] constants. The
-//! optional `defmt` feature enables structured logging for embedded targets.
+//! sized by the const generic page and slot counts of the active
+//! [`ParameterSchema`] (defaulting to [`N_PAGES`] and [`PARAMS_PER_PAGE`]).
+//! The optional `defmt` feature enables structured logging for embedded
+//! targets.
 
+mod curve;
 mod error;
+mod format;
 mod page;
 mod parameter;
+mod schema;
 mod values;
 
+pub use curve::Curve;
 pub use error::ParameterError;
+pub use format::{DisplayFormat, Unit};
 pub use page::Page;
 pub use parameter::{Parameter, ParameterSlot};
-pub use values::{ParameterChange, ParameterValues};
+pub use schema::{PageDescriptor, ParamDescriptor, ParamKind, ParameterSchema};
+pub use values::{ParameterChange, ParameterChanges, ParameterValues};
 
-/// Number of parameter slots per page (matches the number of physical encoders).
+/// Number of parameter slots per page in the default layout (matches the
+/// number of physical encoders).
 pub const PARAMS_PER_PAGE: usize = 4;
 
-/// Number of pages in the parameter system.
+/// Number of pages in the default layout.
 pub const N_PAGES: usize = 4;
 
-/// Human-readable page names for UI display, indexed by page number.
-pub const PAGE_NAMES: [&str; N_PAGES] = ["Filter", "Envelope", "LFO", "Effects"];
-
-/// Parameter names organized by page and encoder slot.
-///
-/// `PARAM_NAMES[page][encoder]` is `Some("Name")` for active slots and
-/// `None` for null slots. This constant drives the initialization of
-/// [`ParameterValues::new()`] — every `Some` becomes an
-/// [`Active`](ParameterSlot::Active) slot and every `None` becomes
-/// [`Null`](ParameterSlot::Null).
+/// Default page/slot layout used by [`ParameterValues::new()`].
 ///
-/// **Invariant:** The runtime [`ParameterSlot`] layout must always match
-/// this table. Modifying parameter names or null positions here requires
-/// no other code changes — `new()` derives the layout automatically.
-pub const PARAM_NAMES: [[Option<&str>; PARAMS_PER_PAGE]; N_PAGES] = [
+/// Every `Some` descriptor becomes an [`Active`](ParameterSlot::Active)
+/// slot and every `None` becomes [`Null`](ParameterSlot::Null). Engines
+/// with a different layout define their own [`ParameterSchema`] and pass
+/// it to [`ParameterValues::with_schema()`].
+pub static DEFAULT_SCHEMA: ParameterSchema<N_PAGES, PARAMS_PER_PAGE> = ParameterSchema::new([
     // Page 0: Filter (all 4 slots active)
-    [
-        Some("Cutoff"),
-        Some("Resonance"),
-        Some("Filter Type"),
-        Some("Filter Env"),
-    ],
+    PageDescriptor::new(
+        "Filter",
+        [
+            Some(
+                ParamDescriptor::new("Cutoff")
+                    .curve(Curve::Exponential(10))
+                    .output_range(20, 20480)
+                    .unit(Unit::Hz),
+            ),
+            Some(ParamDescriptor::new("Resonance")),
+            Some(ParamDescriptor::new("Filter Type").discrete(&["LP", "HP", "BP"])),
+            Some(ParamDescriptor::new("Filter Env").format(DisplayFormat::Percent)),
+        ],
+    ),
     // Page 1: Envelope (all 4 slots active)
-    [
-        Some("Attack"),
-        Some("Decay"),
-        Some("Sustain"),
-        Some("Release"),
-    ],
+    PageDescriptor::new(
+        "Envelope",
+        [
+            Some(
+                ParamDescriptor::new("Attack")
+                    .curve(Curve::Logarithmic(12))
+                    .output_range(1, 4096)
+                    .unit(Unit::Ms),
+            ),
+            Some(
+                ParamDescriptor::new("Decay")
+                    .curve(Curve::Logarithmic(13))
+                    .output_range(1, 8192)
+                    .unit(Unit::Ms),
+            ),
+            Some(ParamDescriptor::new("Sustain").format(DisplayFormat::Percent)),
+            Some(
+                ParamDescriptor::new("Release")
+                    .curve(Curve::Logarithmic(13))
+                    .output_range(1, 8192)
+                    .unit(Unit::Ms),
+            ),
+        ],
+    ),
     // Page 2: LFO (3 active, encoder 4 is null)
-    [
-        Some("LFO Rate"),
-        Some("LFO Depth"),
-        Some("LFO Shape"),
-        None,
-    ],
+    PageDescriptor::new(
+        "LFO",
+        [
+            // Centihertz: 0.05 Hz – 51.20 Hz.
+            Some(
+                ParamDescriptor::new("LFO Rate")
+                    .curve(Curve::Exponential(10))
+                    .output_range(5, 5120)
+                    .format(DisplayFormat::Fixed { decimals: 2 })
+                    .unit(Unit::Hz),
+            ),
+            Some(ParamDescriptor::new("LFO Depth").format(DisplayFormat::Percent)),
+            Some(
+                ParamDescriptor::new("LFO Shape")
+                    .discrete(&["Sine", "Tri", "Saw", "Square"])
+                    .wrapping(),
+            ),
+            None,
+        ],
+    ),
     // Page 3: Effects (2 active, encoders 3 & 4 are null)
-    [Some("Delay Time"), Some("Reverb"), None, None],
-];
+    PageDescriptor::new(
+        "Effects",
+        [
+            Some(
+                ParamDescriptor::new("Delay Time")
+                    .output_range(0, 1000)
+                    .unit(Unit::Ms),
+            ),
+            Some(ParamDescriptor::new("Reverb").format(DisplayFormat::Percent)),
+            None,
+            None,
+        ],
+    ),
+]);
@@ -1,3 +1,5 @@
+use super::schema::ParamDescriptor;
+
 /// Individual synthesizer parameter with value, range, and change tracking.
 ///
 /// Each parameter has a clamped value range and two independent change flags
@@ -30,6 +32,21 @@ impl Default for Parameter {
 }
 
 impl Parameter {
+    /// Create a parameter with the range and default value of `descriptor`.
+    ///
+    /// The default is clamped into `[min_value, max_value]`. No change flags
+    /// are set.
+    pub fn from_descriptor(descriptor: &ParamDescriptor) -> Self {
+        let (min_value, max_value) = descriptor.position_bounds();
+        Self {
+            value: descriptor.default_value.clamp(min_value, max_value),
+            min_value,
+            max_value,
+            changed_oled: false,
+            changed_i2c: false,
+        }
+    }
+
     /// Update the value from an encoder or other local source.
     ///
     /// Clamps the new value to `[min_value, max_value]` and sets **both**
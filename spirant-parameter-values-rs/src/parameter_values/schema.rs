@@ -0,0 +1,381 @@
+use core::fmt;
+
+use super::curve::Curve;
+use super::format::{self, DisplayFormat, Unit};
+
+/// Whether a parameter takes a continuous numeric range or one of a fixed
+/// set of labelled options.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum ParamKind {
+    /// Numeric value in `[min_value, max_value]`, moved by `step` per tick.
+    #[default]
+    Continuous,
+    /// Enumerated value: the value is an index into `labels`.
+    Discrete {
+        /// Display label for each option, indexed by value.
+        labels: &'static [&'static str],
+        /// If `true`, stepping past the last option returns to the first
+        /// (and vice versa) instead of stopping at the end.
+        wrap: bool,
+    },
+}
+
+/// Static description of a single active parameter slot.
+///
+/// Carries the parameter's name, range, default, encoder step size and
+/// display metadata. Descriptors are `const`-constructible so that whole
+/// layouts can live in flash as `static` tables; start from
+/// [`ParamDescriptor::new()`] and chain the builder methods:
+///
+/// ```
+/// use spirant::parameter_values::{Curve, DisplayFormat, ParamDescriptor, Unit};
+///
+/// const ATTACK: ParamDescriptor = ParamDescriptor::new("Attack")
+///     .range(0, 5000)
+///     .default_value(10)
+///     .step(5)
+///     .unit(Unit::Ms);
+///
+/// const DETUNE: ParamDescriptor = ParamDescriptor::new("Detune")
+///     .range(-12, 12)
+///     .format(DisplayFormat::Bipolar)
+///     .unit(Unit::Semitones);
+///
+/// // 128 encoder detents spread exponentially over 20 Hz – 20 kHz.
+/// const CUTOFF: ParamDescriptor = ParamDescriptor::new("Cutoff")
+///     .range(0, 127)
+///     .curve(Curve::Exponential(10))
+///     .output_range(20, 20480)
+///     .unit(Unit::Hz);
+/// assert_eq!(CUTOFF.map_value(127), 20480);
+///
+/// const SHAPE: ParamDescriptor = ParamDescriptor::new("LFO Shape")
+///     .discrete(&["Sine", "Tri", "Saw", "Square"])
+///     .wrapping();
+/// assert_eq!(SHAPE.label(2), Some("Saw"));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct ParamDescriptor {
+    /// Human-readable parameter name for UI display.
+    pub name: &'static str,
+    /// Initial value applied by [`ParameterValues::with_schema()`](super::ParameterValues::with_schema). Default: 0.
+    pub default_value: i32,
+    /// Minimum encoder position (inclusive). Default: 0.
+    pub min_value: i32,
+    /// Maximum encoder position (inclusive). Default: 127.
+    pub max_value: i32,
+    /// Value change per encoder tick. Default: 1.
+    pub step: i32,
+    /// Physical unit shown after the value. Default: [`Unit::None`].
+    pub unit: Unit,
+    /// How the value is rendered as text. Default: [`DisplayFormat::Integer`].
+    /// Ignored for [`Discrete`](ParamKind::Discrete) parameters, which
+    /// show their label.
+    pub format: DisplayFormat,
+    /// Continuous or discrete. Default: [`ParamKind::Continuous`].
+    pub kind: ParamKind,
+    /// Response curve from position to output value. Default:
+    /// [`Curve::Linear`]. Ignored for discrete parameters.
+    pub curve: Curve,
+    /// Output value range `(min, max)`. `None` (the default) means the
+    /// output range equals the position range.
+    pub output_range: Option<(i32, i32)>,
+}
+
+impl ParamDescriptor {
+    /// Create a descriptor for a parameter called `name` with the default
+    /// 0..=127 range, step 1 and plain integer display.
+    pub const fn new(name: &'static str) -> Self {
+        Self {
+            name,
+            default_value: 0,
+            min_value: 0,
+            max_value: 127,
+            step: 1,
+            unit: Unit::None,
+            format: DisplayFormat::Integer,
+            kind: ParamKind::Continuous,
+            curve: Curve::Linear,
+            output_range: None,
+        }
+    }
+
+    /// Make this a discrete parameter choosing one of `labels`.
+    ///
+    /// Sets the range to `0..=labels.len() - 1` and the step to 1. Call
+    /// [`wrapping()`](Self::wrapping) afterwards to wrap around at the ends.
+    pub const fn discrete(mut self, labels: &'static [&'static str]) -> Self {
+        self.kind = ParamKind::Discrete { labels, wrap: false };
+        self.min_value = 0;
+        self.max_value = if labels.is_empty() { 0 } else { labels.len() as i32 - 1 };
+        self.step = 1;
+        self
+    }
+
+    /// Let a discrete parameter wrap from its last option to its first and
+    /// back. No effect on continuous parameters.
+    pub const fn wrapping(mut self) -> Self {
+        if let ParamKind::Discrete { labels, .. } = self.kind {
+            self.kind = ParamKind::Discrete { labels, wrap: true };
+        }
+        self
+    }
+
+    /// Label for `value`, or `None` for continuous parameters and
+    /// out-of-range values.
+    pub fn label(&self, value: i32) -> Option<&'static str> {
+        match self.kind {
+            ParamKind::Continuous => None,
+            ParamKind::Discrete { labels, .. } => {
+                usize::try_from(value).ok().and_then(|i| labels.get(i).copied())
+            }
+        }
+    }
+
+    /// Compute the value reached by moving `delta` encoder ticks from `value`.
+    ///
+    /// Continuous parameters move by `delta * step` and clamp to the range.
+    /// Discrete parameters move one option per tick and either clamp or
+    /// wrap around, depending on the `wrap` setting.
+    pub fn step_value(&self, value: i32, delta: i32) -> i32 {
+        let (min, max) = self.position_bounds();
+        match self.kind {
+            ParamKind::Continuous => value
+                .saturating_add(delta.saturating_mul(self.step))
+                .clamp(min, max),
+            ParamKind::Discrete { wrap: true, .. } => {
+                // At least 1, since the bounds are ordered.
+                let count = max as i64 - min as i64 + 1;
+                let offset = (value as i64 - min as i64 + delta as i64).rem_euclid(count);
+                (min as i64 + offset) as i32
+            }
+            ParamKind::Discrete { wrap: false, .. } => value.saturating_add(delta).clamp(min, max),
+        }
+    }
+
+    /// Position range `(min, max)`, ordered even if the public fields were
+    /// set the wrong way round.
+    pub(crate) fn position_bounds(&self) -> (i32, i32) {
+        if self.min_value <= self.max_value {
+            (self.min_value, self.max_value)
+        } else {
+            (self.max_value, self.min_value)
+        }
+    }
+
+    /// Set the inclusive value range. Bounds given the wrong way round are
+    /// swapped.
+    pub const fn range(mut self, min_value: i32, max_value: i32) -> Self {
+        if min_value <= max_value {
+            self.min_value = min_value;
+            self.max_value = max_value;
+        } else {
+            self.min_value = max_value;
+            self.max_value = min_value;
+        }
+        self
+    }
+
+    /// Set the initial value (clamped to the range when applied).
+    pub const fn default_value(mut self, value: i32) -> Self {
+        self.default_value = value;
+        self
+    }
+
+    /// Set the value change per encoder tick.
+    pub const fn step(mut self, step: i32) -> Self {
+        self.step = step;
+        self
+    }
+
+    /// Set the display unit.
+    pub const fn unit(mut self, unit: Unit) -> Self {
+        self.unit = unit;
+        self
+    }
+
+    /// Set the response curve from position to output value.
+    pub const fn curve(mut self, curve: Curve) -> Self {
+        self.curve = curve;
+        self
+    }
+
+    /// Set the output value range the curve maps onto.
+    pub const fn output_range(mut self, min: i32, max: i32) -> Self {
+        self.output_range = Some((min, max));
+        self
+    }
+
+    /// Output range `(min, max)`, falling back to the (ordered) position
+    /// range.
+    pub fn output_bounds(&self) -> (i32, i32) {
+        self.output_range.unwrap_or_else(|| self.position_bounds())
+    }
+
+    /// Map an encoder position to the output value sent to the synth and
+    /// shown on the display.
+    ///
+    /// Discrete parameters, and linear ones without an output range, return
+    /// the position unchanged.
+    pub fn map_value(&self, position: i32) -> i32 {
+        if matches!(self.kind, ParamKind::Discrete { .. })
+            || (self.curve == Curve::Linear && self.output_range.is_none())
+        {
+            return position;
+        }
+        let (min, max) = self.position_bounds();
+        let (out_min, out_max) = self.output_bounds();
+        self.curve.map(position, min, max, out_min, out_max)
+    }
+
+    /// Map an output value back to the nearest encoder position (the
+    /// inverse of [`map_value()`](Self::map_value)), clamped to the
+    /// position range.
+    pub fn unmap_value(&self, output: i32) -> i32 {
+        let (min, max) = self.position_bounds();
+        if matches!(self.kind, ParamKind::Discrete { .. })
+            || (self.curve == Curve::Linear && self.output_range.is_none())
+        {
+            return output.clamp(min, max);
+        }
+        let (out_min, out_max) = self.output_bounds();
+        self.curve.unmap(output, min, max, out_min, out_max)
+    }
+
+    /// Set the display format hint.
+    pub const fn format(mut self, format: DisplayFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// Write the parameter at encoder position `value` as display text: the
+    /// label for discrete parameters, otherwise the curve-mapped output
+    /// value, formatted and followed by the unit suffix.
+    ///
+    /// Works with any [`fmt::Write`] sink, e.g. a `heapless::String`, so it
+    /// is usable in `no_std` display code.
+    pub fn write_value<W: fmt::Write>(&self, value: i32, w: &mut W) -> fmt::Result {
+        if let Some(label) = self.label(value) {
+            return w.write_str(label);
+        }
+        let (out_min, out_max) = self.output_bounds();
+        format::write_value(w, self.map_value(value), out_min, out_max, self.format, self.unit)
+    }
+}
+
+/// Static description of one page of parameter slots.
+///
+/// `params[slot]` is `Some(descriptor)` for active slots and `None` for
+/// null slots.
+#[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct PageDescriptor<const PARAMS_PER_PAGE: usize> {
+    /// Human-readable page name for UI display.
+    pub name: &'static str,
+    /// Slot descriptors indexed by encoder position.
+    pub params: [Option<ParamDescriptor>; PARAMS_PER_PAGE],
+}
+
+impl<const PARAMS_PER_PAGE: usize> PageDescriptor<PARAMS_PER_PAGE> {
+    /// Create a page descriptor from a name and its slot table.
+    pub const fn new(
+        name: &'static str,
+        params: [Option<ParamDescriptor>; PARAMS_PER_PAGE],
+    ) -> Self {
+        Self { name, params }
+    }
+}
+
+/// Page/slot layout of a synthesizer engine.
+///
+/// A schema is the single source of truth for which slots are
+/// [`Active`](super::ParameterSlot::Active) or
+/// [`Null`](super::ParameterSlot::Null) and what they are called.
+/// [`ParameterValues`](super::ParameterValues) derives its storage layout
+/// from a schema, and the change-consumption methods read names from it.
+///
+/// Different engines (subtractive, FM, 4-operator, ...) each define their
+/// own `static` schema; the firmware picks one at start-up with
+/// [`ParameterValues::with_schema()`](super::ParameterValues::with_schema)
+/// or swaps it later with
+/// [`ParameterValues::load_schema()`](super::ParameterValues::load_schema).
+///
+/// # Examples
+///
+/// ```
+/// use spirant::parameter_values::{
+///     PageDescriptor, ParamDescriptor, ParameterSchema, ParameterValues,
+/// };
+///
+/// static FM_SCHEMA: ParameterSchema<2, 4> = ParameterSchema::new([
+///     PageDescriptor::new(
+///         "Operator",
+///         [
+///             Some(ParamDescriptor::new("Ratio")),
+///             Some(ParamDescriptor::new("Index")),
+///             None,
+///             None,
+///         ],
+///     ),
+///     PageDescriptor::new(
+///         "Envelope",
+///         [
+///             Some(ParamDescriptor::new("Attack")),
+///             Some(ParamDescriptor::new("Decay")),
+///             Some(ParamDescriptor::new("Sustain")),
+///             Some(ParamDescriptor::new("Release")),
+///         ],
+///     ),
+/// ]);
+///
+/// let pv = ParameterValues::with_schema(&FM_SCHEMA);
+/// assert_eq!(pv.count_active_params(0), 2);
+/// assert_eq!(FM_SCHEMA.param_name(1, 3), Some("Release"));
+/// ```
+#[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct ParameterSchema<const N_PAGES: usize, const PARAMS_PER_PAGE: usize> {
+    /// Page descriptors indexed by page number.
+    pub pages: [PageDescriptor<PARAMS_PER_PAGE>; N_PAGES],
+}
+
+impl<const N_PAGES: usize, const PARAMS_PER_PAGE: usize> ParameterSchema<N_PAGES, PARAMS_PER_PAGE> {
+    /// Create a schema from a static page table.
+    pub const fn new(pages: [PageDescriptor<PARAMS_PER_PAGE>; N_PAGES]) -> Self {
+        Self { pages }
+    }
+
+    /// Number of pages in this schema.
+    pub const fn n_pages(&self) -> usize {
+        N_PAGES
+    }
+
+    /// Number of slots on every page.
+    pub const fn params_per_page(&self) -> usize {
+        PARAMS_PER_PAGE
+    }
+
+    /// Total number of slots (active and null) across all pages.
+    pub const fn total_slots(&self) -> usize {
+        N_PAGES * PARAMS_PER_PAGE
+    }
+
+    /// Name of page `page`, or `None` if out of bounds.
+    pub fn page_name(&self, page: usize) -> Option<&'static str> {
+        self.pages.get(page).map(|p| p.name)
+    }
+
+    /// Descriptor for the given slot, or `None` if the slot is null or
+    /// out of bounds.
+    pub fn param(&self, page: usize, slot: usize) -> Option<&ParamDescriptor> {
+        self.pages.get(page)?.params.get(slot)?.as_ref()
+    }
+
+    /// Name of the given slot, or `None` if the slot is null or out of
+    /// bounds.
+    pub fn param_name(&self, page: usize, slot: usize) -> Option<&'static str> {
+        self.param(page, slot).map(|d| d.name)
+    }
+}
@@ -1,24 +1,91 @@
+use core::ops::Index;
+
 use super::error::ParameterError;
 use super::page::Page;
 use super::parameter::{Parameter, ParameterSlot};
-use super::{N_PAGES, PARAMS_PER_PAGE, PARAM_NAMES};
+use super::schema::ParameterSchema;
+use super::DEFAULT_SCHEMA;
 
 /// Describes a single parameter change, returned by the change consumption methods.
 #[derive(Debug, Clone, Copy)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct ParameterChange {
-    /// Static display name of the parameter (from [`PARAM_NAMES`]).
+    /// Static display name of the parameter (from the active [`ParameterSchema`]).
     pub name: &'static str,
-    /// Current value after the change.
+    /// Output value after the change: the encoder position mapped through
+    /// the parameter's response [`Curve`](super::Curve), in real units.
     pub value: i32,
+    /// Option label for [`Discrete`](super::ParamKind::Discrete)
+    /// parameters (e.g. `"Saw"`), `None` for continuous ones.
+    pub label: Option<&'static str>,
     /// Page index (0-based).
     pub page: usize,
     /// Encoder/slot index within the page (0-based).
     pub encoder: usize,
 }
 
-/// Total number of parameter slots across all pages.
-const TOTAL_SLOTS: usize = N_PAGES * PARAMS_PER_PAGE;
+/// Fixed-capacity list of [`ParameterChange`]s returned by
+/// [`ParameterValues::take_oled_changes()`] and
+/// [`ParameterValues::take_i2c_changes()`].
+///
+/// Capacity is one entry per slot of the schema, so every change always
+/// fits. Indexing past [`len()`](Self::len) (but within capacity) yields
+/// `None`, matching the behaviour of a `None`-padded array.
+#[derive(Debug, Clone, Copy)]
+pub struct ParameterChanges<const N_PAGES: usize, const PARAMS_PER_PAGE: usize> {
+    /// Storage laid out row-major: entry `k` lives at
+    /// `entries[k / PARAMS_PER_PAGE][k % PARAMS_PER_PAGE]`.
+    entries: [[Option<ParameterChange>; PARAMS_PER_PAGE]; N_PAGES],
+    len: usize,
+}
+
+impl<const N_PAGES: usize, const PARAMS_PER_PAGE: usize> ParameterChanges<N_PAGES, PARAMS_PER_PAGE> {
+    fn new() -> Self {
+        Self {
+            entries: [[None; PARAMS_PER_PAGE]; N_PAGES],
+            len: 0,
+        }
+    }
+
+    fn push(&mut self, change: ParameterChange) {
+        let k = self.len;
+        self.entries[k / PARAMS_PER_PAGE][k % PARAMS_PER_PAGE] = Some(change);
+        self.len += 1;
+    }
+
+    /// Number of changes collected.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if no changes were collected.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the change at position `idx`, or `None` if `idx >= len()`.
+    pub fn get(&self, idx: usize) -> Option<&ParameterChange> {
+        if idx >= self.len {
+            return None;
+        }
+        self.entries[idx / PARAMS_PER_PAGE][idx % PARAMS_PER_PAGE].as_ref()
+    }
+
+    /// Iterate over the collected changes in page/slot order.
+    pub fn iter(&self) -> impl Iterator<Item = &ParameterChange> {
+        self.entries.iter().flatten().take(self.len).flatten()
+    }
+}
+
+impl<const N_PAGES: usize, const PARAMS_PER_PAGE: usize> Index<usize>
+    for ParameterChanges<N_PAGES, PARAMS_PER_PAGE>
+{
+    type Output = Option<ParameterChange>;
+
+    fn index(&self, idx: usize) -> &Self::Output {
+        &self.entries[idx / PARAMS_PER_PAGE][idx % PARAMS_PER_PAGE]
+    }
+}
 
 /// Main parameter storage with page-based organization.
 ///
@@ -26,18 +93,33 @@ const TOTAL_SLOTS: usize = N_PAGES * PARAMS_PER_PAGE;
 /// change flags for the OLED display and I2C communication consumers,
 /// and provides the shared data structure accessed by multiple async tasks.
 ///
+/// The page and slot counts are const generics taken from the
+/// [`ParameterSchema`] the instance was built from. They default to
+/// [`N_PAGES`](super::N_PAGES) and [`PARAMS_PER_PAGE`](super::PARAMS_PER_PAGE),
+/// so a bare `ParameterValues` is the [`DEFAULT_SCHEMA`] layout.
+///
 /// # Initialization
 ///
-/// [`ParameterValues::new()`] builds the page/slot layout from the static
-/// [`PARAM_NAMES`] configuration. Every `Some(name)` entry becomes an
-/// [`Active`](ParameterSlot::Active) slot with default values; every `None`
+/// [`ParameterValues::new()`] builds the page/slot layout from
+/// [`DEFAULT_SCHEMA`]; [`ParameterValues::with_schema()`] builds it from any
+/// other static schema. Every `Some(descriptor)` entry becomes an
+/// [`Active`](ParameterSlot::Active) slot with the descriptor's range and
+/// default value; every `None`
 /// becomes [`Null`](ParameterSlot::Null).
-pub struct ParameterValues {
+pub struct ParameterValues<
+    const N_PAGES: usize = { super::N_PAGES },
+    const PARAMS_PER_PAGE: usize = { super::PARAMS_PER_PAGE },
+> {
     /// All pages, indexed 0 to `N_PAGES - 1`.
-    pub pages: [Page; N_PAGES],
+    pub pages: [Page<PARAMS_PER_PAGE>; N_PAGES],
     /// Index of the currently active page (determines which parameters
     /// the physical encoders control).
     pub current_page: usize,
+    /// Layout and names the pages were built from.
+    schema: &'static ParameterSchema<N_PAGES, PARAMS_PER_PAGE>,
+    /// Set when the active page (and so the active-slot mask) has changed
+    /// since the last [`take_encoder_mask()`](Self::take_encoder_mask).
+    changed_encoder_mask: bool,
 }
 
 impl Default for ParameterValues {
@@ -47,28 +129,47 @@ impl Default for ParameterValues {
 }
 
 impl ParameterValues {
-    /// Create a new instance with Active/Null slots derived from [`PARAM_NAMES`].
+    /// Create a new instance with Active/Null slots derived from [`DEFAULT_SCHEMA`].
     ///
-    /// Slots corresponding to `Some(name)` in `PARAM_NAMES` are initialized as
-    /// `Active(Parameter::default())`. Slots corresponding to `None` are `Null`.
+    /// Equivalent to `ParameterValues::with_schema(&DEFAULT_SCHEMA)`.
     pub fn new() -> Self {
-        let mut pages = [Page::default(); N_PAGES];
-
-        for (page_idx, page) in pages.iter_mut().enumerate() {
-            for (slot_idx, slot) in page.params.iter_mut().enumerate() {
-                *slot = match PARAM_NAMES[page_idx][slot_idx] {
-                    Some(_) => ParameterSlot::Active(Parameter::default()),
-                    None => ParameterSlot::Null,
-                };
-            }
-        }
+        Self::with_schema(&DEFAULT_SCHEMA)
+    }
+}
 
+impl<const N_PAGES: usize, const PARAMS_PER_PAGE: usize> ParameterValues<N_PAGES, PARAMS_PER_PAGE> {
+    /// Create a new instance with Active/Null slots derived from `schema`.
+    ///
+    /// Slots described by `Some(descriptor)` are initialized as `Active`
+    /// with the descriptor's range and default value (see
+    /// [`Parameter::from_descriptor()`]). Slots described by `None` are `Null`.
+    pub fn with_schema(schema: &'static ParameterSchema<N_PAGES, PARAMS_PER_PAGE>) -> Self {
         Self {
-            pages,
+            pages: Self::build_pages(schema),
             current_page: 0,
+            schema,
+            changed_encoder_mask: true,
         }
     }
 
+    /// Replace the layout with `schema`, resetting every slot to its
+    /// default and returning to page 0.
+    ///
+    /// Used when the firmware switches synthesis engine at runtime. All
+    /// active slots on page 0 are marked as changed for the OLED display so
+    /// the new layout is drawn immediately.
+    pub fn load_schema(&mut self, schema: &'static ParameterSchema<N_PAGES, PARAMS_PER_PAGE>) {
+        self.schema = schema;
+        self.pages = Self::build_pages(schema);
+        // Page 0 always exists when N_PAGES > 0; an empty schema has nothing to mark.
+        let _ = self.set_active_page(0);
+    }
+
+    /// Returns the schema this instance was built from.
+    pub fn schema(&self) -> &'static ParameterSchema<N_PAGES, PARAMS_PER_PAGE> {
+        self.schema
+    }
+
     // ── Page navigation ──────────────────────────────────────────────
 
     /// Returns the index of the currently active page.
@@ -95,6 +196,9 @@ impl ParameterValues {
     ///
     /// This is the typical method to call when the user switches pages,
     /// since the display needs to redraw all parameter names and values.
+    /// The new page's active-slot mask is queued for
+    /// [`take_encoder_mask()`](Self::take_encoder_mask) so the encoder
+    /// driver can mask interrupts of unmapped encoders.
     ///
     /// Returns [`ParameterError::InvalidPageIndex`] if `page >= N_PAGES`.
     pub fn set_active_page(&mut self, page: usize) -> Result<(), ParameterError> {
@@ -102,6 +206,7 @@ impl ParameterValues {
             return Err(ParameterError::InvalidPageIndex);
         }
         self.current_page = page;
+        self.changed_encoder_mask = true;
 
         for slot in &mut self.pages[page].params {
             if let ParameterSlot::Active(param) = slot {
@@ -111,13 +216,54 @@ impl ParameterValues {
         Ok(())
     }
 
+    /// Bitmask of the [`Active`](ParameterSlot::Active) slots on the current
+    /// page: bit `i` is set if encoder `i` controls a parameter.
+    ///
+    /// Only the first 32 slots are represented.
+    pub fn active_encoder_mask(&self) -> u32 {
+        self.get_active_page()
+            .params
+            .iter()
+            .take(32)
+            .enumerate()
+            .filter(|(_, slot)| slot.is_active())
+            .fold(0, |mask, (i, _)| mask | (1 << i))
+    }
+
+    /// Return the [active-slot mask](Self::active_encoder_mask) if the page
+    /// has changed since the last call, and clear the pending flag.
+    ///
+    /// A freshly constructed instance reports its initial mask once, so the
+    /// first call can be used to configure encoder interrupts at startup.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use spirant::parameter_values::ParameterValues;
+    ///
+    /// let mut pv = ParameterValues::new();
+    /// assert_eq!(pv.take_encoder_mask(), Some(0b1111));
+    /// assert_eq!(pv.take_encoder_mask(), None);
+    ///
+    /// // Page 2 (LFO) has a Null slot on encoder 3.
+    /// pv.set_active_page(2).unwrap();
+    /// assert_eq!(pv.take_encoder_mask(), Some(0b0111));
+    /// ```
+    pub fn take_encoder_mask(&mut self) -> Option<u32> {
+        if !self.changed_encoder_mask {
+            return None;
+        }
+        self.changed_encoder_mask = false;
+        Some(self.active_encoder_mask())
+    }
+
     /// Returns an immutable reference to the currently active page.
-    pub fn get_active_page(&self) -> &Page {
+    pub fn get_active_page(&self) -> &Page<PARAMS_PER_PAGE> {
         &self.pages[self.current_page]
     }
 
     /// Returns a mutable reference to the currently active page.
-    pub fn get_active_page_mut(&mut self) -> &mut Page {
+    pub fn get_active_page_mut(&mut self) -> &mut Page<PARAMS_PER_PAGE> {
         &mut self.pages[self.current_page]
     }
 
@@ -125,6 +271,11 @@ impl ParameterValues {
 
     /// Apply an encoder delta to a slot on the **current page**.
     ///
+    /// The new value is computed by
+    /// [`ParamDescriptor::step_value()`](super::ParamDescriptor::step_value):
+    /// continuous parameters move by `delta * step` and clamp to their range;
+    /// discrete parameters move one option per tick and clamp or wrap.
+    ///
     /// If `encoder_idx` is out of bounds or the slot is
     /// [`Null`](ParameterSlot::Null), the call is a silent no-op (logged
     /// via `defmt` when that feature is enabled).
@@ -153,10 +304,15 @@ impl ParameterValues {
             return;
         }
 
+        let descriptor = self.schema.param(self.current_page, encoder_idx);
         let slot = &mut self.pages[self.current_page].params[encoder_idx];
         match slot {
             ParameterSlot::Active(param) => {
-                param.set_value(param.value + delta);
+                let value = match descriptor {
+                    Some(d) => d.step_value(param.value, delta),
+                    None => param.value.saturating_add(delta),
+                };
+                param.set_value(value);
             }
             ParameterSlot::Null => {
                 #[cfg(feature = "defmt")]
@@ -176,7 +332,12 @@ impl ParameterValues {
     /// Sets the value and marks **only** the OLED change flag to prevent
     /// echoing the value back over I2C.
     ///
-    /// Global index mapping:
+    /// `value` is in output units (what [`take_i2c_changes()`](Self::take_i2c_changes)
+    /// emits) and is converted back to the nearest encoder position with
+    /// [`ParamDescriptor::unmap_value()`](super::ParamDescriptor::unmap_value).
+    ///
+    /// Global index mapping is `page * PARAMS_PER_PAGE + encoder`; for the
+    /// default layout:
     /// - 0–3  → page 0, encoders 0–3
     /// - 4–7  → page 1, encoders 0–3
     /// - 8–11 → page 2, encoders 0–3
@@ -187,10 +348,12 @@ impl ParameterValues {
         value: i32,
     ) -> Result<(), ParameterError> {
         let (page, encoder) = self.global_to_page_encoder(global_idx)?;
+        let descriptor = self.schema.param(page, encoder);
 
         match &mut self.pages[page].params[encoder] {
             ParameterSlot::Active(param) => {
-                param.set_value_from_i2c(value);
+                let position = descriptor.map_or(value, |d| d.unmap_value(value));
+                param.set_value_from_i2c(position);
                 Ok(())
             }
             ParameterSlot::Null => Err(ParameterError::NullSlot),
@@ -242,8 +405,8 @@ impl ParameterValues {
     /// Collect all parameters whose OLED change flag is set, then clear
     /// those flags.
     ///
-    /// Returns a fixed-size array and a count of valid entries. Callers
-    /// should iterate `&result.0[..result.1]`.
+    /// Returns a fixed-capacity [`ParameterChanges`] list; callers iterate
+    /// it with [`ParameterChanges::iter()`].
     ///
     /// Only clears `changed_oled`; the `changed_i2c` flag is left intact.
     ///
@@ -253,89 +416,110 @@ impl ParameterValues {
     /// use spirant::parameter_values::ParameterValues;
     ///
     /// let mut pv = ParameterValues::new();
-    /// pv.update_from_encoder(0, 42);
+    /// pv.update_from_encoder(1, 42); // "Resonance", linear
     ///
-    /// let (changes, count) = pv.take_oled_changes();
-    /// assert_eq!(count, 1);
+    /// let changes = pv.take_oled_changes();
+    /// assert_eq!(changes.len(), 1);
     /// assert_eq!(changes[0].unwrap().value, 42);
     ///
     /// // Flags are cleared — second call returns nothing.
-    /// let (_, count2) = pv.take_oled_changes();
-    /// assert_eq!(count2, 0);
+    /// assert!(pv.take_oled_changes().is_empty());
     /// ```
     pub fn take_oled_changes(
         &mut self,
-    ) -> ([Option<ParameterChange>; TOTAL_SLOTS], usize) {
-        let mut result = [None; TOTAL_SLOTS];
-        let mut count = 0;
+    ) -> ParameterChanges<N_PAGES, PARAMS_PER_PAGE> {
+        let mut result = ParameterChanges::new();
 
+        let schema = self.schema;
         for (page_idx, page) in self.pages.iter_mut().enumerate() {
             for (enc_idx, slot) in page.params.iter_mut().enumerate() {
                 if let ParameterSlot::Active(param) = slot {
                     if param.changed_oled {
-                        // PARAM_NAMES entry is guaranteed Some for Active slots
-                        // (maintained by the new() invariant).
-                        let name = PARAM_NAMES[page_idx][enc_idx]
-                            .expect("Active slot must have a name in PARAM_NAMES");
-                        result[count] = Some(ParameterChange {
-                            name,
-                            value: param.value,
-                            page: page_idx,
-                            encoder: enc_idx,
-                        });
-                        count += 1;
+                        result.push(Self::change(schema, page_idx, enc_idx, param.value));
                         param.changed_oled = false;
                     }
                 }
             }
         }
 
-        (result, count)
+        result
     }
 
     /// Collect all parameters whose I2C change flag is set, then clear
     /// those flags.
     ///
-    /// Returns a fixed-size array and a count of valid entries. Callers
-    /// should iterate `&result.0[..result.1]`.
+    /// Returns a fixed-capacity [`ParameterChanges`] list; callers iterate
+    /// it with [`ParameterChanges::iter()`].
     ///
     /// Only clears `changed_i2c`; the `changed_oled` flag is left intact.
     pub fn take_i2c_changes(
         &mut self,
-    ) -> ([Option<ParameterChange>; TOTAL_SLOTS], usize) {
-        let mut result = [None; TOTAL_SLOTS];
-        let mut count = 0;
+    ) -> ParameterChanges<N_PAGES, PARAMS_PER_PAGE> {
+        let mut result = ParameterChanges::new();
 
+        let schema = self.schema;
         for (page_idx, page) in self.pages.iter_mut().enumerate() {
             for (enc_idx, slot) in page.params.iter_mut().enumerate() {
                 if let ParameterSlot::Active(param) = slot {
                     if param.changed_i2c {
-                        let name = PARAM_NAMES[page_idx][enc_idx]
-                            .expect("Active slot must have a name in PARAM_NAMES");
-                        result[count] = Some(ParameterChange {
-                            name,
-                            value: param.value,
-                            page: page_idx,
-                            encoder: enc_idx,
-                        });
-                        count += 1;
+                        result.push(Self::change(schema, page_idx, enc_idx, param.value));
                         param.changed_i2c = false;
                     }
                 }
             }
         }
 
-        (result, count)
+        result
     }
 
     // ── Private helpers ──────────────────────────────────────────────
 
+    /// Describe the parameter at `page_idx`/`enc_idx` holding `position`.
+    ///
+    /// `pages` is public, so an Active slot may have no descriptor in the
+    /// schema; it is then reported with an empty name, no label and its
+    /// raw position, like [`update_from_encoder()`](Self::update_from_encoder)
+    /// steps it by the raw delta.
+    fn change(
+        schema: &ParameterSchema<N_PAGES, PARAMS_PER_PAGE>,
+        page_idx: usize,
+        enc_idx: usize,
+        position: i32,
+    ) -> ParameterChange {
+        let descriptor = schema.param(page_idx, enc_idx);
+        ParameterChange {
+            name: descriptor.map_or("", |d| d.name),
+            value: descriptor.map_or(position, |d| d.map_value(position)),
+            label: descriptor.and_then(|d| d.label(position)),
+            page: page_idx,
+            encoder: enc_idx,
+        }
+    }
+
+    /// Build the Active/Null page layout described by `schema`.
+    fn build_pages(
+        schema: &ParameterSchema<N_PAGES, PARAMS_PER_PAGE>,
+    ) -> [Page<PARAMS_PER_PAGE>; N_PAGES] {
+        let mut pages = [Page::default(); N_PAGES];
+
+        for (page_idx, page) in pages.iter_mut().enumerate() {
+            for (slot_idx, slot) in page.params.iter_mut().enumerate() {
+                *slot = match schema.param(page_idx, slot_idx) {
+                    Some(descriptor) => ParameterSlot::Active(Parameter::from_descriptor(descriptor)),
+                    None => ParameterSlot::Null,
+                };
+            }
+        }
+
+        pages
+    }
+
     /// Convert a global parameter index to (page, encoder) coordinates.
     fn global_to_page_encoder(
         &self,
         global_idx: usize,
     ) -> Result<(usize, usize), ParameterError> {
-        if global_idx >= TOTAL_SLOTS {
+        if global_idx >= N_PAGES * PARAMS_PER_PAGE {
             return Err(ParameterError::InvalidGlobalIndex);
         }
         let page = global_idx / PARAMS_PER_PAGE;
@@ -349,6 +533,20 @@ impl ParameterValues {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::parameter_values::{PageDescriptor, ParamDescriptor, PARAMS_PER_PAGE};
+
+    // A small non-default layout: 2 pages × 3 slots.
+    static SMALL_SCHEMA: ParameterSchema<2, 3> = ParameterSchema::new([
+        PageDescriptor::new(
+            "Osc",
+            [
+                Some(ParamDescriptor::new("Ratio")),
+                None,
+                Some(ParamDescriptor::new("Index")),
+            ],
+        ),
+        PageDescriptor::new("Amp", [Some(ParamDescriptor::new("Level")), None, None]),
+    ]);
 
     // Helper: make a ParameterValues with a known active slot value.
     fn make_pv_with_value(page: usize, encoder: usize, value: i32) -> ParameterValues {
@@ -368,19 +566,17 @@ mod tests {
 
         // No changes should be pending.
         let mut pv = pv;
-        let (_, oled_count) = pv.take_oled_changes();
-        let (_, i2c_count) = pv.take_i2c_changes();
-        assert_eq!(oled_count, 0);
-        assert_eq!(i2c_count, 0);
+        assert!(pv.take_oled_changes().is_empty());
+        assert!(pv.take_i2c_changes().is_empty());
     }
 
     #[test]
-    fn default_initializes_active_and_null_slots_from_param_names() {
+    fn default_initializes_active_and_null_slots_from_default_schema() {
         let pv = ParameterValues::new();
 
         for (page_idx, page) in pv.pages.iter().enumerate() {
             for (slot_idx, slot) in page.params.iter().enumerate() {
-                match PARAM_NAMES[page_idx][slot_idx] {
+                match DEFAULT_SCHEMA.param(page_idx, slot_idx) {
                     Some(_) => assert!(slot.is_active(), "page {} slot {} should be Active", page_idx, slot_idx),
                     None => assert!(!slot.is_active(), "page {} slot {} should be Null", page_idx, slot_idx),
                 }
@@ -388,6 +584,308 @@ mod tests {
         }
     }
 
+    // ── Custom schemas ───────────────────────────────────────────────
+
+    #[test]
+    fn with_schema_builds_custom_layout() {
+        let pv = ParameterValues::with_schema(&SMALL_SCHEMA);
+        assert_eq!(pv.pages.len(), 2);
+        assert_eq!(pv.pages[0].params.len(), 3);
+        assert_eq!(pv.count_active_params(0), 2);
+        assert_eq!(pv.count_active_params(1), 1);
+        assert!(!pv.pages[0].params[1].is_active());
+    }
+
+    #[test]
+    fn custom_schema_names_flow_into_changes() {
+        let mut pv = ParameterValues::with_schema(&SMALL_SCHEMA);
+        pv.update_from_encoder(2, 7);
+        pv.update_from_i2c(3, 9).unwrap(); // page 1, slot 0
+
+        let changes = pv.take_oled_changes();
+        assert_eq!(changes.len(), 2);
+        assert_eq!(changes[0].unwrap().name, "Index");
+        assert_eq!(changes[1].unwrap().name, "Level");
+        assert_eq!(changes[1].unwrap().page, 1);
+    }
+
+    #[test]
+    fn custom_schema_bounds() {
+        let mut pv = ParameterValues::with_schema(&SMALL_SCHEMA);
+        assert_eq!(pv.set_page(2), Err(ParameterError::InvalidPageIndex));
+        assert_eq!(pv.update_from_i2c(6, 1), Err(ParameterError::InvalidGlobalIndex));
+        assert_eq!(pv.update_from_i2c(1, 1), Err(ParameterError::NullSlot));
+    }
+
+    #[test]
+    fn load_schema_resets_layout_and_marks_page_zero() {
+        static OTHER: ParameterSchema<2, 3> = ParameterSchema::new([
+            PageDescriptor::new("A", [None, None, Some(ParamDescriptor::new("Z"))]),
+            PageDescriptor::new("B", [None; 3]),
+        ]);
+
+        let mut pv = ParameterValues::with_schema(&SMALL_SCHEMA);
+        pv.set_page(1).unwrap();
+        pv.update_from_encoder(0, 5);
+        let _ = pv.take_oled_changes();
+
+        pv.load_schema(&OTHER);
+        assert_eq!(pv.current_page(), 0);
+        assert_eq!(pv.schema().page_name(0), Some("A"));
+        assert_eq!(pv.count_active_params(1), 0);
+
+        let changes = pv.take_oled_changes();
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].unwrap().name, "Z");
+        assert!(pv.take_i2c_changes().is_empty());
+    }
+
+    #[test]
+    fn parameter_changes_indexing_past_len_is_none() {
+        let mut pv = ParameterValues::new();
+        pv.update_from_encoder(1, 3);
+
+        let changes = pv.take_oled_changes();
+        assert_eq!(changes.get(0).unwrap().name, "Resonance");
+        assert!(changes.get(1).is_none());
+        assert!(changes[1].is_none());
+        assert!(changes[15].is_none());
+    }
+
+    #[test]
+    fn schema_accessors() {
+        assert_eq!(DEFAULT_SCHEMA.n_pages(), 4);
+        assert_eq!(DEFAULT_SCHEMA.params_per_page(), PARAMS_PER_PAGE);
+        assert_eq!(DEFAULT_SCHEMA.total_slots(), 16);
+        assert_eq!(DEFAULT_SCHEMA.page_name(2), Some("LFO"));
+        assert_eq!(DEFAULT_SCHEMA.page_name(4), None);
+        assert_eq!(DEFAULT_SCHEMA.param_name(3, 1), Some("Reverb"));
+        assert_eq!(DEFAULT_SCHEMA.param_name(3, 2), None);
+        assert_eq!(DEFAULT_SCHEMA.param_name(0, 9), None);
+    }
+
+    #[test]
+    fn default_times_are_mapped_to_milliseconds() {
+        for page in 0..DEFAULT_SCHEMA.n_pages() {
+            for slot in 0..PARAMS_PER_PAGE {
+                let Some(desc) = DEFAULT_SCHEMA.param(page, slot) else {
+                    continue;
+                };
+                if desc.unit == crate::parameter_values::Unit::Ms {
+                    assert!(desc.output_range.is_some(), "{} has no ms range", desc.name);
+                }
+            }
+        }
+    }
+
+    // ── Parameter metadata ───────────────────────────────────────────
+
+    static METADATA_SCHEMA: ParameterSchema<1, 2> = ParameterSchema::new([PageDescriptor::new(
+        "Env",
+        [
+            Some(
+                ParamDescriptor::new("Attack")
+                    .range(1, 5000)
+                    .default_value(10)
+                    .step(5)
+                    .unit(crate::parameter_values::Unit::Ms),
+            ),
+            Some(ParamDescriptor::new("Detune").range(-12, 12).default_value(99)),
+        ],
+    )]);
+
+    #[test]
+    fn with_schema_applies_range_and_default() {
+        let pv = ParameterValues::with_schema(&METADATA_SCHEMA);
+
+        let attack = pv.pages[0].params[0].as_ref().unwrap();
+        assert_eq!(attack.value, 10);
+        assert_eq!(attack.min_value, 1);
+        assert_eq!(attack.max_value, 5000);
+
+        // Out-of-range default is clamped.
+        let detune = pv.pages[0].params[1].as_ref().unwrap();
+        assert_eq!(detune.value, 12);
+        assert!(!detune.changed_oled);
+        assert!(!detune.changed_i2c);
+    }
+
+    #[test]
+    fn update_from_encoder_applies_step() {
+        let mut pv = ParameterValues::with_schema(&METADATA_SCHEMA);
+        pv.update_from_encoder(0, 3);
+        assert_eq!(pv.pages[0].params[0].as_ref().unwrap().value, 25);
+
+        pv.update_from_encoder(0, -100);
+        assert_eq!(pv.pages[0].params[0].as_ref().unwrap().value, 1);
+    }
+
+    #[test]
+    fn descriptor_write_value_uses_format_and_unit() {
+        struct Sink([u8; 16], usize);
+        impl core::fmt::Write for Sink {
+            fn write_str(&mut self, s: &str) -> core::fmt::Result {
+                self.0[self.1..self.1 + s.len()].copy_from_slice(s.as_bytes());
+                self.1 += s.len();
+                Ok(())
+            }
+        }
+
+        let desc = METADATA_SCHEMA.param(0, 0).unwrap();
+        let mut sink = Sink([0; 16], 0);
+        desc.write_value(250, &mut sink).unwrap();
+        assert_eq!(&sink.0[..sink.1], b"250ms");
+    }
+
+    // ── Discrete parameters ──────────────────────────────────────────
+
+    #[test]
+    fn discrete_clamps_without_wrap() {
+        let mut pv = ParameterValues::new();
+        // Page 0 slot 2 is "Filter Type": LP/HP/BP, no wrap.
+        pv.update_from_encoder(2, 1);
+        assert_eq!(pv.pages[0].params[2].as_ref().unwrap().value, 1);
+        pv.update_from_encoder(2, 5);
+        assert_eq!(pv.pages[0].params[2].as_ref().unwrap().value, 2);
+        pv.update_from_encoder(2, -9);
+        assert_eq!(pv.pages[0].params[2].as_ref().unwrap().value, 0);
+    }
+
+    #[test]
+    fn discrete_wraps_when_enabled() {
+        let mut pv = ParameterValues::new();
+        pv.set_page(2).unwrap();
+        // Page 2 slot 2 is "LFO Shape": Sine/Tri/Saw/Square, wrapping.
+        pv.update_from_encoder(2, -1);
+        assert_eq!(pv.pages[2].params[2].as_ref().unwrap().value, 3);
+        pv.update_from_encoder(2, 1);
+        assert_eq!(pv.pages[2].params[2].as_ref().unwrap().value, 0);
+        pv.update_from_encoder(2, 6);
+        assert_eq!(pv.pages[2].params[2].as_ref().unwrap().value, 2);
+    }
+
+    #[test]
+    fn discrete_change_carries_label() {
+        let mut pv = ParameterValues::new();
+        pv.set_page(2).unwrap();
+        pv.update_from_encoder(2, 2);
+        pv.update_from_encoder(0, 1); // "LFO Rate" is continuous
+
+        let changes = pv.take_i2c_changes();
+        assert_eq!(changes.len(), 2);
+        assert_eq!(changes[0].unwrap().label, None);
+        let shape = changes[1].unwrap();
+        assert_eq!(shape.name, "LFO Shape");
+        assert_eq!(shape.value, 2);
+        assert_eq!(shape.label, Some("Saw"));
+    }
+
+    #[test]
+    fn discrete_descriptor_range_and_labels() {
+        let desc = DEFAULT_SCHEMA.param(0, 2).unwrap();
+        assert_eq!(desc.min_value, 0);
+        assert_eq!(desc.max_value, 2);
+        assert_eq!(desc.label(1), Some("HP"));
+        assert_eq!(desc.label(3), None);
+        assert_eq!(desc.label(-1), None);
+        assert_eq!(DEFAULT_SCHEMA.param(0, 0).unwrap().label(0), None);
+    }
+
+    #[test]
+    fn reversed_range_is_ordered() {
+        let desc = ParamDescriptor::new("Depth").range(10, -10);
+        assert_eq!((desc.min_value, desc.max_value), (-10, 10));
+
+        // Bounds written directly the wrong way round do not panic.
+        let desc = ParamDescriptor {
+            min_value: 10,
+            max_value: -10,
+            ..ParamDescriptor::new("Depth")
+        };
+        let param = Parameter::from_descriptor(&desc);
+        assert_eq!((param.min_value, param.max_value), (-10, 10));
+    }
+
+    #[test]
+    fn reversed_bounds_step_without_panicking() {
+        let desc = ParamDescriptor {
+            min_value: 10,
+            max_value: -10,
+            ..ParamDescriptor::new("Depth")
+        };
+        assert_eq!(desc.step_value(0, 50), 10);
+
+        let wrapped = ParamDescriptor {
+            min_value: 3,
+            max_value: 0,
+            ..ParamDescriptor::new("Shape").discrete(&["A", "B", "C", "D"]).wrapping()
+        };
+        assert_eq!(wrapped.step_value(3, 1), 0);
+    }
+
+    // ── Response curves ──────────────────────────────────────────────
+
+    static CURVE_SCHEMA: ParameterSchema<1, 1> = ParameterSchema::new([PageDescriptor::new(
+        "Filter",
+        [Some(
+            ParamDescriptor::new("Cutoff")
+                .range(0, 100)
+                .curve(crate::parameter_values::Curve::Exponential(10))
+                .output_range(20, 20480)
+                .unit(crate::parameter_values::Unit::Hz),
+        )],
+    )]);
+
+    #[test]
+    fn i2c_changes_emit_mapped_value() {
+        let mut pv = ParameterValues::with_schema(&CURVE_SCHEMA);
+        pv.update_from_encoder(0, 100);
+
+        let changes = pv.take_i2c_changes();
+        assert_eq!(changes[0].unwrap().value, 20480);
+        // The stored value is still the linear encoder position.
+        assert_eq!(pv.pages[0].params[0].as_ref().unwrap().value, 100);
+    }
+
+    #[test]
+    fn update_from_i2c_unmaps_to_position() {
+        let mut pv = ParameterValues::with_schema(&CURVE_SCHEMA);
+        pv.update_from_i2c(0, 640).unwrap();
+
+        let position = pv.pages[0].params[0].as_ref().unwrap().value;
+        assert!((position - 50).abs() <= 1, "position = {}", position);
+        let changes = pv.take_oled_changes();
+        assert!((changes[0].unwrap().value - 640).abs() < 30);
+    }
+
+    #[test]
+    fn reversed_bounds_map_like_ordered_ones() {
+        let ordered = ParamDescriptor::new("Cutoff")
+            .range(0, 100)
+            .curve(crate::parameter_values::Curve::Exponential(10))
+            .output_range(20, 20480);
+        let reversed = ParamDescriptor {
+            min_value: 100,
+            max_value: 0,
+            ..ordered
+        };
+        for position in [0, 25, 50, 100] {
+            assert_eq!(reversed.map_value(position), ordered.map_value(position));
+        }
+        assert_eq!(reversed.unmap_value(640), ordered.unmap_value(640));
+
+        // Without an output range the output follows the ordered positions.
+        let depth = ParamDescriptor {
+            min_value: 10,
+            max_value: -10,
+            ..ParamDescriptor::new("Depth").curve(crate::parameter_values::Curve::SCurve)
+        };
+        assert_eq!(depth.output_bounds(), (-10, 10));
+        assert_eq!(depth.map_value(-10), -10);
+        assert_eq!(depth.map_value(10), 10);
+        assert_eq!(depth.unmap_value(-50), -10);
+    }
+
     // ── Page navigation ──────────────────────────────────────────────
 
     #[test]
@@ -412,7 +910,7 @@ mod tests {
         pv.set_page(1).unwrap();
 
         // No OLED flags should be set.
-        let (_, count) = pv.take_oled_changes();
+        let count = pv.take_oled_changes().len();
         assert_eq!(count, 0);
     }
 
@@ -454,6 +952,30 @@ mod tests {
         assert_eq!(pv.current_page(), 0);
     }
 
+    #[test]
+    fn active_encoder_mask_tracks_null_slots() {
+        let mut pv = ParameterValues::new();
+        assert_eq!(pv.active_encoder_mask(), 0b1111);
+        pv.set_page(3).unwrap(); // Effects: two Null slots
+        assert_eq!(pv.active_encoder_mask(), 0b0011);
+    }
+
+    #[test]
+    fn take_encoder_mask_reports_page_changes_once() {
+        let mut pv = ParameterValues::new();
+        assert_eq!(pv.take_encoder_mask(), Some(0b1111));
+        assert_eq!(pv.take_encoder_mask(), None);
+
+        // set_page is a silent switch and does not queue a mask.
+        pv.set_page(1).unwrap();
+        assert_eq!(pv.take_encoder_mask(), None);
+
+        pv.set_active_page(3).unwrap();
+        assert_eq!(pv.take_encoder_mask(), Some(0b0011));
+        assert_eq!(pv.set_active_page(9), Err(ParameterError::InvalidPageIndex));
+        assert_eq!(pv.take_encoder_mask(), None);
+    }
+
     #[test]
     fn get_active_page_reflects_current() {
         let mut pv = ParameterValues::new();
@@ -519,7 +1041,7 @@ mod tests {
         pv.update_from_encoder(100, 10);
 
         // Page 0 slots are unmodified.
-        let (_, count) = pv.take_oled_changes();
+        let count = pv.take_oled_changes().len();
         assert_eq!(count, 0);
     }
 
@@ -543,9 +1065,9 @@ mod tests {
     #[test]
     fn update_from_i2c_sets_only_oled_flag() {
         let mut pv = ParameterValues::new();
-        pv.update_from_i2c(0, 50).unwrap();
+        pv.update_from_i2c(1, 50).unwrap(); // "Resonance" is linear
 
-        let param = pv.pages[0].params[0].as_ref().unwrap();
+        let param = pv.pages[0].params[1].as_ref().unwrap();
         assert_eq!(param.value, 50);
         assert!(param.changed_oled);
         assert!(!param.changed_i2c);
@@ -599,9 +1121,9 @@ mod tests {
     #[test]
     fn set_param_by_global_idx_works() {
         let mut pv = ParameterValues::new();
-        pv.set_param_by_global_idx(5, 64).unwrap();
+        pv.set_param_by_global_idx(6, 64).unwrap(); // "Sustain" is linear
 
-        let param = pv.pages[1].params[1].as_ref().unwrap();
+        let param = pv.pages[1].params[2].as_ref().unwrap();
         assert_eq!(param.value, 64);
         assert!(param.changed_oled);
         assert!(!param.changed_i2c); // I2C semantics
@@ -614,12 +1136,15 @@ mod tests {
         let mut pv = ParameterValues::new();
         pv.update_from_encoder(0, 42); // page 0, encoder 0 = "Cutoff"
 
-        let (changes, count) = pv.take_oled_changes();
+        let changes = pv.take_oled_changes();
+        let count = changes.len();
         assert_eq!(count, 1);
 
         let change = changes[0].unwrap();
         assert_eq!(change.name, "Cutoff");
-        assert_eq!(change.value, 42);
+        // The value is reported in Hz, through the cutoff's curve.
+        assert_eq!(change.value, DEFAULT_SCHEMA.param(0, 0).unwrap().map_value(42));
+        assert!(change.value > 42);
         assert_eq!(change.page, 0);
         assert_eq!(change.encoder, 0);
     }
@@ -629,10 +1154,10 @@ mod tests {
         let mut pv = ParameterValues::new();
         pv.update_from_encoder(0, 10);
 
-        let (_, count1) = pv.take_oled_changes();
+        let count1 = pv.take_oled_changes().len();
         assert_eq!(count1, 1);
 
-        let (_, count2) = pv.take_oled_changes();
+        let count2 = pv.take_oled_changes().len();
         assert_eq!(count2, 0);
     }
 
@@ -645,22 +1170,44 @@ mod tests {
             pv.update_from_encoder(i, 10);
         }
 
-        let (changes, count) = pv.take_oled_changes();
+        let changes = pv.take_oled_changes();
+        let count = changes.len();
         assert_eq!(count, 3); // Only 3 active slots on page 2.
 
-        for i in 0..count {
-            let change = changes[i].unwrap();
+        for change in changes.iter() {
             assert_eq!(change.page, 2);
         }
     }
 
+    #[test]
+    fn take_changes_tolerate_slot_without_descriptor() {
+        let mut pv = ParameterValues::new();
+        // Activate page 2's null slot behind the schema's back.
+        let null_slot = (0..PARAMS_PER_PAGE)
+            .find(|&i| !pv.pages[2].params[i].is_active())
+            .unwrap();
+        let mut param = Parameter::default();
+        param.set_value(7);
+        pv.pages[2].params[null_slot] = ParameterSlot::Active(param);
+
+        for changes in [pv.take_oled_changes(), pv.take_i2c_changes()] {
+            assert_eq!(changes.len(), 1);
+            let change = changes[0].unwrap();
+            assert_eq!((change.page, change.encoder), (2, null_slot));
+            assert_eq!(change.name, "");
+            assert_eq!(change.value, 7);
+            assert_eq!(change.label, None);
+        }
+    }
+
     #[test]
     fn take_oled_changes_skips_unchanged() {
         let mut pv = ParameterValues::new();
         // Only change encoder 1 on page 0.
         pv.update_from_encoder(1, 5);
 
-        let (result, count) = pv.take_oled_changes();
+        let result = pv.take_oled_changes();
+        let count = result.len();
         assert_eq!(count, 1);
         assert_eq!(result[0].unwrap().name, "Resonance");
     }
@@ -671,7 +1218,7 @@ mod tests {
         pv.update_from_encoder(0, 10); // Sets both flags.
 
         // Consume I2C changes.
-        let (_, i2c_count) = pv.take_i2c_changes();
+        let i2c_count = pv.take_i2c_changes().len();
         assert_eq!(i2c_count, 1);
 
         // OLED flag should still be set.
@@ -685,7 +1232,7 @@ mod tests {
         pv.update_from_encoder(0, 10); // Sets both flags.
 
         // Consume OLED changes.
-        let (_, oled_count) = pv.take_oled_changes();
+        let oled_count = pv.take_oled_changes().len();
         assert_eq!(oled_count, 1);
 
         // I2C flag should still be set.
@@ -771,14 +1318,16 @@ mod tests {
         pv.update_from_i2c(4, 80).unwrap(); // page 1, encoder 0
 
         // OLED should see both changes (encoder sets both flags, I2C sets OLED only).
-        let (oled_changes, oled_count) = pv.take_oled_changes();
+        let oled_changes = pv.take_oled_changes();
+        let oled_count = oled_changes.len();
         assert_eq!(oled_count, 2);
         assert_eq!(oled_changes[0].unwrap().name, "Cutoff");
         assert_eq!(oled_changes[1].unwrap().name, "Attack");
 
         // I2C should see only the encoder-driven change (the I2C write
         // did not set changed_i2c, so it doesn't appear here).
-        let (i2c_changes, i2c_count) = pv.take_i2c_changes();
+        let i2c_changes = pv.take_i2c_changes();
+        let i2c_count = i2c_changes.len();
         assert_eq!(i2c_count, 1);
         assert_eq!(i2c_changes[0].unwrap().name, "Cutoff");
     }
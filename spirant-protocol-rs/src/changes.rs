@@ -0,0 +1,134 @@
+//! Parameter changes on their way to the Daisy.
+//!
+//! [`Link`](crate::Link) and [`DaisySync`](crate::DaisySync) send local
+//! changes by the same rule, implemented once here:
+//!
+//! 1. Snapshot up to one batch of parameters with `changed_i2c` set,
+//!    **without** clearing the flags.
+//! 2. Send one [`SetParam`](Message::SetParam) per change.
+//! 3. Once the send succeeded, clear `changed_i2c` only for parameters whose
+//!    position is still the one that was sent.
+//!
+//! A value that changed during the send keeps its flag and goes out next
+//! time; a failed send clears nothing.
+//!
+//! Enabled by the **`task`** feature.
+
+use spirant::parameter_values::{ParameterSlot, ParameterValues};
+
+use crate::message::Message;
+
+/// Most changes in one batch.
+pub const MAX_BATCH: usize = 8;
+
+/// A change read from [`ParameterValues`] but not yet acknowledged.
+#[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+struct Change {
+    /// Global parameter index.
+    index: u16,
+    /// Encoder position when read, to tell whether it changed again.
+    position: i32,
+    /// Value sent, in output units.
+    value: i32,
+}
+
+/// Snapshot of pending changes, in global index order.
+#[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub(crate) struct ChangeBatch {
+    changes: [Change; MAX_BATCH],
+    len: usize,
+}
+
+impl ChangeBatch {
+    /// Empty batch.
+    pub(crate) const fn new() -> Self {
+        Self {
+            changes: [Change {
+                index: 0,
+                position: 0,
+                value: 0,
+            }; MAX_BATCH],
+            len: 0,
+        }
+    }
+
+    /// Snapshot up to `max` (at most [`MAX_BATCH`]) pending changes with
+    /// global index `from` or above.
+    ///
+    /// Also returns, if the batch filled up, the global index to resume
+    /// from.
+    pub(crate) fn collect<const N_PAGES: usize, const PARAMS: usize>(
+        params: &ParameterValues<N_PAGES, PARAMS>,
+        from: usize,
+        max: usize,
+    ) -> (Self, Option<usize>) {
+        let max = max.clamp(1, MAX_BATCH);
+        let schema = params.schema();
+        let mut batch = Self::new();
+        for index in from..N_PAGES * PARAMS {
+            let (page, slot) = (index / PARAMS, index % PARAMS);
+            let ParameterSlot::Active(param) = &params.pages[page].params[slot] else {
+                continue;
+            };
+            if !param.changed_i2c {
+                continue;
+            }
+            if batch.len == max {
+                return (batch, Some(index));
+            }
+            batch.changes[batch.len] = Change {
+                index: index as u16,
+                position: param.value,
+                value: schema
+                    .param(page, slot)
+                    .map_or(param.value, |d| d.map_value(param.value)),
+            };
+            batch.len += 1;
+        }
+        (batch, None)
+    }
+
+    /// Number of changes in the batch.
+    pub(crate) fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if nothing is pending.
+    pub(crate) fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// The `SetParam` for change `i`.
+    pub(crate) fn message(&self, i: usize) -> Message {
+        let change = self.changes[i];
+        Message::SetParam {
+            index: change.index,
+            value: change.value,
+        }
+    }
+
+    /// Keep only the first `len` changes.
+    pub(crate) fn truncate(&mut self, len: usize) {
+        self.len = self.len.min(len);
+    }
+
+    /// Mark the batch as delivered: clear `changed_i2c` for each change
+    /// whose position is unchanged since the snapshot.
+    pub(crate) fn acknowledge<const N_PAGES: usize, const PARAMS: usize>(
+        &self,
+        params: &mut ParameterValues<N_PAGES, PARAMS>,
+    ) {
+        for change in &self.changes[..self.len] {
+            let index = change.index as usize;
+            if let ParameterSlot::Active(param) =
+                &mut params.pages[index / PARAMS].params[index % PARAMS]
+            {
+                if param.value == change.position {
+                    param.changed_i2c = false;
+                }
+            }
+        }
+    }
+}
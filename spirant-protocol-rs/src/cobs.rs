@@ -0,0 +1,266 @@
+//! Link transport over a byte stream, with COBS framing.
+//!
+//! A UART or USB CDC connection delivers bytes with no boundaries, and
+//! may start mid-frame. Each frame is therefore sent
+//! [COBS](https://en.wikipedia.org/wiki/Consistent_Overhead_Byte_Stuffing)-encoded,
+//! which removes every zero byte, followed by a single zero as delimiter.
+//! A receiver that joins mid-stream, or sees a corrupted packet, resumes
+//! at the next zero.
+//!
+//! Enabled by the **`task`** feature.
+
+use embedded_io_async::{Read, ReadExactError, Write};
+
+use crate::frame::{frame_len, MAX_FRAME_LEN};
+use crate::transport::{split_frames, LinkTransport};
+
+/// Length of the longest encoded frame, delimiter included.
+pub const MAX_PACKET_LEN: usize = MAX_FRAME_LEN + MAX_FRAME_LEN / 254 + 2;
+
+/// COBS-encode `data` into `out`, returning the encoded length (without
+/// the delimiter).
+///
+/// `out` must hold `data.len() + data.len() / 254 + 1` bytes.
+pub(crate) fn cobs_encode(data: &[u8], out: &mut [u8]) -> usize {
+    let mut code_at = 0;
+    let mut len = 1;
+    let mut code = 1u8;
+    for &byte in data {
+        if byte != 0 {
+            out[len] = byte;
+            len += 1;
+            code += 1;
+        }
+        if byte == 0 || code == 0xFF {
+            out[code_at] = code;
+            code_at = len;
+            len += 1;
+            code = 1;
+        }
+    }
+    out[code_at] = code;
+    len
+}
+
+/// Decode a COBS packet (without its delimiter) in place, returning the
+/// decoded length, or `None` if it is malformed.
+pub(crate) fn cobs_decode(buf: &mut [u8]) -> Option<usize> {
+    let mut read = 0;
+    let mut write = 0;
+    while read < buf.len() {
+        let code = buf[read] as usize;
+        if code == 0 || read + code > buf.len() {
+            return None;
+        }
+        read += 1;
+        for _ in 1..code {
+            buf[write] = buf[read];
+            write += 1;
+            read += 1;
+        }
+        if code != 0xFF && read < buf.len() {
+            buf[write] = 0;
+            write += 1;
+        }
+    }
+    Some(write)
+}
+
+/// Link transport over a byte stream (UART, USB CDC).
+///
+/// [`send()`](LinkTransport::send) writes each frame as one COBS packet
+/// and flushes. [`receive()`](LinkTransport::receive) reads up to the next
+/// delimiter and returns the packet if it decodes to exactly one frame;
+/// anything else is dropped and the next packet is tried.
+pub struct CobsTransport<IO> {
+    io: IO,
+    /// Encoded bytes of the packet being received.
+    packet: [u8; MAX_PACKET_LEN],
+    packet_len: usize,
+    /// The packet overflowed; skip to the next delimiter.
+    overflow: bool,
+}
+
+impl<IO: Read + Write> CobsTransport<IO> {
+    /// Transport over `io`.
+    pub fn new(io: IO) -> Self {
+        Self {
+            io,
+            packet: [0; MAX_PACKET_LEN],
+            packet_len: 0,
+            overflow: false,
+        }
+    }
+
+    /// Give back the byte stream. A partly received packet is lost.
+    pub fn release(self) -> IO {
+        self.io
+    }
+
+    /// Decode the packet collected so far into `buf`, if it is one frame.
+    fn finish_packet(&mut self, buf: &mut [u8; MAX_FRAME_LEN]) -> Option<usize> {
+        let len = core::mem::take(&mut self.packet_len);
+        if core::mem::take(&mut self.overflow) || len == 0 {
+            return None;
+        }
+        let decoded = cobs_decode(&mut self.packet[..len])?;
+        let frame = self.packet.get(..decoded)?;
+        if frame_len(frame) != Ok(decoded) {
+            return None;
+        }
+        buf[..decoded].copy_from_slice(frame);
+        Some(decoded)
+    }
+}
+
+impl<IO: Read + Write> LinkTransport for CobsTransport<IO> {
+    type Error = ReadExactError<IO::Error>;
+
+    async fn send(&mut self, frames: &[u8]) -> Result<(), Self::Error> {
+        let mut packet = [0u8; MAX_PACKET_LEN];
+        for frame in split_frames(frames) {
+            let len = cobs_encode(frame, &mut packet);
+            packet[len] = 0;
+            self.io.write_all(&packet[..=len]).await?;
+        }
+        self.io.flush().await?;
+        Ok(())
+    }
+
+    async fn receive(&mut self, buf: &mut [u8; MAX_FRAME_LEN]) -> Result<usize, Self::Error> {
+        loop {
+            let mut byte = [0u8];
+            self.io.read_exact(&mut byte).await?;
+            if byte[0] == 0 {
+                if let Some(len) = self.finish_packet(buf) {
+                    return Ok(len);
+                }
+                #[cfg(feature = "defmt")]
+                defmt::debug!("Dropping bad COBS packet");
+            } else if self.packet_len < MAX_PACKET_LEN {
+                self.packet[self.packet_len] = byte[0];
+                self.packet_len += 1;
+            } else {
+                self.overflow = true;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use std::collections::VecDeque;
+    use std::vec::Vec;
+
+    use embedded_io_async::ErrorType;
+
+    use super::*;
+    use crate::frame::{decode_frame, encode_frame};
+    use crate::message::tests::samples;
+    use crate::message::Message;
+    use crate::transport::tests::block_on;
+
+    fn round_trip(data: &[u8]) {
+        let mut encoded = [0u8; 600];
+        let len = cobs_encode(data, &mut encoded);
+        assert!(len <= data.len() + data.len() / 254 + 1);
+        assert!(!encoded[..len].contains(&0), "{data:?}");
+        assert_eq!(cobs_decode(&mut encoded[..len]), Some(data.len()));
+        assert_eq!(&encoded[..data.len()], data);
+    }
+
+    #[test]
+    fn cobs_round_trips() {
+        round_trip(&[]);
+        round_trip(&[0]);
+        round_trip(&[0, 0]);
+        round_trip(&[1, 0, 2, 0]);
+        round_trip(&[0x11, 0x22, 0x00, 0x33]);
+        let run: Vec<u8> = (1..=255).cycle().take(520).collect();
+        round_trip(&run);
+        round_trip(&run[..254]);
+        round_trip(&run[..253]);
+    }
+
+    #[test]
+    fn cobs_known_encoding() {
+        let mut out = [0u8; 8];
+        let len = cobs_encode(&[0x11, 0x22, 0x00, 0x33], &mut out);
+        assert_eq!(&out[..len], &[0x03, 0x11, 0x22, 0x02, 0x33]);
+    }
+
+    #[test]
+    fn malformed_cobs_is_rejected() {
+        assert_eq!(cobs_decode(&mut [0x05, 0x11]), None);
+        assert_eq!(cobs_decode(&mut [0x00]), None);
+    }
+
+    /// Loopback byte pipe: everything written can be read back.
+    #[derive(Default)]
+    struct Pipe(VecDeque<u8>);
+
+    impl ErrorType for Pipe {
+        type Error = core::convert::Infallible;
+    }
+
+    impl Read for Pipe {
+        async fn read(&mut self, buf: &mut [u8]) -> Result<usize, Self::Error> {
+            let mut n = 0;
+            while n < buf.len() {
+                let Some(byte) = self.0.pop_front() else {
+                    break;
+                };
+                buf[n] = byte;
+                n += 1;
+            }
+            Ok(n)
+        }
+    }
+
+    impl Write for Pipe {
+        async fn write(&mut self, buf: &[u8]) -> Result<usize, Self::Error> {
+            self.0.extend(buf);
+            Ok(buf.len())
+        }
+    }
+
+    #[test]
+    fn every_message_crosses_the_stream() {
+        let mut link = CobsTransport::new(Pipe::default());
+        let mut frames = [0u8; 16 * MAX_FRAME_LEN];
+        let mut len = 0;
+        for message in samples() {
+            len += encode_frame(&message, &mut frames[len..]).unwrap();
+        }
+        block_on(link.send(&frames[..len])).unwrap();
+
+        let mut buf = [0u8; MAX_FRAME_LEN];
+        for message in samples() {
+            let n = block_on(link.receive(&mut buf)).unwrap();
+            assert_eq!(decode_frame(&buf[..n]), Ok((message, n)));
+        }
+        assert!(matches!(
+            block_on(link.receive(&mut buf)),
+            Err(ReadExactError::UnexpectedEof)
+        ));
+    }
+
+    #[test]
+    fn receiver_resyncs_after_garbage() {
+        let mut link = CobsTransport::new(Pipe::default());
+        // Mid-frame join, a packet that is not a frame, and a packet
+        // longer than any frame.
+        link.io.0.extend([0x42, 0x17, 0x00, 0x02, 0x05, 0x00]);
+        link.io.0.extend([0x01; 2 * MAX_PACKET_LEN]);
+        link.io.0.push_back(0x00);
+        let mut frame = [0u8; MAX_FRAME_LEN];
+        let len = encode_frame(&Message::Ping { seq: 3 }, &mut frame).unwrap();
+        block_on(link.send(&frame[..len])).unwrap();
+
+        let mut buf = [0u8; MAX_FRAME_LEN];
+        let n = block_on(link.receive(&mut buf)).unwrap();
+        assert_eq!(decode_frame(&buf[..n]), Ok((Message::Ping { seq: 3 }, n)));
+    }
+}
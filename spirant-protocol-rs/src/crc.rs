@@ -0,0 +1,63 @@
+//! Checksums used by the frame format.
+//!
+//! Both are bitwise implementations of standard catalogue CRCs, so the
+//! Daisy side can use any library implementation of the same variant.
+
+/// CRC-8/SMBUS: polynomial 0x07, initial value 0, no reflection, no final
+/// XOR. Protects the frame header.
+pub const fn crc8(data: &[u8]) -> u8 {
+    let mut crc = 0u8;
+    let mut i = 0;
+    while i < data.len() {
+        crc ^= data[i];
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 0x80 != 0 {
+                (crc << 1) ^ 0x07
+            } else {
+                crc << 1
+            };
+            bit += 1;
+        }
+        i += 1;
+    }
+    crc
+}
+
+/// CRC-16/CCITT-FALSE: polynomial 0x1021, initial value 0xFFFF, no
+/// reflection, no final XOR. Protects the frame body.
+pub const fn crc16(data: &[u8]) -> u16 {
+    let mut crc = 0xFFFFu16;
+    let mut i = 0;
+    while i < data.len() {
+        crc ^= (data[i] as u16) << 8;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 0x8000 != 0 {
+                (crc << 1) ^ 0x1021
+            } else {
+                crc << 1
+            };
+            bit += 1;
+        }
+        i += 1;
+    }
+    crc
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn catalogue_check_values() {
+        assert_eq!(crc8(b"123456789"), 0xF4);
+        assert_eq!(crc16(b"123456789"), 0x29B1);
+    }
+
+    #[test]
+    fn empty_input_yields_initial_value() {
+        assert_eq!(crc8(&[]), 0x00);
+        assert_eq!(crc16(&[]), 0xFFFF);
+    }
+}
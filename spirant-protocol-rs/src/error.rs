@@ -0,0 +1,141 @@
+//! Error types for encoding and decoding.
+
+use core::fmt;
+
+/// Errors that can occur when encoding or decoding a frame.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum ProtocolError {
+    /// The output buffer cannot hold the encoded frame.
+    BufferTooSmall,
+
+    /// The input ends before the frame does; read more bytes and retry.
+    Truncated,
+
+    /// The header check byte does not match, so the length cannot be
+    /// trusted.
+    HeaderCrc,
+
+    /// The body checksum does not match.
+    Crc,
+
+    /// The frame was encoded with a protocol version this side does not
+    /// speak.
+    UnsupportedVersion(u8),
+
+    /// The body is longer than any message can be.
+    TooLong(u8),
+
+    /// The message tag is not known to this protocol version.
+    UnknownMessage(u8),
+
+    /// The body length or a field value does not fit the message.
+    Malformed,
+}
+
+impl ProtocolError {
+    /// The [`ErrorCode`] to report to the peer for this error.
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            ProtocolError::HeaderCrc | ProtocolError::Crc => ErrorCode::Crc,
+            ProtocolError::UnsupportedVersion(_) => ErrorCode::UnsupportedVersion,
+            ProtocolError::UnknownMessage(_) => ErrorCode::UnknownMessage,
+            ProtocolError::BufferTooSmall
+            | ProtocolError::Truncated
+            | ProtocolError::TooLong(_)
+            | ProtocolError::Malformed => ErrorCode::Malformed,
+        }
+    }
+}
+
+impl fmt::Display for ProtocolError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ProtocolError::BufferTooSmall => write!(f, "Output buffer too small"),
+            ProtocolError::Truncated => write!(f, "Frame truncated"),
+            ProtocolError::HeaderCrc => write!(f, "Header checksum mismatch"),
+            ProtocolError::Crc => write!(f, "Body checksum mismatch"),
+            ProtocolError::UnsupportedVersion(v) => {
+                write!(f, "Unsupported protocol version {}", v)
+            }
+            ProtocolError::TooLong(len) => write!(f, "Body too long ({} bytes)", len),
+            ProtocolError::UnknownMessage(tag) => write!(f, "Unknown message tag {:#04x}", tag),
+            ProtocolError::Malformed => write!(f, "Malformed message"),
+        }
+    }
+}
+
+/// Reason carried by an [`Error`](crate::Message::Error) message.
+///
+/// Codes this version does not know are kept as [`Other`](Self::Other),
+/// so an error from a newer peer still decodes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum ErrorCode {
+    /// A frame failed its checksum.
+    Crc,
+    /// A frame or message could not be parsed.
+    Malformed,
+    /// The frame's protocol version is not supported.
+    UnsupportedVersion,
+    /// The message tag is not known.
+    UnknownMessage,
+    /// The parameter index is out of range.
+    InvalidIndex,
+    /// The parameter index refers to an empty slot.
+    NullSlot,
+    /// A code not defined in this protocol version.
+    Other(u8),
+}
+
+impl From<u8> for ErrorCode {
+    fn from(code: u8) -> Self {
+        match code {
+            1 => ErrorCode::Crc,
+            2 => ErrorCode::Malformed,
+            3 => ErrorCode::UnsupportedVersion,
+            4 => ErrorCode::UnknownMessage,
+            5 => ErrorCode::InvalidIndex,
+            6 => ErrorCode::NullSlot,
+            other => ErrorCode::Other(other),
+        }
+    }
+}
+
+impl From<ErrorCode> for u8 {
+    fn from(code: ErrorCode) -> Self {
+        match code {
+            ErrorCode::Crc => 1,
+            ErrorCode::Malformed => 2,
+            ErrorCode::UnsupportedVersion => 3,
+            ErrorCode::UnknownMessage => 4,
+            ErrorCode::InvalidIndex => 5,
+            ErrorCode::NullSlot => 6,
+            ErrorCode::Other(other) => other,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_code_round_trips() {
+        for byte in 0..=u8::MAX {
+            assert_eq!(u8::from(ErrorCode::from(byte)), byte);
+        }
+        assert_eq!(ErrorCode::from(5), ErrorCode::InvalidIndex);
+        assert_eq!(ErrorCode::from(200), ErrorCode::Other(200));
+    }
+
+    #[test]
+    fn protocol_errors_map_to_codes() {
+        assert_eq!(ProtocolError::HeaderCrc.code(), ErrorCode::Crc);
+        assert_eq!(
+            ProtocolError::UnsupportedVersion(9).code(),
+            ErrorCode::UnsupportedVersion
+        );
+        assert_eq!(ProtocolError::TooLong(200).code(), ErrorCode::Malformed);
+    }
+}
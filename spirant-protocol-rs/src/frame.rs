@@ -0,0 +1,212 @@
+//! Length-prefixed, checksummed frames.
+//!
+//! Every message travels in a frame:
+//!
+//! ```text
+//! +---------+--------+----------+------------------+----------------+
+//! | version | length | hdr CRC8 | body (length B)  | CRC16 (BE)     |
+//! +---------+--------+----------+------------------+----------------+
+//! ```
+//!
+//! - **version** — [`VERSION`] of the protocol the frame is encoded with.
+//! - **length** — body length in bytes, at most [`MAX_BODY_LEN`].
+//! - **hdr CRC8** — [`crc8`] of `version` and `length`, so a corrupted
+//!   length is caught before the receiver waits for that many bytes.
+//! - **body** — the [`Message`] encoding: tag, then fields.
+//! - **CRC16** — [`crc16`] of the body, most significant byte first.
+//!
+//! [`frame_len`] reads just the header, for transports that receive a
+//! frame in two steps; [`decode_frame`] checks and decodes a whole frame.
+
+use crate::crc::{crc16, crc8};
+use crate::error::ProtocolError;
+use crate::message::{Message, MAX_BODY_LEN};
+
+/// Protocol version written into every frame.
+pub const VERSION: u8 = 1;
+
+/// Oldest protocol version this side still decodes.
+pub const MIN_VERSION: u8 = 1;
+
+/// Bytes before the body.
+pub const HEADER_LEN: usize = 3;
+
+/// Bytes after the body.
+pub const TRAILER_LEN: usize = 2;
+
+/// Length of the longest frame; a buffer this size holds any frame.
+pub const MAX_FRAME_LEN: usize = HEADER_LEN + MAX_BODY_LEN + TRAILER_LEN;
+
+/// Encode `message` as a complete frame into `buf`, returning its length.
+///
+/// # Errors
+/// * [`ProtocolError::BufferTooSmall`] if `buf` is too short
+pub fn encode_frame(message: &Message, buf: &mut [u8]) -> Result<usize, ProtocolError> {
+    let body_end = buf.len().saturating_sub(TRAILER_LEN);
+    let body = buf
+        .get_mut(HEADER_LEN..body_end)
+        .ok_or(ProtocolError::BufferTooSmall)?;
+    let len = message.encode(body)?;
+    let crc = crc16(&body[..len]);
+
+    buf[0] = VERSION;
+    buf[1] = len as u8;
+    buf[2] = crc8(&buf[..2]);
+    let end = HEADER_LEN + len;
+    buf[end..end + TRAILER_LEN].copy_from_slice(&crc.to_be_bytes());
+    Ok(end + TRAILER_LEN)
+}
+
+/// Check a frame header and return the length of the whole frame.
+///
+/// `header` may be longer than [`HEADER_LEN`]; only the header is read.
+///
+/// # Errors
+/// * [`ProtocolError::Truncated`] if `header` is shorter than
+///   [`HEADER_LEN`]
+/// * [`ProtocolError::HeaderCrc`] if the header check byte is wrong
+/// * [`ProtocolError::UnsupportedVersion`] outside
+///   [`MIN_VERSION`]`..=`[`VERSION`]
+/// * [`ProtocolError::TooLong`] if the length exceeds [`MAX_BODY_LEN`]
+pub fn frame_len(header: &[u8]) -> Result<usize, ProtocolError> {
+    let &[version, len, check, ..] = header else {
+        return Err(ProtocolError::Truncated);
+    };
+    if crc8(&[version, len]) != check {
+        return Err(ProtocolError::HeaderCrc);
+    }
+    if !(MIN_VERSION..=VERSION).contains(&version) {
+        return Err(ProtocolError::UnsupportedVersion(version));
+    }
+    if len as usize > MAX_BODY_LEN {
+        return Err(ProtocolError::TooLong(len));
+    }
+    Ok(HEADER_LEN + len as usize + TRAILER_LEN)
+}
+
+/// Check and decode the frame at the start of `buf`.
+///
+/// Returns the message and the number of bytes the frame occupied, so
+/// several frames can be decoded from one buffer.
+///
+/// # Errors
+/// * Any error of [`frame_len`]
+/// * [`ProtocolError::Truncated`] if `buf` ends before the frame does
+/// * [`ProtocolError::Crc`] if the body checksum is wrong
+/// * Any error of [`Message::decode`]
+pub fn decode_frame(buf: &[u8]) -> Result<(Message, usize), ProtocolError> {
+    let total = frame_len(buf)?;
+    let frame = buf.get(..total).ok_or(ProtocolError::Truncated)?;
+    let (body, trailer) = frame[HEADER_LEN..].split_at(total - HEADER_LEN - TRAILER_LEN);
+    if crc16(body).to_be_bytes() != trailer {
+        return Err(ProtocolError::Crc);
+    }
+    Ok((Message::decode(body)?, total))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::message::tests::samples;
+    use crate::message::{DumpChunk, MAX_DUMP_VALUES};
+
+    fn frame(message: &Message) -> ([u8; MAX_FRAME_LEN], usize) {
+        let mut buf = [0u8; MAX_FRAME_LEN];
+        let len = encode_frame(message, &mut buf).unwrap();
+        (buf, len)
+    }
+
+    #[test]
+    fn every_message_round_trips_through_a_frame() {
+        for message in samples() {
+            let (buf, len) = frame(&message);
+            assert_eq!(frame_len(&buf), Ok(len));
+            assert_eq!(decode_frame(&buf[..len]), Ok((message, len)));
+        }
+    }
+
+    #[test]
+    fn frame_layout() {
+        let (buf, len) = frame(&Message::Ping { seq: 0x2A });
+        let body = [0x02, 0x2A];
+        let crc = crc16(&body).to_be_bytes();
+        assert_eq!(
+            &buf[..len],
+            &[VERSION, 2, crc8(&[VERSION, 2]), 0x02, 0x2A, crc[0], crc[1]]
+        );
+    }
+
+    #[test]
+    fn longest_frame_fits_max_frame_len() {
+        let chunk = DumpChunk::new(8, 0, &[-1; MAX_DUMP_VALUES]).unwrap();
+        let (_, len) = frame(&Message::DumpChunk(chunk));
+        assert_eq!(len, MAX_FRAME_LEN);
+    }
+
+    #[test]
+    fn every_single_bit_error_is_detected() {
+        for message in samples() {
+            let (buf, len) = frame(&message);
+            for byte in 0..len {
+                for bit in 0..8 {
+                    let mut corrupt = buf;
+                    corrupt[byte] ^= 1 << bit;
+                    assert!(
+                        decode_frame(&corrupt[..len]).is_err(),
+                        "{message:?}: bit {bit} of byte {byte} not detected"
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn truncated_frames_ask_for_more() {
+        let (buf, len) = frame(&Message::GetParam { index: 4 });
+        for short in 0..len {
+            assert_eq!(decode_frame(&buf[..short]), Err(ProtocolError::Truncated));
+        }
+    }
+
+    #[test]
+    fn consecutive_frames_decode_in_turn() {
+        let mut buf = [0u8; 2 * MAX_FRAME_LEN];
+        let first = encode_frame(&Message::Ping { seq: 1 }, &mut buf).unwrap();
+        let second = encode_frame(&Message::Pong { seq: 1 }, &mut buf[first..]).unwrap();
+
+        let (message, used) = decode_frame(&buf).unwrap();
+        assert_eq!((message, used), (Message::Ping { seq: 1 }, first));
+        let (message, used) = decode_frame(&buf[first..]).unwrap();
+        assert_eq!((message, used), (Message::Pong { seq: 1 }, second));
+    }
+
+    #[test]
+    fn header_errors() {
+        let header = |version: u8, len: u8| [version, len, crc8(&[version, len])];
+        assert_eq!(
+            frame_len(&header(VERSION + 1, 2)),
+            Err(ProtocolError::UnsupportedVersion(VERSION + 1))
+        );
+        assert_eq!(
+            frame_len(&header(0, 2)),
+            Err(ProtocolError::UnsupportedVersion(0))
+        );
+        assert_eq!(
+            frame_len(&header(VERSION, MAX_BODY_LEN as u8 + 1)),
+            Err(ProtocolError::TooLong(MAX_BODY_LEN as u8 + 1))
+        );
+        assert_eq!(frame_len(&[VERSION, 2, 0]), Err(ProtocolError::HeaderCrc));
+    }
+
+    #[test]
+    fn short_output_buffer_is_reported() {
+        for message in samples() {
+            let (_, len) = frame(&message);
+            let mut buf = [0u8; MAX_FRAME_LEN];
+            assert_eq!(
+                encode_frame(&message, &mut buf[..len - 1]),
+                Err(ProtocolError::BufferTooSmall)
+            );
+        }
+    }
+}
@@ -0,0 +1,472 @@
+//! Link transports over I2C, for either end of the bus.
+//!
+//! I2C has one controller, so frames only move when the controller asks:
+//!
+//! - A controller **write** carries one or more frames to the target.
+//! - A controller **read** of [`MAX_FRAME_LEN`] bytes collects one frame
+//!   from the target. A target with nothing to send answers with zero
+//!   bytes, which never start a valid frame.
+//!
+//! [`I2cControllerTransport`] is the controller side over
+//! `embedded_hal_async::i2c::I2c`; [`I2cTargetTransport`] is the target
+//! side over the small [`I2cTarget`] trait, which the firmware implements
+//! for its chip's target peripheral.
+//!
+//! Enabled by the **`task`** feature.
+
+use core::future::Future;
+
+use embassy_time::Duration;
+use embedded_hal_async::delay::DelayNs;
+use embedded_hal_async::i2c::{Error as _, ErrorKind, I2c};
+
+use crate::error::ProtocolError;
+use crate::frame::{frame_len, MAX_FRAME_LEN};
+use crate::transport::{split_frames, LinkTransport};
+
+// ── Controller ───────────────────────────────────────────────────────────
+
+/// Settings for [`I2cControllerTransport`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct I2cControllerConfig {
+    /// 7-bit I2C address of the target.
+    pub address: u8,
+    /// How many times a NACKed write is repeated before it fails.
+    pub retries: u8,
+    /// Pause before each repeat.
+    pub retry_delay: Duration,
+    /// Time between reads while the target has nothing to send.
+    pub poll_interval: Duration,
+}
+
+impl I2cControllerConfig {
+    /// Defaults for a target at `address`: three retries 2 ms apart, and
+    /// a read every 10 ms while idle.
+    pub const fn new(address: u8) -> Self {
+        Self {
+            address,
+            retries: 3,
+            retry_delay: Duration::from_millis(2),
+            poll_interval: Duration::from_millis(10),
+        }
+    }
+}
+
+/// Controller end of an I2C link.
+///
+/// [`send()`](LinkTransport::send) writes all frames in one transaction,
+/// repeating it while the target NACKs (it may be busy). Any other error,
+/// or a NACK after the last retry, is returned.
+///
+/// [`receive()`](LinkTransport::receive) reads from the target every
+/// `poll_interval` until a read returns a frame header. A read that fails
+/// is returned as an error.
+pub struct I2cControllerTransport<I2C, D> {
+    i2c: I2C,
+    delay: D,
+    config: I2cControllerConfig,
+}
+
+impl<I2C, D> I2cControllerTransport<I2C, D>
+where
+    I2C: I2c,
+    D: DelayNs,
+{
+    /// Transport to the target in `config`, pausing with `delay`.
+    pub fn new(i2c: I2C, delay: D, config: I2cControllerConfig) -> Self {
+        Self { i2c, delay, config }
+    }
+
+    /// Give back the I2C bus and delay provider.
+    pub fn release(self) -> (I2C, D) {
+        (self.i2c, self.delay)
+    }
+}
+
+impl<I2C, D> LinkTransport for I2cControllerTransport<I2C, D>
+where
+    I2C: I2c,
+    D: DelayNs,
+{
+    type Error = I2C::Error;
+
+    async fn send(&mut self, frames: &[u8]) -> Result<(), I2C::Error> {
+        let mut attempt = 0;
+        loop {
+            match self.i2c.write(self.config.address, frames).await {
+                Ok(()) => return Ok(()),
+                Err(e)
+                    if matches!(e.kind(), ErrorKind::NoAcknowledge(_))
+                        && attempt < self.config.retries =>
+                {
+                    attempt += 1;
+                    self.delay
+                        .delay_us(self.config.retry_delay.as_micros() as u32)
+                        .await;
+                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    async fn receive(&mut self, buf: &mut [u8; MAX_FRAME_LEN]) -> Result<usize, I2C::Error> {
+        loop {
+            self.i2c.read(self.config.address, buf).await?;
+            match frame_len(buf) {
+                Ok(len) => return Ok(len),
+                // Zero fill: the target has nothing to send.
+                Err(_) if buf[0] == 0 => {}
+                Err(_e) => {
+                    #[cfg(feature = "defmt")]
+                    defmt::warn!("Dropping bad frame header from I2C target: {}", _e);
+                }
+            }
+            self.delay
+                .delay_us(self.config.poll_interval.as_micros() as u32)
+                .await;
+        }
+    }
+}
+
+// ── Target ───────────────────────────────────────────────────────────────
+
+/// What the controller asked for when it addressed the target.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum TargetCommand {
+    /// The controller wrote this many bytes.
+    Write(usize),
+    /// The controller wants to read.
+    Read,
+    /// The controller wrote this many bytes, then wants to read.
+    WriteRead(usize),
+}
+
+/// An I2C peripheral in target mode.
+///
+/// Modelled on the target drivers of the Embassy HALs; the firmware wraps
+/// its peripheral in a newtype implementing this.
+pub trait I2cTarget {
+    /// Error from the peripheral.
+    type Error;
+
+    /// Wait until the controller addresses this target. Written bytes are
+    /// copied into `buf` (further bytes are dropped).
+    fn listen(
+        &mut self,
+        buf: &mut [u8],
+    ) -> impl Future<Output = Result<TargetCommand, Self::Error>>;
+
+    /// Answer the pending read with `data`, followed by zero bytes for as
+    /// long as the controller keeps reading.
+    fn respond(&mut self, data: &[u8]) -> impl Future<Output = Result<(), Self::Error>>;
+}
+
+/// Bytes of frames the target transport holds in each direction.
+const TARGET_QUEUE_LEN: usize = 4 * MAX_FRAME_LEN;
+
+/// Target end of an I2C link.
+///
+/// Sent frames are queued and handed out one per controller read;
+/// [`send()`](LinkTransport::send) only waits (serving the bus) when the
+/// queue is full. Frames written by the controller are collected and
+/// returned one by one from [`receive()`](LinkTransport::receive).
+///
+/// Written bytes that do not form a valid frame are discarded along with
+/// everything queued behind them, since the frame boundaries after them
+/// are unknown.
+pub struct I2cTargetTransport<T> {
+    target: T,
+    inbox: [u8; TARGET_QUEUE_LEN],
+    inbox_len: usize,
+    outbox: [u8; TARGET_QUEUE_LEN],
+    outbox_len: usize,
+}
+
+impl<T: I2cTarget> I2cTargetTransport<T> {
+    /// Transport serving the controller through `target`.
+    pub fn new(target: T) -> Self {
+        Self {
+            target,
+            inbox: [0; TARGET_QUEUE_LEN],
+            inbox_len: 0,
+            outbox: [0; TARGET_QUEUE_LEN],
+            outbox_len: 0,
+        }
+    }
+
+    /// Give back the target peripheral. Queued frames are lost.
+    pub fn release(self) -> T {
+        self.target
+    }
+
+    /// Handle one command from the controller.
+    async fn serve(&mut self) -> Result<(), T::Error> {
+        let command = self
+            .target
+            .listen(&mut self.inbox[self.inbox_len..])
+            .await?;
+        match command {
+            TargetCommand::Write(len) => {
+                self.inbox_len += len.min(TARGET_QUEUE_LEN - self.inbox_len);
+                Ok(())
+            }
+            TargetCommand::WriteRead(len) => {
+                self.inbox_len += len.min(TARGET_QUEUE_LEN - self.inbox_len);
+                self.answer_read().await
+            }
+            TargetCommand::Read => self.answer_read().await,
+        }
+    }
+
+    /// Answer a read with the oldest queued frame, or with zeroes.
+    async fn answer_read(&mut self) -> Result<(), T::Error> {
+        let len = match frame_len(&self.outbox[..self.outbox_len]) {
+            Ok(len) => len.min(self.outbox_len),
+            Err(_) => 0,
+        };
+        self.target.respond(&self.outbox[..len]).await?;
+        self.outbox.copy_within(len..self.outbox_len, 0);
+        self.outbox_len -= len;
+        Ok(())
+    }
+
+    /// Move the first complete frame out of the inbox into `buf`.
+    fn take_frame(&mut self, buf: &mut [u8; MAX_FRAME_LEN]) -> Option<usize> {
+        let len = match frame_len(&self.inbox[..self.inbox_len]) {
+            Ok(len) if len <= self.inbox_len => len,
+            Ok(_) | Err(ProtocolError::Truncated) => return None,
+            Err(_e) => {
+                #[cfg(feature = "defmt")]
+                defmt::warn!("Dropping bad bytes from I2C controller: {}", _e);
+                self.inbox_len = 0;
+                return None;
+            }
+        };
+        buf[..len].copy_from_slice(&self.inbox[..len]);
+        self.inbox.copy_within(len..self.inbox_len, 0);
+        self.inbox_len -= len;
+        Some(len)
+    }
+}
+
+impl<T: I2cTarget> LinkTransport for I2cTargetTransport<T> {
+    type Error = T::Error;
+
+    async fn send(&mut self, frames: &[u8]) -> Result<(), T::Error> {
+        for frame in split_frames(frames) {
+            while TARGET_QUEUE_LEN - self.outbox_len < frame.len() {
+                self.serve().await?;
+            }
+            self.outbox[self.outbox_len..self.outbox_len + frame.len()].copy_from_slice(frame);
+            self.outbox_len += frame.len();
+        }
+        Ok(())
+    }
+
+    async fn receive(&mut self, buf: &mut [u8; MAX_FRAME_LEN]) -> Result<usize, T::Error> {
+        loop {
+            if let Some(len) = self.take_frame(buf) {
+                return Ok(len);
+            }
+            self.serve().await?;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use std::collections::VecDeque;
+    use std::vec::Vec;
+
+    use embedded_hal_async::i2c::{ErrorType, NoAcknowledgeSource, Operation};
+
+    use super::*;
+    use crate::frame::{decode_frame, encode_frame};
+    use crate::message::Message;
+    use crate::transport::tests::block_on;
+
+    fn frame(message: Message) -> Vec<u8> {
+        let mut buf = [0u8; MAX_FRAME_LEN];
+        let len = encode_frame(&message, &mut buf).unwrap();
+        buf[..len].to_vec()
+    }
+
+    /// Target device on a mock bus: records writes, NACKs the first
+    /// `nacks` transactions, and answers reads from `replies` (zero fill
+    /// when empty).
+    #[derive(Default)]
+    struct Bus {
+        writes: Vec<Vec<u8>>,
+        replies: VecDeque<Vec<u8>>,
+        nacks: usize,
+        reads: usize,
+    }
+
+    impl ErrorType for Bus {
+        type Error = ErrorKind;
+    }
+
+    impl I2c for Bus {
+        async fn transaction(
+            &mut self,
+            address: u8,
+            operations: &mut [Operation<'_>],
+        ) -> Result<(), ErrorKind> {
+            assert_eq!(address, 0x42);
+            if self.nacks > 0 {
+                self.nacks -= 1;
+                return Err(ErrorKind::NoAcknowledge(NoAcknowledgeSource::Address));
+            }
+            for op in operations {
+                match op {
+                    Operation::Write(bytes) => self.writes.push(bytes.to_vec()),
+                    Operation::Read(buf) => {
+                        self.reads += 1;
+                        buf.fill(0);
+                        if let Some(reply) = self.replies.pop_front() {
+                            buf[..reply.len()].copy_from_slice(&reply);
+                        }
+                    }
+                }
+            }
+            Ok(())
+        }
+    }
+
+    struct NoDelay;
+
+    impl DelayNs for NoDelay {
+        async fn delay_ns(&mut self, _ns: u32) {}
+    }
+
+    fn controller(bus: Bus) -> I2cControllerTransport<Bus, NoDelay> {
+        I2cControllerTransport::new(bus, NoDelay, I2cControllerConfig::new(0x42))
+    }
+
+    #[test]
+    fn controller_retries_nacked_writes() {
+        let mut link = controller(Bus {
+            nacks: 2,
+            ..Bus::default()
+        });
+        let ping = frame(Message::Ping { seq: 1 });
+        assert_eq!(block_on(link.send(&ping)), Ok(()));
+        assert_eq!(link.release().0.writes, [ping]);
+    }
+
+    #[test]
+    fn controller_gives_up_after_the_last_retry() {
+        let mut link = controller(Bus {
+            nacks: 4,
+            ..Bus::default()
+        });
+        assert!(matches!(
+            block_on(link.send(&frame(Message::Ping { seq: 1 }))),
+            Err(ErrorKind::NoAcknowledge(_))
+        ));
+        assert!(link.release().0.writes.is_empty());
+    }
+
+    #[test]
+    fn controller_polls_until_the_target_has_a_frame() {
+        let pong = frame(Message::Pong { seq: 7 });
+        let mut link = controller(Bus {
+            replies: [Vec::new(), Vec::new(), pong.clone()].into(),
+            ..Bus::default()
+        });
+        let mut buf = [0u8; MAX_FRAME_LEN];
+        assert_eq!(block_on(link.receive(&mut buf)), Ok(pong.len()));
+        assert_eq!(&buf[..pong.len()], &pong[..]);
+        assert_eq!(link.release().0.reads, 3);
+    }
+
+    /// Scripted controller: each `listen()` plays the next command,
+    /// writing its bytes; each `respond()` is recorded.
+    #[derive(Default)]
+    struct Controller {
+        script: VecDeque<(TargetCommand, Vec<u8>)>,
+        responses: Vec<Vec<u8>>,
+    }
+
+    impl I2cTarget for Controller {
+        type Error = ();
+
+        async fn listen(&mut self, buf: &mut [u8]) -> Result<TargetCommand, ()> {
+            let (command, bytes) = self.script.pop_front().ok_or(())?;
+            buf[..bytes.len()].copy_from_slice(&bytes);
+            Ok(command)
+        }
+
+        async fn respond(&mut self, data: &[u8]) -> Result<(), ()> {
+            self.responses.push(data.to_vec());
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn target_splits_written_frames() {
+        let mut both = frame(Message::SetParam {
+            index: 2,
+            value: -3,
+        });
+        both.extend(frame(Message::Ping { seq: 4 }));
+        let mut link = I2cTargetTransport::new(Controller {
+            script: [(TargetCommand::Write(both.len()), both)].into(),
+            ..Controller::default()
+        });
+
+        let mut buf = [0u8; MAX_FRAME_LEN];
+        for expected in [
+            Message::SetParam {
+                index: 2,
+                value: -3,
+            },
+            Message::Ping { seq: 4 },
+        ] {
+            let len = block_on(link.receive(&mut buf)).unwrap();
+            assert_eq!(decode_frame(&buf[..len]), Ok((expected, len)));
+        }
+        // Script exhausted: the next listen fails.
+        assert_eq!(block_on(link.receive(&mut buf)), Err(()));
+    }
+
+    #[test]
+    fn target_hands_out_one_frame_per_read() {
+        let request = frame(Message::GetParam { index: 1 });
+        let mut link = I2cTargetTransport::new(Controller {
+            script: [
+                (TargetCommand::WriteRead(request.len()), request),
+                (TargetCommand::Read, Vec::new()),
+                (TargetCommand::Read, Vec::new()),
+            ]
+            .into(),
+            ..Controller::default()
+        });
+
+        let mut queued = frame(Message::ParamValue { index: 1, value: 5 });
+        queued.extend(frame(Message::Pong { seq: 2 }));
+        block_on(link.send(&queued)).unwrap();
+
+        let mut buf = [0u8; MAX_FRAME_LEN];
+        let len = block_on(link.receive(&mut buf)).unwrap();
+        assert_eq!(
+            decode_frame(&buf[..len]).unwrap().0,
+            Message::GetParam { index: 1 }
+        );
+        // The write-read took the first frame; two reads remain.
+        assert_eq!(block_on(link.receive(&mut buf)), Err(()));
+        let controller = link.release();
+        assert_eq!(
+            controller.responses,
+            [
+                frame(Message::ParamValue { index: 1, value: 5 }),
+                frame(Message::Pong { seq: 2 }),
+                Vec::new(),
+            ]
+        );
+    }
+}
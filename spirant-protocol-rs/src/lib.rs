@@ -0,0 +1,111 @@
+//! Wire protocol between the Spirant controller and the Daisy Seed.
+//!
+//! The controller (Raspberry Pi Pico 2) and the synthesis engine (Daisy
+//! Seed) exchange parameter values as small binary messages. This crate
+//! defines those messages and their framing once, for both sides; it does
+//! no I/O and allocates nothing, so the encoder and decoder are tested
+//! exhaustively on the host.
+//!
+//! # Architecture
+//!
+//! - **[`Message`]** — Typed messages: [`Hello`](Message::Hello) with the
+//!   protocol version and schema hash, [`Ping`](Message::Ping) /
+//!   [`Pong`](Message::Pong), [`SetParam`](Message::SetParam),
+//!   [`GetParam`](Message::GetParam) / [`ParamValue`](Message::ParamValue),
+//!   [`DumpRequest`](Message::DumpRequest) / [`DumpChunk`], and
+//!   [`Error`](Message::Error) with an [`ErrorCode`]. Each encodes to a
+//!   one-byte tag followed by little-endian fields.
+//! - **[`encode_frame`] / [`decode_frame`]** — Wrap a message body in a
+//!   frame carrying the protocol [`VERSION`], the body length with its own
+//!   CRC-8, and a CRC-16 of the body.
+//! - **[`crc8`] / [`crc16`]** — The two checksums (CRC-8/SMBUS and
+//!   CRC-16/CCITT-FALSE).
+//! - **`Responder`** *(feature `params`)* — The target side: applies
+//!   frames written by the Daisy to `ParameterValues` and answers reads
+//!   from a register-style view of it, independent of the bus.
+//! - **`schema_hash`** *(feature `params`)* — Fingerprint of a parameter
+//!   schema, exchanged in `Hello` so both sides agree on every index.
+//! - **`Link`** *(feature `task`)* — Pico-side connection state machine:
+//!   handshake, full dump in either direction, incremental sync, and
+//!   resync when the heartbeat drops.
+//! - **`LinkTransport`** *(feature `task`)* — The async trait the sync
+//!   logic is written against: send frames, wait for a frame. Implemented
+//!   by `I2cControllerTransport`, `I2cTargetTransport`, `CobsTransport`
+//!   (UART or any byte stream) and `MemoryChannel` (tests, simulations).
+//! - **`sync_task`** *(feature `task`)* — `link_sync_task`, which runs a
+//!   `Link` over a transport, and `daisy_sync_task`, which only sends
+//!   locally changed parameters as [`SetParam`](Message::SetParam) frames.
+//!
+//! # Quick start
+//!
+//! ```
+//! use spirant_protocol::{decode_frame, encode_frame, Message, MAX_FRAME_LEN};
+//!
+//! let mut buf = [0u8; MAX_FRAME_LEN];
+//! let len = encode_frame(&Message::SetParam { index: 4, value: 640 }, &mut buf)?;
+//!
+//! let (message, used) = decode_frame(&buf[..len])?;
+//! assert_eq!(message, Message::SetParam { index: 4, value: 640 });
+//! assert_eq!(used, len);
+//! # Ok::<(), spirant_protocol::ProtocolError>(())
+//! ```
+//!
+//! # Features
+//!
+//! - **`defmt`** — Enable [`defmt::Format`] implementations for embedded
+//!   logging.
+//! - **`params`** — Enable `Responder` and `schema_hash` (requires the
+//!   `spirant` parameter crate). They do no I/O either.
+//! - **`task`** — Enable `Link`, the transports and the sync tasks
+//!   (requires Embassy; implies `params`).
+
+#![no_std]
+
+#[cfg(feature = "task")]
+pub use cobs::{CobsTransport, MAX_PACKET_LEN};
+pub use crc::{crc16, crc8};
+pub use error::{ErrorCode, ProtocolError};
+pub use frame::{
+    decode_frame, encode_frame, frame_len, HEADER_LEN, MAX_FRAME_LEN, MIN_VERSION, TRAILER_LEN,
+    VERSION,
+};
+#[cfg(feature = "task")]
+pub use i2c::{
+    I2cControllerConfig, I2cControllerTransport, I2cTarget, I2cTargetTransport, TargetCommand,
+};
+#[cfg(feature = "task")]
+pub use link::{Authority, Link, LinkConfig, LinkState};
+#[cfg(feature = "task")]
+pub use memory::{MemoryChannel, MemoryTransport};
+pub use message::{DumpChunk, Message, MAX_BODY_LEN, MAX_DUMP_VALUES};
+#[cfg(feature = "params")]
+pub use responder::Responder;
+#[cfg(feature = "params")]
+pub use schema::schema_hash;
+#[cfg(feature = "task")]
+pub use sync_task::{daisy_sync_task, link_sync_task, DaisySync, SyncConfig};
+#[cfg(feature = "task")]
+pub use transport::LinkTransport;
+
+#[cfg(feature = "task")]
+mod changes;
+#[cfg(feature = "task")]
+mod cobs;
+mod crc;
+mod error;
+mod frame;
+#[cfg(feature = "task")]
+mod i2c;
+#[cfg(feature = "task")]
+mod link;
+#[cfg(feature = "task")]
+mod memory;
+mod message;
+#[cfg(feature = "params")]
+mod responder;
+#[cfg(feature = "params")]
+mod schema;
+#[cfg(feature = "task")]
+pub mod sync_task;
+#[cfg(feature = "task")]
+mod transport;
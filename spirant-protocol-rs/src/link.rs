@@ -0,0 +1,826 @@
+//! Connection state machine for the Pico side of the link.
+//!
+//! After a reset of either board the two sides disagree about every
+//! parameter. [`Link`] brings them back in step and keeps them there:
+//!
+//! 1. **Handshake** — Send [`Hello`](Message::Hello) with the protocol
+//!    version, [`schema_hash`](crate::schema_hash) and parameter count
+//!    every `hello_interval` until the Daisy answers with a matching one.
+//!    A mismatch parks the link in [`LinkState::Incompatible`]; it keeps
+//!    saying hello in case the Daisy loads a matching engine.
+//! 2. **Dump** — Copy every active slot one way, chosen by [`Authority`]:
+//!    the Pico sends a [`SetParam`](Message::SetParam) per slot, or asks
+//!    for a [`DumpRequest`](Message::DumpRequest) and applies the Daisy's
+//!    [`DumpChunk`](crate::DumpChunk)s.
+//! 3. **Synced** — Send each locally changed parameter as it changes,
+//!    apply the Daisy's, and [`Ping`](Message::Ping) every
+//!    `heartbeat_interval`. If nothing is heard for `heartbeat_timeout`,
+//!    start again from the handshake.
+//!
+//! A `Hello` received once the handshake is done means the Daisy was
+//! reset: the link answers with its own `Hello` and repeats the dump. A
+//! side still in its handshake never answers a `Hello`, so two sides can
+//! not keep answering each other. The Daisy firmware follows the same
+//! rule.
+//!
+//! Like the encoder crate's button detector, it performs no I/O: the
+//! caller feeds it received messages and sends what it returns, so it is
+//! tested on the host over an in-memory loopback.
+//!
+//! Enabled by the **`task`** feature.
+
+use embassy_time::{Duration, Instant};
+use spirant::parameter_values::{ParameterSlot, ParameterValues};
+
+use crate::changes::{ChangeBatch, MAX_BATCH};
+use crate::frame::{MIN_VERSION, VERSION};
+use crate::message::Message;
+use crate::responder::{error_code, output_value};
+
+/// Which board's values win when the link (re)connects.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Authority {
+    /// The Pico sends its values; the synth takes on the panel's state.
+    Pico,
+    /// The Pico fetches the Daisy's values; the panel takes on the
+    /// synth's state (e.g. after the Daisy loaded a preset).
+    Daisy,
+}
+
+/// Where the link is in its life cycle.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum LinkState {
+    /// Saying hello, waiting for a matching answer.
+    Handshake,
+    /// The Daisy answered with another schema or an unsupported version.
+    Incompatible,
+    /// Copying every parameter in the [`Authority`]'s direction.
+    Dumping,
+    /// Both sides agree; only changes are sent.
+    Synced,
+}
+
+/// Settings for a [`Link`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct LinkConfig {
+    /// Which side's values are copied after each handshake.
+    pub authority: Authority,
+    /// [`schema_hash`](crate::schema_hash) of the local schema.
+    pub schema_hash: u32,
+    /// Time between `Hello`s while no matching answer has arrived.
+    pub hello_interval: Duration,
+    /// Time between `Ping`s once synced, and before a `DumpRequest` that
+    /// made no progress is repeated.
+    pub heartbeat_interval: Duration,
+    /// Silence after which the Daisy is considered gone.
+    pub heartbeat_timeout: Duration,
+}
+
+impl LinkConfig {
+    /// Defaults for a schema with hash `schema_hash`: hello every 250 ms,
+    /// ping every 200 ms, and a 1 s timeout.
+    pub const fn new(schema_hash: u32, authority: Authority) -> Self {
+        Self {
+            authority,
+            schema_hash,
+            hello_interval: Duration::from_millis(250),
+            heartbeat_interval: Duration::from_millis(200),
+            heartbeat_timeout: Duration::from_millis(1000),
+        }
+    }
+}
+
+/// Pico-side connection state machine.
+///
+/// Call [`receive()`](Self::receive) with every message from the Daisy and
+/// [`poll()`](Self::poll) until it returns `None` whenever a message
+/// arrived or [`next_deadline()`](Self::next_deadline) passed, sending
+/// each message it returns. Then report how the send went:
+/// [`sent()`](Self::sent) or [`send_failed()`](Self::send_failed).
+///
+/// Changed parameters follow the same rule as
+/// [`DaisySync`](crate::DaisySync): `changed_i2c` is only cleared by
+/// `sent()`, and only if the value has not moved again since its
+/// `SetParam` was returned. Until then `poll()` returns no further
+/// changes. A Daisy-authoritative dump leaves flagged slots alone, so a
+/// local edit that has not reached the Daisy is sent after the dump
+/// instead of being overwritten.
+///
+/// # Example
+///
+/// ```
+/// use embassy_time::Instant;
+/// use spirant::parameter_values::ParameterValues;
+/// use spirant_protocol::{schema_hash, Authority, Link, LinkConfig, Message};
+///
+/// let mut params = ParameterValues::new();
+/// let config = LinkConfig::new(schema_hash(params.schema()), Authority::Pico);
+/// let mut link = Link::new(config, Instant::from_millis(0));
+///
+/// let hello = link.poll(Instant::from_millis(0), &mut params);
+/// assert!(matches!(hello, Some(Message::Hello { .. })));
+/// ```
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Link {
+    config: LinkConfig,
+    state: LinkState,
+    /// When anything was last received from the Daisy.
+    last_heard: Instant,
+    /// When the next `Hello`, `DumpRequest` or `Ping` is due.
+    next_send: Instant,
+    /// Next global index of the dump.
+    cursor: u16,
+    /// Global index the next scan for changes starts at.
+    sync_from: usize,
+    /// Changes being sent, awaiting [`sent()`](Self::sent).
+    batch: ChangeBatch,
+    /// How many changes of `batch` `poll()` has returned.
+    batch_polled: usize,
+    /// Sequence number of the next `Ping`.
+    seq: u8,
+    /// Answer a `Hello` from a Daisy that was reset.
+    hello_reply: bool,
+    /// Answer to the last request from the Daisy.
+    reply: Option<Message>,
+}
+
+impl Link {
+    /// Link in the handshake state with a `Hello` due at `now`.
+    pub fn new(config: LinkConfig, now: Instant) -> Self {
+        Self {
+            config,
+            state: LinkState::Handshake,
+            last_heard: now,
+            next_send: now,
+            cursor: 0,
+            sync_from: 0,
+            batch: ChangeBatch::new(),
+            batch_polled: 0,
+            seq: 0,
+            hello_reply: false,
+            reply: None,
+        }
+    }
+
+    /// Current state.
+    pub fn state(&self) -> LinkState {
+        self.state
+    }
+
+    /// Returns `true` once the dump has finished and only changes are
+    /// exchanged.
+    pub fn is_synced(&self) -> bool {
+        self.state == LinkState::Synced
+    }
+
+    /// The configuration the link was created with.
+    pub fn config(&self) -> &LinkConfig {
+        &self.config
+    }
+
+    /// Drop back to the handshake, with a `Hello` due at `now`.
+    ///
+    /// Changes returned by [`poll()`](Self::poll) but not confirmed by
+    /// [`sent()`](Self::sent) keep their flags.
+    pub fn restart(&mut self, now: Instant) {
+        self.state = LinkState::Handshake;
+        self.next_send = now;
+        self.hello_reply = false;
+        self.reply = None;
+        self.batch = ChangeBatch::new();
+        self.batch_polled = 0;
+    }
+
+    /// Report that every message [`poll()`](Self::poll) returned since
+    /// the last report was delivered.
+    ///
+    /// Clears `changed_i2c` for the changes among them whose value has not
+    /// moved since.
+    pub fn sent<const N_PAGES: usize, const PARAMS: usize>(
+        &mut self,
+        params: &mut ParameterValues<N_PAGES, PARAMS>,
+    ) {
+        self.batch.truncate(self.batch_polled);
+        self.batch.acknowledge(params);
+        self.batch = ChangeBatch::new();
+        self.batch_polled = 0;
+    }
+
+    /// Report that sending the messages [`poll()`](Self::poll) returned
+    /// failed.
+    ///
+    /// Unconfirmed changes keep their flags and the link restarts its
+    /// handshake, with the next `Hello` after `hello_interval` so a dead
+    /// bus is not retried in a tight loop.
+    pub fn send_failed(&mut self, now: Instant) {
+        self.restart(now);
+        self.next_send = now + self.config.hello_interval;
+    }
+
+    /// Apply one message from the Daisy.
+    ///
+    /// Parameter values are only accepted once the handshake has matched
+    /// the schemas; answers to the Daisy's requests are returned by the
+    /// next [`poll()`](Self::poll).
+    pub fn receive<const N_PAGES: usize, const PARAMS: usize>(
+        &mut self,
+        now: Instant,
+        params: &mut ParameterValues<N_PAGES, PARAMS>,
+        message: Message,
+    ) {
+        self.last_heard = now;
+        let connected = matches!(self.state, LinkState::Dumping | LinkState::Synced);
+
+        match message {
+            Message::Hello {
+                version,
+                schema_hash,
+                param_count,
+            } => {
+                if version < MIN_VERSION
+                    || schema_hash != self.config.schema_hash
+                    || param_count as usize != N_PAGES * PARAMS
+                {
+                    #[cfg(feature = "defmt")]
+                    defmt::warn!(
+                        "Daisy is incompatible: version {}, schema {=u32:#x}, {} parameters",
+                        version,
+                        schema_hash,
+                        param_count
+                    );
+                    self.state = LinkState::Incompatible;
+                    return;
+                }
+                // A Hello after the handshake means the Daisy was reset
+                // and has not heard ours.
+                self.hello_reply = connected;
+                self.start_dump(now);
+            }
+            Message::Ping { seq } => self.reply = Some(Message::Pong { seq }),
+            Message::SetParam { index, value } if connected => {
+                if let Err(e) = params.update_from_i2c(index as usize, value) {
+                    self.reply = Some(Message::Error {
+                        code: error_code(e),
+                        index,
+                    });
+                }
+            }
+            Message::DumpChunk(chunk)
+                if self.state == LinkState::Dumping
+                    && self.config.authority == Authority::Daisy =>
+            {
+                if chunk.first() != self.cursor {
+                    // A chunk went missing; ask again from the gap.
+                    self.next_send = now;
+                    return;
+                }
+                for (i, &value) in chunk.values().iter().enumerate() {
+                    let index = chunk.first() as usize + i;
+                    let Some(ParameterSlot::Active(param)) = params
+                        .pages
+                        .get(index / PARAMS)
+                        .map(|page| &page.params[index % PARAMS])
+                    else {
+                        continue;
+                    };
+                    // A local edit the Daisy has not heard of yet wins; it
+                    // is sent once synced.
+                    if param.changed_i2c {
+                        continue;
+                    }
+                    let _ = params.update_from_i2c(index, value);
+                }
+                self.cursor += chunk.values().len() as u16;
+                if chunk.is_last() {
+                    self.enter_synced(now);
+                } else {
+                    self.next_send = now + self.config.heartbeat_interval;
+                }
+            }
+            Message::Error {
+                code: _code,
+                index: _index,
+            } => {
+                #[cfg(feature = "defmt")]
+                defmt::warn!("Daisy reported {} for parameter {}", _code, _index);
+            }
+            _other => {
+                #[cfg(feature = "defmt")]
+                defmt::debug!("Ignoring {} in state {}", _other, self.state);
+            }
+        }
+    }
+
+    /// Next message to send to the Daisy, or `None` until the next
+    /// message arrives or [`next_deadline()`](Self::next_deadline) passes.
+    pub fn poll<const N_PAGES: usize, const PARAMS: usize>(
+        &mut self,
+        now: Instant,
+        params: &mut ParameterValues<N_PAGES, PARAMS>,
+    ) -> Option<Message> {
+        if matches!(self.state, LinkState::Dumping | LinkState::Synced)
+            && now >= self.last_heard + self.config.heartbeat_timeout
+        {
+            #[cfg(feature = "defmt")]
+            defmt::warn!("Daisy link lost; restarting handshake");
+            self.restart(now);
+        }
+
+        if core::mem::take(&mut self.hello_reply) {
+            return Some(self.hello::<N_PAGES, PARAMS>());
+        }
+        if let Some(reply) = self.reply.take() {
+            return Some(reply);
+        }
+
+        match (self.state, self.config.authority) {
+            (LinkState::Handshake | LinkState::Incompatible, _) => {
+                if now < self.next_send {
+                    return None;
+                }
+                self.next_send = now + self.config.hello_interval;
+                Some(self.hello::<N_PAGES, PARAMS>())
+            }
+            (LinkState::Dumping, Authority::Pico) => {
+                if let Some(message) = self.next_dump_value(params) {
+                    return Some(message);
+                }
+                self.enter_synced(now);
+                self.poll_synced(now, params)
+            }
+            (LinkState::Dumping, Authority::Daisy) => {
+                if now < self.next_send {
+                    return None;
+                }
+                self.next_send = now + self.config.heartbeat_interval;
+                Some(Message::DumpRequest { first: self.cursor })
+            }
+            (LinkState::Synced, _) => self.poll_synced(now, params),
+        }
+    }
+
+    /// Earliest time at which [`poll()`](Self::poll) has something to send
+    /// without a new message or parameter change.
+    pub fn next_deadline(&self) -> Instant {
+        match self.state {
+            LinkState::Handshake | LinkState::Incompatible => self.next_send,
+            LinkState::Dumping | LinkState::Synced => self
+                .next_send
+                .min(self.last_heard + self.config.heartbeat_timeout),
+        }
+    }
+
+    fn hello<const N_PAGES: usize, const PARAMS: usize>(&self) -> Message {
+        Message::Hello {
+            version: VERSION,
+            schema_hash: self.config.schema_hash,
+            param_count: (N_PAGES * PARAMS) as u16,
+        }
+    }
+
+    fn start_dump(&mut self, now: Instant) {
+        #[cfg(feature = "defmt")]
+        defmt::info!("Daisy link up; dumping ({})", self.config.authority);
+        self.state = LinkState::Dumping;
+        self.cursor = 0;
+        self.next_send = now;
+    }
+
+    fn enter_synced(&mut self, now: Instant) {
+        #[cfg(feature = "defmt")]
+        defmt::info!("Daisy link synced");
+        self.state = LinkState::Synced;
+        self.next_send = now + self.config.heartbeat_interval;
+    }
+
+    fn poll_synced<const N_PAGES: usize, const PARAMS: usize>(
+        &mut self,
+        now: Instant,
+        params: &mut ParameterValues<N_PAGES, PARAMS>,
+    ) -> Option<Message> {
+        if self.batch.is_empty() {
+            self.batch = self.collect_changes(params);
+            self.batch_polled = 0;
+        }
+        if self.batch_polled < self.batch.len() {
+            let message = self.batch.message(self.batch_polled);
+            self.batch_polled += 1;
+            return Some(message);
+        }
+        if now < self.next_send {
+            return None;
+        }
+        self.next_send = now + self.config.heartbeat_interval;
+        let seq = self.seq;
+        self.seq = seq.wrapping_add(1);
+        Some(Message::Ping { seq })
+    }
+
+    /// `SetParam` for the next active slot of a Pico-authoritative dump.
+    ///
+    /// Clears the slot's `changed_i2c` flag: its current value is being
+    /// sent, and a failed send restarts the whole dump.
+    fn next_dump_value<const N_PAGES: usize, const PARAMS: usize>(
+        &mut self,
+        params: &mut ParameterValues<N_PAGES, PARAMS>,
+    ) -> Option<Message> {
+        let total = N_PAGES * PARAMS;
+        while (self.cursor as usize) < total {
+            let index = self.cursor as usize;
+            self.cursor += 1;
+            if let ParameterSlot::Active(param) =
+                &mut params.pages[index / PARAMS].params[index % PARAMS]
+            {
+                param.changed_i2c = false;
+                if let Ok(value) = output_value(params, index) {
+                    return Some(Message::SetParam {
+                        index: index as u16,
+                        value,
+                    });
+                }
+            }
+        }
+        None
+    }
+
+    /// Snapshot the next batch of changed parameters, taking turns so a
+    /// knob that keeps moving cannot starve the others.
+    fn collect_changes<const N_PAGES: usize, const PARAMS: usize>(
+        &mut self,
+        params: &ParameterValues<N_PAGES, PARAMS>,
+    ) -> ChangeBatch {
+        let (mut batch, mut resume) = ChangeBatch::collect(params, self.sync_from, MAX_BATCH);
+        if batch.is_empty() && self.sync_from != 0 {
+            (batch, resume) = ChangeBatch::collect(params, 0, MAX_BATCH);
+        }
+        self.sync_from = resume.unwrap_or(0);
+        batch
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use std::collections::VecDeque;
+    use std::vec::Vec;
+
+    use super::*;
+    use crate::frame::{decode_frame, encode_frame, MAX_FRAME_LEN};
+    use crate::responder::dump_chunk;
+    use crate::schema::schema_hash;
+
+    /// Send `message` through the framing, as a real transport would.
+    fn over_the_wire(message: Message) -> Message {
+        let mut buf = [0u8; MAX_FRAME_LEN];
+        let len = encode_frame(&message, &mut buf).unwrap();
+        decode_frame(&buf[..len]).unwrap().0
+    }
+
+    fn outputs(params: &ParameterValues) -> Vec<i32> {
+        (0..16)
+            .map(|i| output_value(params, i).unwrap_or(0))
+            .collect()
+    }
+
+    /// Daisy stand-in following the same handshake rule as [`Link`].
+    struct FakeDaisy {
+        params: ParameterValues,
+        schema_hash: u32,
+        /// Waiting for the Pico's Hello after a reset.
+        handshaking: bool,
+        outbox: VecDeque<Message>,
+    }
+
+    impl FakeDaisy {
+        fn new() -> Self {
+            let params = ParameterValues::new();
+            Self {
+                schema_hash: schema_hash(params.schema()),
+                params,
+                handshaking: false,
+                outbox: VecDeque::new(),
+            }
+        }
+
+        /// Power-cycle: back to default values, announcing itself.
+        fn reset(&mut self) {
+            self.params = ParameterValues::new();
+            self.handshaking = true;
+            self.outbox.clear();
+            self.outbox.push_back(self.hello());
+        }
+
+        fn hello(&self) -> Message {
+            Message::Hello {
+                version: VERSION,
+                schema_hash: self.schema_hash,
+                param_count: 16,
+            }
+        }
+
+        fn receive(&mut self, message: Message) {
+            match message {
+                Message::Hello { .. } => {
+                    if !self.handshaking {
+                        self.outbox.push_back(self.hello());
+                    }
+                    self.handshaking = false;
+                }
+                Message::SetParam { index, value } => {
+                    self.params.update_from_i2c(index as usize, value).unwrap();
+                }
+                Message::DumpRequest { mut first } => loop {
+                    let chunk = dump_chunk(&self.params, first);
+                    self.outbox.push_back(Message::DumpChunk(chunk));
+                    if chunk.is_last() {
+                        break;
+                    }
+                    first += chunk.values().len() as u16;
+                },
+                Message::Ping { seq } => self.outbox.push_back(Message::Pong { seq }),
+                _ => {}
+            }
+        }
+    }
+
+    /// A `Link` and a `FakeDaisy` joined by an in-memory wire that can be
+    /// cut (messages vanish) or fail (the Pico's sends report an error).
+    struct Loopback {
+        link: Link,
+        pico: ParameterValues,
+        daisy: FakeDaisy,
+        connected: bool,
+        failing: bool,
+        now_ms: u64,
+    }
+
+    impl Loopback {
+        fn new(authority: Authority) -> Self {
+            let pico = ParameterValues::new();
+            let config = LinkConfig::new(schema_hash(pico.schema()), authority);
+            Self {
+                link: Link::new(config, Instant::from_millis(0)),
+                pico,
+                daisy: FakeDaisy::new(),
+                connected: true,
+                failing: false,
+                now_ms: 0,
+            }
+        }
+
+        /// Run both sides for `ms` milliseconds in 5 ms steps.
+        fn run(&mut self, ms: u64) {
+            for _ in 0..ms / 5 {
+                let now = Instant::from_millis(self.now_ms);
+                let mut polled = Vec::new();
+                while let Some(message) = self.link.poll(now, &mut self.pico) {
+                    polled.push(message);
+                }
+                if self.failing {
+                    if !polled.is_empty() {
+                        self.link.send_failed(now);
+                    }
+                } else {
+                    for message in polled {
+                        if self.connected {
+                            self.daisy.receive(over_the_wire(message));
+                        }
+                    }
+                    self.link.sent(&mut self.pico);
+                }
+                while let Some(message) = self.daisy.outbox.pop_front() {
+                    if self.connected {
+                        self.link
+                            .receive(now, &mut self.pico, over_the_wire(message));
+                    }
+                }
+                self.now_ms += 5;
+            }
+        }
+    }
+
+    #[test]
+    fn pico_authoritative_dump_overwrites_the_daisy() {
+        let mut wire = Loopback::new(Authority::Pico);
+        wire.pico.update_from_encoder(0, 5);
+        wire.daisy.params.update_from_encoder(1, 7);
+
+        wire.run(50);
+        assert_eq!(wire.link.state(), LinkState::Synced);
+        assert_eq!(outputs(&wire.daisy.params), outputs(&wire.pico));
+        assert_eq!(outputs(&ParameterValues::new())[1], outputs(&wire.pico)[1]);
+
+        // Heartbeats keep the link up.
+        wire.run(5000);
+        assert!(wire.link.is_synced());
+    }
+
+    #[test]
+    fn daisy_authoritative_dump_overwrites_the_pico() {
+        let mut wire = Loopback::new(Authority::Daisy);
+        wire.pico.update_from_encoder(0, 5);
+        // Sent before the Daisy was reset with other values.
+        wire.pico.take_i2c_changes();
+        wire.daisy.params.update_from_encoder(1, 7);
+        let daisy_values = outputs(&wire.daisy.params);
+
+        wire.run(50);
+        assert!(wire.link.is_synced());
+        assert_eq!(outputs(&wire.pico), daisy_values);
+        assert_eq!(outputs(&wire.daisy.params), daisy_values);
+        // Nothing left to echo back.
+        assert!(wire.pico.take_i2c_changes().is_empty());
+    }
+
+    #[test]
+    fn changes_follow_once_synced() {
+        let mut wire = Loopback::new(Authority::Pico);
+        wire.run(50);
+
+        wire.pico.update_from_encoder(2, 1);
+        wire.pico.set_active_page(1).unwrap();
+        wire.pico.update_from_encoder(3, -4);
+        wire.run(10);
+        assert_eq!(outputs(&wire.daisy.params), outputs(&wire.pico));
+
+        // Values from the Daisy are applied without being echoed.
+        let value = wire.daisy.params.schema().param(0, 1).unwrap().map_value(9);
+        wire.daisy
+            .outbox
+            .push_back(Message::SetParam { index: 1, value });
+        wire.run(10);
+        assert_eq!(wire.pico.get_param_by_global_idx(1).unwrap().value, 9);
+        assert!(wire.pico.take_i2c_changes().is_empty());
+    }
+
+    #[test]
+    fn unsent_edit_survives_daisy_authoritative_dump() {
+        let mut wire = Loopback::new(Authority::Daisy);
+        wire.daisy.params.update_from_encoder(1, 7);
+        wire.run(50);
+        assert!(wire.link.is_synced());
+
+        // The edit's send fails and the link drops to the handshake.
+        wire.failing = true;
+        wire.pico.update_from_encoder(0, 5);
+        wire.run(5);
+        assert_eq!(wire.link.state(), LinkState::Handshake);
+        assert!(wire.pico.pages[0].params[0].as_ref().unwrap().changed_i2c);
+
+        // The dump takes the Daisy's values except for the pending edit,
+        // which is sent afterwards.
+        wire.failing = false;
+        wire.run(600);
+        assert!(wire.link.is_synced());
+        assert_eq!(wire.pico.pages[0].params[0].as_ref().unwrap().value, 5);
+        assert_eq!(outputs(&wire.daisy.params), outputs(&wire.pico));
+        assert!(wire.pico.take_i2c_changes().is_empty());
+    }
+
+    #[test]
+    fn change_is_confirmed_only_once_sent() {
+        let mut params = ParameterValues::new();
+        let config = LinkConfig::new(schema_hash(params.schema()), Authority::Daisy);
+        let mut link = Link::new(config, Instant::from_millis(0));
+        let now = Instant::from_millis(0);
+        link.receive(now, &mut params, FakeDaisy::new().hello());
+        let mut first = 0;
+        while !link.is_synced() {
+            let chunk = dump_chunk(&params, first);
+            link.receive(now, &mut params, Message::DumpChunk(chunk));
+            first += chunk.values().len() as u16;
+        }
+
+        params.update_from_encoder(0, 5);
+        params.update_from_encoder(1, 5);
+        assert!(matches!(
+            link.poll(now, &mut params),
+            Some(Message::SetParam { index: 0, .. })
+        ));
+        assert!(matches!(
+            link.poll(now, &mut params),
+            Some(Message::SetParam { index: 1, .. })
+        ));
+        // Nothing more until the batch is confirmed.
+        assert_eq!(link.poll(now, &mut params), None);
+
+        // Encoder 1 moves again while the batch is on the wire.
+        params.update_from_encoder(1, 1);
+        link.sent(&mut params);
+        assert!(!params.pages[0].params[0].as_ref().unwrap().changed_i2c);
+        assert!(params.pages[0].params[1].as_ref().unwrap().changed_i2c);
+        assert!(matches!(
+            link.poll(now, &mut params),
+            Some(Message::SetParam { index: 1, .. })
+        ));
+    }
+
+    #[test]
+    fn values_before_the_handshake_are_ignored() {
+        let mut params = ParameterValues::new();
+        let config = LinkConfig::new(schema_hash(params.schema()), Authority::Pico);
+        let mut link = Link::new(config, Instant::from_millis(0));
+        let set = Message::SetParam { index: 1, value: 9 };
+
+        link.receive(Instant::from_millis(0), &mut params, set);
+        assert_eq!(outputs(&params), outputs(&ParameterValues::new()));
+
+        link.receive(
+            Instant::from_millis(0),
+            &mut params,
+            Message::Hello {
+                version: VERSION,
+                schema_hash: config.schema_hash ^ 1,
+                param_count: 16,
+            },
+        );
+        link.receive(Instant::from_millis(0), &mut params, set);
+        assert_eq!(link.state(), LinkState::Incompatible);
+        assert_eq!(outputs(&params), outputs(&ParameterValues::new()));
+    }
+
+    #[test]
+    fn schema_mismatch_blocks_values() {
+        let mut wire = Loopback::new(Authority::Pico);
+        wire.daisy.schema_hash ^= 1;
+        wire.pico.update_from_encoder(0, 5);
+
+        wire.run(1000);
+        assert_eq!(wire.link.state(), LinkState::Incompatible);
+        assert_eq!(
+            outputs(&wire.daisy.params),
+            outputs(&ParameterValues::new())
+        );
+
+        // The Daisy switches to a matching engine.
+        wire.daisy.schema_hash ^= 1;
+        wire.run(300);
+        assert!(wire.link.is_synced());
+        assert_eq!(outputs(&wire.daisy.params), outputs(&wire.pico));
+    }
+
+    #[test]
+    fn lost_heartbeat_resyncs() {
+        let mut wire = Loopback::new(Authority::Pico);
+        wire.pico.update_from_encoder(0, 5);
+        wire.run(50);
+
+        // The Daisy is power-cycled while the wire is cut.
+        wire.connected = false;
+        wire.daisy.reset();
+        wire.run(1100);
+        assert_eq!(wire.link.state(), LinkState::Handshake);
+
+        wire.connected = true;
+        wire.run(600);
+        assert!(wire.link.is_synced());
+        assert_eq!(outputs(&wire.daisy.params), outputs(&wire.pico));
+    }
+
+    #[test]
+    fn daisy_reset_is_answered_and_resynced() {
+        let mut wire = Loopback::new(Authority::Pico);
+        wire.pico.update_from_encoder(0, 5);
+        wire.run(50);
+
+        wire.daisy.reset();
+        assert_ne!(outputs(&wire.daisy.params), outputs(&wire.pico));
+        wire.run(20);
+        assert!(wire.link.is_synced());
+        assert!(!wire.daisy.handshaking);
+        assert_eq!(outputs(&wire.daisy.params), outputs(&wire.pico));
+    }
+
+    #[test]
+    fn deadlines_follow_the_state() {
+        let mut params = ParameterValues::new();
+        let config = LinkConfig::new(schema_hash(params.schema()), Authority::Daisy);
+        let mut link = Link::new(config, Instant::from_millis(0));
+
+        assert!(link.poll(Instant::from_millis(0), &mut params).is_some());
+        assert_eq!(link.next_deadline(), Instant::from_millis(250));
+        assert!(link.poll(Instant::from_millis(100), &mut params).is_none());
+
+        let hello = FakeDaisy::new().hello();
+        link.receive(Instant::from_millis(100), &mut params, hello);
+        assert_eq!(link.state(), LinkState::Dumping);
+        assert_eq!(
+            link.poll(Instant::from_millis(100), &mut params),
+            Some(Message::DumpRequest { first: 0 })
+        );
+        assert_eq!(link.next_deadline(), Instant::from_millis(300));
+
+        // No answer: the request is repeated, then the link gives up.
+        assert_eq!(
+            link.poll(Instant::from_millis(300), &mut params),
+            Some(Message::DumpRequest { first: 0 })
+        );
+        link.poll(Instant::from_millis(1100), &mut params);
+        assert_eq!(link.state(), LinkState::Handshake);
+    }
+}
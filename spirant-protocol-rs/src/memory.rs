@@ -0,0 +1,132 @@
+//! In-memory link transport.
+//!
+//! [`MemoryChannel`] joins two [`MemoryTransport`] ends through a pair of
+//! Embassy channels, so both sides of the link can run in one program:
+//! host tests, simulations, or a desktop front end talking to a simulated
+//! synth.
+//!
+//! Enabled by the **`task`** feature.
+
+use core::convert::Infallible;
+
+use embassy_sync::blocking_mutex::raw::RawMutex;
+use embassy_sync::channel::Channel;
+
+use crate::frame::MAX_FRAME_LEN;
+use crate::transport::{split_frames, LinkTransport};
+
+/// One frame in flight.
+#[derive(Clone, Copy)]
+struct Frame {
+    bytes: [u8; MAX_FRAME_LEN],
+    len: usize,
+}
+
+/// Two connected link ends, each buffering up to `N` frames from the
+/// other.
+///
+/// # Example
+///
+/// ```
+/// use embassy_sync::blocking_mutex::raw::NoopRawMutex;
+/// use spirant_protocol::MemoryChannel;
+///
+/// let channel = MemoryChannel::<NoopRawMutex, 8>::new();
+/// let (pico, daisy) = channel.split();
+/// ```
+pub struct MemoryChannel<M: RawMutex, const N: usize> {
+    a_to_b: Channel<M, Frame, N>,
+    b_to_a: Channel<M, Frame, N>,
+}
+
+impl<M: RawMutex, const N: usize> MemoryChannel<M, N> {
+    /// Empty channel, usable in a `static`.
+    pub const fn new() -> Self {
+        Self {
+            a_to_b: Channel::new(),
+            b_to_a: Channel::new(),
+        }
+    }
+
+    /// The two ends. Frames sent on one are received on the other.
+    pub fn split(&self) -> (MemoryTransport<'_, M, N>, MemoryTransport<'_, M, N>) {
+        (
+            MemoryTransport {
+                tx: &self.a_to_b,
+                rx: &self.b_to_a,
+            },
+            MemoryTransport {
+                tx: &self.b_to_a,
+                rx: &self.a_to_b,
+            },
+        )
+    }
+}
+
+impl<M: RawMutex, const N: usize> Default for MemoryChannel<M, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// One end of a [`MemoryChannel`].
+///
+/// [`send()`](LinkTransport::send) waits while the other end's buffer is
+/// full.
+pub struct MemoryTransport<'a, M: RawMutex, const N: usize> {
+    tx: &'a Channel<M, Frame, N>,
+    rx: &'a Channel<M, Frame, N>,
+}
+
+impl<M: RawMutex, const N: usize> LinkTransport for MemoryTransport<'_, M, N> {
+    type Error = Infallible;
+
+    async fn send(&mut self, frames: &[u8]) -> Result<(), Infallible> {
+        for frame in split_frames(frames) {
+            let mut bytes = [0u8; MAX_FRAME_LEN];
+            let len = frame.len().min(MAX_FRAME_LEN);
+            bytes[..len].copy_from_slice(&frame[..len]);
+            self.tx.send(Frame { bytes, len }).await;
+        }
+        Ok(())
+    }
+
+    async fn receive(&mut self, buf: &mut [u8; MAX_FRAME_LEN]) -> Result<usize, Infallible> {
+        let frame = self.rx.receive().await;
+        buf[..frame.len].copy_from_slice(&frame.bytes[..frame.len]);
+        Ok(frame.len)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use embassy_sync::blocking_mutex::raw::NoopRawMutex;
+
+    use super::*;
+    use crate::frame::{decode_frame, encode_frame};
+    use crate::message::Message;
+    use crate::transport::tests::block_on;
+
+    #[test]
+    fn frames_cross_in_both_directions() {
+        let channel = MemoryChannel::<NoopRawMutex, 4>::new();
+        let (mut pico, mut daisy) = channel.split();
+
+        let mut frames = [0u8; 2 * MAX_FRAME_LEN];
+        let first = encode_frame(&Message::Ping { seq: 1 }, &mut frames).unwrap();
+        let len =
+            first + encode_frame(&Message::GetParam { index: 6 }, &mut frames[first..]).unwrap();
+        block_on(pico.send(&frames[..len])).unwrap();
+
+        let mut buf = [0u8; MAX_FRAME_LEN];
+        for expected in [Message::Ping { seq: 1 }, Message::GetParam { index: 6 }] {
+            let n = block_on(daisy.receive(&mut buf)).unwrap();
+            assert_eq!(decode_frame(&buf[..n]), Ok((expected, n)));
+        }
+
+        let n = encode_frame(&Message::Pong { seq: 1 }, &mut frames).unwrap();
+        block_on(daisy.send(&frames[..n])).unwrap();
+        let n = block_on(pico.receive(&mut buf)).unwrap();
+        assert_eq!(decode_frame(&buf[..n]), Ok((Message::Pong { seq: 1 }, n)));
+    }
+}
@@ -0,0 +1,461 @@
+//! Typed messages and their binary encoding.
+//!
+//! A message body is a one-byte tag followed by the message's fields in
+//! declaration order, little-endian, with no padding. Parameters are
+//! addressed by global index (`page * PARAMS_PER_PAGE + slot`, as in
+//! `ParameterValues::update_from_i2c`) and carry values in output units.
+//!
+//! | Tag    | Message         | Fields                                        |
+//! |--------|-----------------|-----------------------------------------------|
+//! | `0x01` | [`Hello`]       | `version: u8, schema_hash: u32, param_count: u16` |
+//! | `0x02` | [`Ping`]        | `seq: u8`                                     |
+//! | `0x03` | [`Pong`]        | `seq: u8`                                     |
+//! | `0x0F` | [`Error`]       | `code: u8, index: u16`                        |
+//! | `0x10` | [`SetParam`]    | `index: u16, value: i32`                      |
+//! | `0x11` | [`GetParam`]    | `index: u16`                                  |
+//! | `0x12` | [`ParamValue`]  | `index: u16, value: i32`                      |
+//! | `0x20` | [`DumpRequest`] | `first: u16`                                  |
+//! | `0x21` | [`DumpChunk`]   | `total: u16, first: u16, count: u8, values: [i32; count]` |
+//!
+//! [`Hello`]: Message::Hello
+//! [`Ping`]: Message::Ping
+//! [`Pong`]: Message::Pong
+//! [`Error`]: Message::Error
+//! [`SetParam`]: Message::SetParam
+//! [`GetParam`]: Message::GetParam
+//! [`ParamValue`]: Message::ParamValue
+//! [`DumpRequest`]: Message::DumpRequest
+
+use crate::error::{ErrorCode, ProtocolError};
+
+/// Most parameter values carried by one [`DumpChunk`].
+pub const MAX_DUMP_VALUES: usize = 8;
+
+/// Length of the longest message body (a full [`DumpChunk`]).
+pub const MAX_BODY_LEN: usize = 1 + 2 + 2 + 1 + 4 * MAX_DUMP_VALUES;
+
+mod tag {
+    pub const HELLO: u8 = 0x01;
+    pub const PING: u8 = 0x02;
+    pub const PONG: u8 = 0x03;
+    pub const ERROR: u8 = 0x0F;
+    pub const SET_PARAM: u8 = 0x10;
+    pub const GET_PARAM: u8 = 0x11;
+    pub const PARAM_VALUE: u8 = 0x12;
+    pub const DUMP_REQUEST: u8 = 0x20;
+    pub const DUMP_CHUNK: u8 = 0x21;
+}
+
+/// A message exchanged between the controller and the Daisy Seed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Message {
+    /// Sent by each side after a reset to introduce itself.
+    Hello {
+        /// Highest protocol version the sender speaks.
+        version: u8,
+        /// Hash of the sender's parameter schema; both sides must agree
+        /// before values are exchanged.
+        schema_hash: u32,
+        /// Number of global parameter indices in the sender's schema.
+        param_count: u16,
+    },
+    /// Liveness check; answered with a [`Pong`](Self::Pong) carrying the
+    /// same `seq`.
+    Ping {
+        /// Sequence number chosen by the sender.
+        seq: u8,
+    },
+    /// Answer to a [`Ping`](Self::Ping).
+    Pong {
+        /// Sequence number of the ping being answered.
+        seq: u8,
+    },
+    /// A request could not be carried out.
+    Error {
+        /// What went wrong.
+        code: ErrorCode,
+        /// Parameter index the error refers to, or 0.
+        index: u16,
+    },
+    /// Set a parameter to a value.
+    SetParam {
+        /// Global parameter index.
+        index: u16,
+        /// New value, in output units.
+        value: i32,
+    },
+    /// Ask for a parameter's value; answered with
+    /// [`ParamValue`](Self::ParamValue).
+    GetParam {
+        /// Global parameter index.
+        index: u16,
+    },
+    /// A parameter's current value.
+    ParamValue {
+        /// Global parameter index.
+        index: u16,
+        /// Current value, in output units.
+        value: i32,
+    },
+    /// Ask for the values of every parameter from `first` on; answered
+    /// with [`DumpChunk`]s.
+    DumpRequest {
+        /// First global parameter index to send.
+        first: u16,
+    },
+    /// Consecutive parameter values, part of a dump.
+    DumpChunk(DumpChunk),
+}
+
+/// Values of consecutive parameters, sent in answer to a
+/// [`DumpRequest`](Message::DumpRequest).
+///
+/// Empty slots are included, with a value of 0, so a chunk always covers
+/// a contiguous index range.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct DumpChunk {
+    total: u16,
+    first: u16,
+    count: u8,
+    /// Unused entries are always zero, so derived equality is exact.
+    values: [i32; MAX_DUMP_VALUES],
+}
+
+impl DumpChunk {
+    /// Chunk carrying `values` for indices `first..`, out of `total`
+    /// parameters. Returns `None` if there are more than
+    /// [`MAX_DUMP_VALUES`] values.
+    pub fn new(total: u16, first: u16, values: &[i32]) -> Option<Self> {
+        if values.len() > MAX_DUMP_VALUES {
+            return None;
+        }
+        let mut chunk = Self {
+            total,
+            first,
+            count: values.len() as u8,
+            values: [0; MAX_DUMP_VALUES],
+        };
+        chunk.values[..values.len()].copy_from_slice(values);
+        Some(chunk)
+    }
+
+    /// Number of parameters in the whole dump.
+    pub fn total(&self) -> u16 {
+        self.total
+    }
+
+    /// Global index of the first value.
+    pub fn first(&self) -> u16 {
+        self.first
+    }
+
+    /// The values, for indices `first()..first() + values().len()`.
+    pub fn values(&self) -> &[i32] {
+        &self.values[..self.count as usize]
+    }
+
+    /// Returns `true` if this chunk ends the dump.
+    pub fn is_last(&self) -> bool {
+        self.first as usize + self.count as usize >= self.total as usize
+    }
+}
+
+impl Message {
+    /// Encode the message body into `buf`, returning its length.
+    ///
+    /// # Errors
+    /// * [`ProtocolError::BufferTooSmall`] if `buf` is too short
+    pub fn encode(&self, buf: &mut [u8]) -> Result<usize, ProtocolError> {
+        let mut w = Writer { buf, pos: 0 };
+        match *self {
+            Message::Hello {
+                version,
+                schema_hash,
+                param_count,
+            } => {
+                w.u8(tag::HELLO)?;
+                w.u8(version)?;
+                w.bytes(&schema_hash.to_le_bytes())?;
+                w.bytes(&param_count.to_le_bytes())?;
+            }
+            Message::Ping { seq } => {
+                w.u8(tag::PING)?;
+                w.u8(seq)?;
+            }
+            Message::Pong { seq } => {
+                w.u8(tag::PONG)?;
+                w.u8(seq)?;
+            }
+            Message::Error { code, index } => {
+                w.u8(tag::ERROR)?;
+                w.u8(code.into())?;
+                w.bytes(&index.to_le_bytes())?;
+            }
+            Message::SetParam { index, value } => {
+                w.u8(tag::SET_PARAM)?;
+                w.bytes(&index.to_le_bytes())?;
+                w.bytes(&value.to_le_bytes())?;
+            }
+            Message::GetParam { index } => {
+                w.u8(tag::GET_PARAM)?;
+                w.bytes(&index.to_le_bytes())?;
+            }
+            Message::ParamValue { index, value } => {
+                w.u8(tag::PARAM_VALUE)?;
+                w.bytes(&index.to_le_bytes())?;
+                w.bytes(&value.to_le_bytes())?;
+            }
+            Message::DumpRequest { first } => {
+                w.u8(tag::DUMP_REQUEST)?;
+                w.bytes(&first.to_le_bytes())?;
+            }
+            Message::DumpChunk(ref chunk) => {
+                w.u8(tag::DUMP_CHUNK)?;
+                w.bytes(&chunk.total.to_le_bytes())?;
+                w.bytes(&chunk.first.to_le_bytes())?;
+                w.u8(chunk.count)?;
+                for value in chunk.values() {
+                    w.bytes(&value.to_le_bytes())?;
+                }
+            }
+        }
+        Ok(w.pos)
+    }
+
+    /// Decode a message body (tag and fields, nothing else).
+    ///
+    /// # Errors
+    /// * [`ProtocolError::UnknownMessage`] for an unknown tag
+    /// * [`ProtocolError::Malformed`] if the body is empty, too short or
+    ///   too long for its message, or a dump chunk holds too many values
+    pub fn decode(body: &[u8]) -> Result<Self, ProtocolError> {
+        let (&tag, fields) = body.split_first().ok_or(ProtocolError::Malformed)?;
+        let mut r = Reader { buf: fields };
+        let message = match tag {
+            tag::HELLO => Message::Hello {
+                version: r.u8()?,
+                schema_hash: r.u32()?,
+                param_count: r.u16()?,
+            },
+            tag::PING => Message::Ping { seq: r.u8()? },
+            tag::PONG => Message::Pong { seq: r.u8()? },
+            tag::ERROR => Message::Error {
+                code: r.u8()?.into(),
+                index: r.u16()?,
+            },
+            tag::SET_PARAM => Message::SetParam {
+                index: r.u16()?,
+                value: r.i32()?,
+            },
+            tag::GET_PARAM => Message::GetParam { index: r.u16()? },
+            tag::PARAM_VALUE => Message::ParamValue {
+                index: r.u16()?,
+                value: r.i32()?,
+            },
+            tag::DUMP_REQUEST => Message::DumpRequest { first: r.u16()? },
+            tag::DUMP_CHUNK => {
+                let total = r.u16()?;
+                let first = r.u16()?;
+                let count = r.u8()? as usize;
+                if count > MAX_DUMP_VALUES {
+                    return Err(ProtocolError::Malformed);
+                }
+                let mut values = [0; MAX_DUMP_VALUES];
+                for value in &mut values[..count] {
+                    *value = r.i32()?;
+                }
+                Message::DumpChunk(DumpChunk {
+                    total,
+                    first,
+                    count: count as u8,
+                    values,
+                })
+            }
+            other => return Err(ProtocolError::UnknownMessage(other)),
+        };
+        if !r.buf.is_empty() {
+            return Err(ProtocolError::Malformed);
+        }
+        Ok(message)
+    }
+}
+
+/// Bounds-checked output cursor.
+struct Writer<'a> {
+    buf: &'a mut [u8],
+    pos: usize,
+}
+
+impl Writer<'_> {
+    fn u8(&mut self, byte: u8) -> Result<(), ProtocolError> {
+        self.bytes(&[byte])
+    }
+
+    fn bytes(&mut self, bytes: &[u8]) -> Result<(), ProtocolError> {
+        let end = self.pos + bytes.len();
+        self.buf
+            .get_mut(self.pos..end)
+            .ok_or(ProtocolError::BufferTooSmall)?
+            .copy_from_slice(bytes);
+        self.pos = end;
+        Ok(())
+    }
+}
+
+/// Bounds-checked input cursor.
+struct Reader<'a> {
+    buf: &'a [u8],
+}
+
+impl Reader<'_> {
+    fn take<const N: usize>(&mut self) -> Result<[u8; N], ProtocolError> {
+        let (head, rest) = self
+            .buf
+            .split_first_chunk::<N>()
+            .ok_or(ProtocolError::Malformed)?;
+        self.buf = rest;
+        Ok(*head)
+    }
+
+    fn u8(&mut self) -> Result<u8, ProtocolError> {
+        Ok(self.take::<1>()?[0])
+    }
+
+    fn u16(&mut self) -> Result<u16, ProtocolError> {
+        Ok(u16::from_le_bytes(self.take()?))
+    }
+
+    fn u32(&mut self) -> Result<u32, ProtocolError> {
+        Ok(u32::from_le_bytes(self.take()?))
+    }
+
+    fn i32(&mut self) -> Result<i32, ProtocolError> {
+        Ok(i32::from_le_bytes(self.take()?))
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    /// One of every message, with field values that exercise byte order
+    /// and sign.
+    pub(crate) fn samples() -> [Message; 10] {
+        [
+            Message::Hello {
+                version: 1,
+                schema_hash: 0xDEAD_BEEF,
+                param_count: 16,
+            },
+            Message::Ping { seq: 7 },
+            Message::Pong { seq: 255 },
+            Message::Error {
+                code: ErrorCode::NullSlot,
+                index: 11,
+            },
+            Message::SetParam {
+                index: 0x0102,
+                value: -640,
+            },
+            Message::GetParam { index: 3 },
+            Message::ParamValue {
+                index: 15,
+                value: i32::MAX,
+            },
+            Message::DumpRequest { first: 0 },
+            Message::DumpChunk(DumpChunk::new(16, 8, &[1, -2, 3, -4, 5, -6, 7, i32::MIN]).unwrap()),
+            Message::DumpChunk(DumpChunk::new(16, 16, &[]).unwrap()),
+        ]
+    }
+
+    #[test]
+    fn every_message_round_trips() {
+        for message in samples() {
+            let mut buf = [0u8; MAX_BODY_LEN];
+            let len = message.encode(&mut buf).unwrap();
+            assert_eq!(Message::decode(&buf[..len]), Ok(message));
+        }
+    }
+
+    #[test]
+    fn encoding_is_little_endian_and_compact() {
+        let mut buf = [0u8; MAX_BODY_LEN];
+        let message = Message::SetParam {
+            index: 0x0102,
+            value: -2,
+        };
+        let len = message.encode(&mut buf).unwrap();
+        assert_eq!(&buf[..len], &[0x10, 0x02, 0x01, 0xFE, 0xFF, 0xFF, 0xFF]);
+
+        let full = Message::DumpChunk(DumpChunk::new(8, 0, &[0; MAX_DUMP_VALUES]).unwrap());
+        assert_eq!(full.encode(&mut buf), Ok(MAX_BODY_LEN));
+    }
+
+    #[test]
+    fn every_truncation_and_extension_is_rejected() {
+        for message in samples() {
+            let mut buf = [0u8; MAX_BODY_LEN + 1];
+            let len = message.encode(&mut buf).unwrap();
+            for short in 0..len {
+                assert_eq!(
+                    Message::decode(&buf[..short]),
+                    Err(ProtocolError::Malformed)
+                );
+            }
+            assert_eq!(
+                Message::decode(&buf[..len + 1]),
+                Err(ProtocolError::Malformed)
+            );
+        }
+    }
+
+    #[test]
+    fn short_buffer_is_reported() {
+        for message in samples() {
+            let mut buf = [0u8; MAX_BODY_LEN];
+            let len = message.encode(&mut buf).unwrap();
+            assert_eq!(
+                message.encode(&mut buf[..len - 1]),
+                Err(ProtocolError::BufferTooSmall)
+            );
+        }
+    }
+
+    #[test]
+    fn unknown_tags_are_rejected() {
+        let known = samples().map(|m| {
+            let mut buf = [0u8; MAX_BODY_LEN];
+            m.encode(&mut buf).unwrap();
+            buf[0]
+        });
+        for tag in 0..=u8::MAX {
+            if !known.contains(&tag) {
+                assert_eq!(
+                    Message::decode(&[tag, 0, 0]),
+                    Err(ProtocolError::UnknownMessage(tag))
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn oversized_dump_chunks_are_rejected() {
+        assert!(DumpChunk::new(16, 0, &[0; MAX_DUMP_VALUES + 1]).is_none());
+        let mut body = [0u8; MAX_BODY_LEN + 4];
+        body[0] = tag::DUMP_CHUNK;
+        body[5] = MAX_DUMP_VALUES as u8 + 1;
+        assert_eq!(Message::decode(&body), Err(ProtocolError::Malformed));
+    }
+
+    #[test]
+    fn dump_chunk_accessors() {
+        let chunk = DumpChunk::new(10, 8, &[4, 5]).unwrap();
+        assert_eq!(chunk.total(), 10);
+        assert_eq!(chunk.first(), 8);
+        assert_eq!(chunk.values(), &[4, 5]);
+        assert!(chunk.is_last());
+        assert!(!DumpChunk::new(10, 0, &[0; 8]).unwrap().is_last());
+    }
+}
@@ -0,0 +1,414 @@
+//! Target-side handling of frames from the Daisy Seed.
+//!
+//! When the Pico is the I2C target, the Daisy writes frames to it and
+//! reads answers back. [`Responder`] is the part of that exchange that does
+//! not depend on the bus: it applies each write to [`ParameterValues`] and
+//! produces the bytes for each read. The firmware owns the peripheral and
+//! only moves bytes between it and the responder.
+//!
+//! Reads follow a register model:
+//!
+//! - After [`GetParam`](Message::GetParam) or
+//!   [`SetParam`](Message::SetParam), the **register pointer** holds that
+//!   index. Each read returns a [`ParamValue`](Message::ParamValue) for the
+//!   pointer and advances it, wrapping after the last index, so
+//!   consecutive reads walk through every parameter.
+//! - A request with a direct answer ([`Ping`](Message::Ping), or any
+//!   request that failed) queues that answer; the next read returns it.
+//! - After a [`DumpRequest`](Message::DumpRequest), each read returns the
+//!   next [`DumpChunk`] until the last one has been read.
+//!
+//! Enabled by the **`params`** feature.
+
+use spirant::parameter_values::{ParameterError, ParameterSlot, ParameterValues};
+
+use crate::error::{ErrorCode, ProtocolError};
+use crate::frame::{decode_frame, encode_frame};
+use crate::message::{DumpChunk, Message, MAX_DUMP_VALUES};
+
+/// Register-style protocol endpoint over a shared [`ParameterValues`].
+///
+/// Call [`on_write`](Self::on_write) with the bytes of each controller
+/// write and [`on_read`](Self::on_read) to fill each controller read; both
+/// take the parameters by reference, so the caller decides how long the
+/// shared mutex is held.
+///
+/// There is no handshake: a `SetParam` is applied as soon as it arrives,
+/// whatever schema the Daisy is running. Do not serve a Responder on the
+/// same `ParameterValues` as a `Link`, which only accepts values once the
+/// schemas match; route the Daisy's frames through the link instead.
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Responder {
+    /// Global index answered by the next plain read.
+    pointer: u16,
+    /// Answer to the last request, sent by the next read.
+    reply: Option<Message>,
+    /// Next index of a dump in progress.
+    dump: Option<u16>,
+}
+
+impl Responder {
+    /// Responder with the register pointer at index 0 and nothing queued.
+    pub const fn new() -> Self {
+        Self {
+            pointer: 0,
+            reply: None,
+            dump: None,
+        }
+    }
+
+    /// Global index the next plain read answers for.
+    pub fn pointer(&self) -> u16 {
+        self.pointer
+    }
+
+    /// Apply every frame in the bytes of one controller write and return
+    /// how many were decoded.
+    ///
+    /// Decoding stops at the first bad frame, since its length cannot be
+    /// trusted; the error is queued as an [`Error`](Message::Error) for the
+    /// next read.
+    pub fn on_write<const N_PAGES: usize, const PARAMS: usize>(
+        &mut self,
+        params: &mut ParameterValues<N_PAGES, PARAMS>,
+        bytes: &[u8],
+    ) -> usize {
+        let mut rest = bytes;
+        let mut count = 0;
+        while !rest.is_empty() {
+            match decode_frame(rest) {
+                Ok((message, used)) => {
+                    self.handle(params, message);
+                    rest = &rest[used..];
+                    count += 1;
+                }
+                Err(e) => {
+                    #[cfg(feature = "defmt")]
+                    defmt::warn!("Dropping bad frame from the Daisy: {}", e);
+                    self.fail(e.code(), 0);
+                    break;
+                }
+            }
+        }
+        count
+    }
+
+    /// Apply one decoded message.
+    ///
+    /// Messages a target never receives (answers such as
+    /// [`Pong`](Message::Pong) or [`ParamValue`](Message::ParamValue)) are
+    /// ignored.
+    pub fn handle<const N_PAGES: usize, const PARAMS: usize>(
+        &mut self,
+        params: &mut ParameterValues<N_PAGES, PARAMS>,
+        message: Message,
+    ) {
+        match message {
+            Message::SetParam { index, value } => {
+                self.pointer = index;
+                if let Err(e) = params.update_from_i2c(index as usize, value) {
+                    self.fail(error_code(e), index);
+                }
+            }
+            Message::GetParam { index } => {
+                self.pointer = index;
+                self.reply = None;
+                self.dump = None;
+            }
+            Message::Ping { seq } => self.reply = Some(Message::Pong { seq }),
+            Message::DumpRequest { first } => {
+                if first as usize >= N_PAGES * PARAMS {
+                    self.fail(ErrorCode::InvalidIndex, first);
+                } else {
+                    self.reply = None;
+                    self.dump = Some(first);
+                }
+            }
+            _other => {
+                #[cfg(feature = "defmt")]
+                defmt::debug!("Ignoring {} from the Daisy", _other);
+            }
+        }
+    }
+
+    /// Encode the answer to one controller read into `buf`, returning its
+    /// length.
+    ///
+    /// A buffer of [`MAX_FRAME_LEN`](crate::MAX_FRAME_LEN) bytes holds any
+    /// answer.
+    ///
+    /// # Errors
+    /// * [`ProtocolError::BufferTooSmall`] if `buf` is too short; the
+    ///   answer is lost
+    pub fn on_read<const N_PAGES: usize, const PARAMS: usize>(
+        &mut self,
+        params: &ParameterValues<N_PAGES, PARAMS>,
+        buf: &mut [u8],
+    ) -> Result<usize, ProtocolError> {
+        let message = match (self.reply.take(), self.dump) {
+            (Some(reply), _) => reply,
+            (None, Some(first)) => {
+                let chunk = dump_chunk(params, first);
+                self.dump = (!chunk.is_last()).then(|| first + chunk.values().len() as u16);
+                Message::DumpChunk(chunk)
+            }
+            (None, None) => {
+                let index = self.pointer;
+                self.pointer = next_index(index, N_PAGES * PARAMS);
+                param_value(params, index)
+            }
+        };
+        encode_frame(&message, buf)
+    }
+
+    /// Queue an [`Error`](Message::Error) for the next read, ending any
+    /// dump.
+    fn fail(&mut self, code: ErrorCode, index: u16) {
+        self.reply = Some(Message::Error { code, index });
+        self.dump = None;
+    }
+}
+
+/// [`ParamValue`](Message::ParamValue) for `index`, or the
+/// [`Error`](Message::Error) explaining why there is none.
+fn param_value<const N_PAGES: usize, const PARAMS: usize>(
+    params: &ParameterValues<N_PAGES, PARAMS>,
+    index: u16,
+) -> Message {
+    match output_value(params, index as usize) {
+        Ok(value) => Message::ParamValue { index, value },
+        Err(e) => Message::Error {
+            code: error_code(e),
+            index,
+        },
+    }
+}
+
+/// Chunk of output values starting at `first`, with 0 for empty slots.
+pub(crate) fn dump_chunk<const N_PAGES: usize, const PARAMS: usize>(
+    params: &ParameterValues<N_PAGES, PARAMS>,
+    first: u16,
+) -> DumpChunk {
+    let total = N_PAGES * PARAMS;
+    let first_index = first as usize;
+    let count = total.saturating_sub(first_index).min(MAX_DUMP_VALUES);
+    let mut values = [0i32; MAX_DUMP_VALUES];
+    for (i, value) in values[..count].iter_mut().enumerate() {
+        *value = output_value(params, first_index + i).unwrap_or(0);
+    }
+    DumpChunk::new(total as u16, first, &values[..count]).expect("count is at most MAX_DUMP_VALUES")
+}
+
+/// Value of the parameter at global `index`, in output units.
+pub(crate) fn output_value<const N_PAGES: usize, const PARAMS: usize>(
+    params: &ParameterValues<N_PAGES, PARAMS>,
+    index: usize,
+) -> Result<i32, ParameterError> {
+    if index >= N_PAGES * PARAMS {
+        return Err(ParameterError::InvalidGlobalIndex);
+    }
+    let (page, slot) = (index / PARAMS, index % PARAMS);
+    match &params.pages[page].params[slot] {
+        ParameterSlot::Active(param) => Ok(params
+            .schema()
+            .param(page, slot)
+            .map_or(param.value, |d| d.map_value(param.value))),
+        ParameterSlot::Null => Err(ParameterError::NullSlot),
+    }
+}
+
+/// Index after `index`, wrapping to 0 at `total`.
+fn next_index(index: u16, total: usize) -> u16 {
+    if index as usize + 1 >= total {
+        0
+    } else {
+        index + 1
+    }
+}
+
+/// Error code reported to the peer for a failed parameter access.
+pub(crate) fn error_code(e: ParameterError) -> ErrorCode {
+    match e {
+        ParameterError::NullSlot => ErrorCode::NullSlot,
+        ParameterError::InvalidGlobalIndex
+        | ParameterError::InvalidPageIndex
+        | ParameterError::InvalidEncoderIndex => ErrorCode::InvalidIndex,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::frame::MAX_FRAME_LEN;
+
+    /// Encode `messages` back to back, as one controller write.
+    fn write(messages: &[Message]) -> ([u8; 4 * MAX_FRAME_LEN], usize) {
+        let mut buf = [0u8; 4 * MAX_FRAME_LEN];
+        let mut len = 0;
+        for message in messages {
+            len += encode_frame(message, &mut buf[len..]).unwrap();
+        }
+        (buf, len)
+    }
+
+    /// Perform one controller read and decode the answer.
+    fn read(responder: &mut Responder, params: &ParameterValues) -> Message {
+        let mut buf = [0u8; MAX_FRAME_LEN];
+        let len = responder.on_read(params, &mut buf).unwrap();
+        let (message, used) = decode_frame(&buf[..len]).unwrap();
+        assert_eq!(used, len);
+        message
+    }
+
+    fn output(params: &ParameterValues, index: usize) -> i32 {
+        output_value(params, index).unwrap()
+    }
+
+    #[test]
+    fn set_param_writes_through_update_from_i2c() {
+        let mut params = ParameterValues::new();
+        let mut responder = Responder::new();
+        let position = params.get_param_by_global_idx(5).unwrap().value;
+        let target = params.schema().param(1, 1).unwrap().map_value(position + 1);
+        let (buf, len) = write(&[
+            Message::SetParam {
+                index: 5,
+                value: target,
+            },
+            Message::SetParam { index: 0, value: 0 },
+        ]);
+
+        assert_eq!(responder.on_write(&mut params, &buf[..len]), 2);
+        assert_eq!(
+            params.get_param_by_global_idx(5).unwrap().value,
+            position + 1
+        );
+        let param = params.get_param_by_global_idx(5).unwrap();
+        // Values from the Daisy are shown but never echoed back.
+        assert!(param.changed_oled);
+        assert!(!param.changed_i2c);
+    }
+
+    #[test]
+    fn reads_walk_the_registers_from_the_pointer() {
+        let mut params = ParameterValues::new();
+        let mut responder = Responder::new();
+        let (buf, len) = write(&[Message::GetParam { index: 14 }]);
+        responder.on_write(&mut params, &buf[..len]);
+
+        // 14 and 15 are Null slots in the default schema.
+        assert_eq!(
+            read(&mut responder, &params),
+            Message::Error {
+                code: ErrorCode::NullSlot,
+                index: 14
+            }
+        );
+        assert_eq!(
+            read(&mut responder, &params),
+            Message::Error {
+                code: ErrorCode::NullSlot,
+                index: 15
+            }
+        );
+        // Wraps to the first parameter.
+        assert_eq!(
+            read(&mut responder, &params),
+            Message::ParamValue {
+                index: 0,
+                value: output(&params, 0)
+            }
+        );
+        assert_eq!(responder.pointer(), 1);
+    }
+
+    #[test]
+    fn ping_is_answered_once() {
+        let mut params = ParameterValues::new();
+        let mut responder = Responder::new();
+        let (buf, len) = write(&[Message::Ping { seq: 9 }]);
+        responder.on_write(&mut params, &buf[..len]);
+
+        assert_eq!(read(&mut responder, &params), Message::Pong { seq: 9 });
+        assert!(matches!(
+            read(&mut responder, &params),
+            Message::ParamValue { index: 0, .. }
+        ));
+    }
+
+    #[test]
+    fn dump_is_read_in_chunks() {
+        let mut params = ParameterValues::new();
+        let mut responder = Responder::new();
+        let (buf, len) = write(&[Message::DumpRequest { first: 2 }]);
+        responder.on_write(&mut params, &buf[..len]);
+
+        let mut next = 2;
+        loop {
+            let Message::DumpChunk(chunk) = read(&mut responder, &params) else {
+                panic!("expected a dump chunk");
+            };
+            assert_eq!((chunk.total(), chunk.first()), (16, next));
+            for (i, &value) in chunk.values().iter().enumerate() {
+                let index = next as usize + i;
+                assert_eq!(value, output_value(&params, index).unwrap_or(0));
+            }
+            next += chunk.values().len() as u16;
+            if chunk.is_last() {
+                break;
+            }
+        }
+        assert_eq!(next, 16);
+        // Back to plain register reads.
+        assert!(matches!(
+            read(&mut responder, &params),
+            Message::ParamValue { index: 0, .. }
+        ));
+    }
+
+    #[test]
+    fn errors_are_reported_on_the_next_read() {
+        let mut params = ParameterValues::new();
+        let mut responder = Responder::new();
+
+        let (buf, len) = write(&[Message::SetParam {
+            index: 15,
+            value: 1,
+        }]);
+        responder.on_write(&mut params, &buf[..len]);
+        assert_eq!(
+            read(&mut responder, &params),
+            Message::Error {
+                code: ErrorCode::NullSlot,
+                index: 15
+            }
+        );
+
+        let (buf, len) = write(&[Message::DumpRequest { first: 16 }]);
+        responder.on_write(&mut params, &buf[..len]);
+        assert_eq!(
+            read(&mut responder, &params),
+            Message::Error {
+                code: ErrorCode::InvalidIndex,
+                index: 16
+            }
+        );
+
+        let (mut buf, len) = write(&[
+            Message::SetParam { index: 1, value: 3 },
+            Message::SetParam { index: 2, value: 3 },
+        ]);
+        buf[len - 1] ^= 0xFF;
+        let before = output(&params, 2);
+        assert_eq!(responder.on_write(&mut params, &buf[..len]), 1);
+        assert_eq!(output(&params, 2), before);
+        assert_eq!(
+            read(&mut responder, &params),
+            Message::Error {
+                code: ErrorCode::Crc,
+                index: 0
+            }
+        );
+    }
+}
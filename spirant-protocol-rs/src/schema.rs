@@ -0,0 +1,103 @@
+//! Schema fingerprint exchanged in [`Hello`](crate::Message::Hello).
+//!
+//! Both boards must agree on what every global parameter index means
+//! before any value crosses the link. [`schema_hash`] condenses that into
+//! 32 bits: the layout, and for each active slot its name and the output
+//! range its values are sent in.
+//!
+//! Enabled by the **`params`** feature.
+
+use spirant::parameter_values::{ParamKind, ParameterSchema};
+
+const FNV_OFFSET: u32 = 0x811C_9DC5;
+const FNV_PRIME: u32 = 0x0100_0193;
+
+/// FNV-1a hash of everything in `schema` that affects the wire format.
+///
+/// Display-only details (units, formats, step sizes, page names) are left
+/// out, so changing how a value is shown does not break the link.
+///
+/// Each slot contributes, in global index order:
+///
+/// - a `0` byte for a null slot, otherwise
+/// - a `1` byte, the name's UTF-8 bytes and a `0` terminator, the output
+///   minimum and maximum (little-endian `i32`), and the number of labels
+///   for a discrete parameter (little-endian `u16`, 0 for continuous).
+///
+/// The page and slot counts are hashed first, as little-endian `u16`s.
+pub fn schema_hash<const N_PAGES: usize, const PARAMS: usize>(
+    schema: &ParameterSchema<N_PAGES, PARAMS>,
+) -> u32 {
+    let mut hash = Fnv(FNV_OFFSET);
+    hash.write(&(N_PAGES as u16).to_le_bytes());
+    hash.write(&(PARAMS as u16).to_le_bytes());
+    for page in &schema.pages {
+        for slot in &page.params {
+            let Some(descriptor) = slot else {
+                hash.write(&[0]);
+                continue;
+            };
+            let (out_min, out_max) = descriptor.output_bounds();
+            let labels = match descriptor.kind {
+                ParamKind::Discrete { labels, .. } => labels.len() as u16,
+                ParamKind::Continuous => 0,
+            };
+            hash.write(&[1]);
+            hash.write(descriptor.name.as_bytes());
+            hash.write(&[0]);
+            hash.write(&out_min.to_le_bytes());
+            hash.write(&out_max.to_le_bytes());
+            hash.write(&labels.to_le_bytes());
+        }
+    }
+    hash.0
+}
+
+struct Fnv(u32);
+
+impl Fnv {
+    fn write(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.0 = (self.0 ^ byte as u32).wrapping_mul(FNV_PRIME);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use spirant::parameter_values::{PageDescriptor, ParamDescriptor, Unit, DEFAULT_SCHEMA};
+
+    use super::*;
+
+    fn schema(cutoff: ParamDescriptor) -> ParameterSchema<1, 2> {
+        ParameterSchema::new([PageDescriptor::new("Filter", [Some(cutoff), None])])
+    }
+
+    #[test]
+    fn fnv_check_value() {
+        let mut hash = Fnv(FNV_OFFSET);
+        hash.write(b"a");
+        assert_eq!(hash.0, 0xE40C_292C);
+    }
+
+    #[test]
+    fn hash_tracks_the_wire_format_only() {
+        let base = schema_hash(&schema(ParamDescriptor::new("Cutoff")));
+        assert_eq!(base, schema_hash(&schema(ParamDescriptor::new("Cutoff"))));
+
+        // Display details do not matter...
+        let shown = ParamDescriptor::new("Cutoff").unit(Unit::Hz).step(4);
+        assert_eq!(schema_hash(&schema(shown)), base);
+
+        // ...names, ranges and option counts do.
+        for changed in [
+            ParamDescriptor::new("Cutof"),
+            ParamDescriptor::new("Cutoff").range(0, 100),
+            ParamDescriptor::new("Cutoff").output_range(20, 20480),
+            ParamDescriptor::new("Cutoff").discrete(&["Low", "High"]),
+        ] {
+            assert_ne!(schema_hash(&schema(changed)), base);
+        }
+        assert_ne!(schema_hash(&DEFAULT_SCHEMA), base);
+    }
+}
@@ -0,0 +1,647 @@
+//! Parameter sync tasks, written once against [`LinkTransport`].
+//!
+//! Both tasks consume the `changed_i2c` flags and run over any transport:
+//!
+//! - **[`link_sync_task`]** — Drives a [`Link`]: handshake, full dump,
+//!   incremental sync and heartbeat. Use this when the Daisy speaks the
+//!   whole protocol.
+//! - **[`daisy_sync_task`]** — Only pushes changes, as
+//!   [`SetParam`](crate::Message::SetParam) frames, with no handshake.
+//!   [`DaisySync`] holds its send-one-round logic so it can be driven and
+//!   tested without a timer.
+//!
+//! Enabled by the **`task`** feature.
+
+use embassy_futures::select::{select, select3, Either3};
+use embassy_sync::blocking_mutex::raw::{CriticalSectionRawMutex, RawMutex};
+use embassy_sync::mutex::Mutex;
+use embassy_sync::signal::Signal;
+use embassy_time::{Duration, Instant, Timer};
+use spirant::parameter_values::{ParameterValues, PARAMS_PER_PAGE};
+
+use crate::changes::ChangeBatch;
+pub use crate::changes::MAX_BATCH;
+use crate::frame::{decode_frame, encode_frame, HEADER_LEN, MAX_FRAME_LEN, TRAILER_LEN};
+use crate::link::{Link, LinkConfig};
+use crate::transport::LinkTransport;
+
+/// Length of one [`SetParam`](crate::Message::SetParam) frame.
+const SET_PARAM_FRAME_LEN: usize = HEADER_LEN + 7 + TRAILER_LEN;
+
+/// Pause after a failed receive before listening again, so a broken bus
+/// does not spin the executor.
+const RECEIVE_BACKOFF: Duration = Duration::from_millis(10);
+
+// ── Configuration ────────────────────────────────────────────────────────
+
+/// Settings for [`daisy_sync_task`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct SyncConfig {
+    /// How often pending changes are sent when no notification arrives.
+    pub period: Duration,
+    /// Changes per transport write (clamped to `1..=`[`MAX_BATCH`]).
+    pub max_batch: usize,
+}
+
+impl Default for SyncConfig {
+    /// A 20 ms period and full batches.
+    fn default() -> Self {
+        Self {
+            period: Duration::from_millis(20),
+            max_batch: MAX_BATCH,
+        }
+    }
+}
+
+// ── Sync logic ───────────────────────────────────────────────────────────
+
+/// Sends pending parameter changes to the Daisy Seed.
+///
+/// Each round has the same shape as the display task's update:
+///
+/// - **Step 1** — Lock the parameters and snapshot up to one batch of
+///   parameters with `changed_i2c` set, **without** clearing the flags.
+/// - **Step 2** — Encode one `SetParam` frame per change, back to back in
+///   one buffer (no mutex held).
+/// - **Step 3** — Send the batch in one [`LinkTransport::send`] (the I2C
+///   transport repeats it after a NACK).
+/// - **Step 4** — Lock the parameters and clear `changed_i2c` only for
+///   parameters whose position is still the one that was sent. A value
+///   that changed during the write keeps its flag and goes out next time.
+///
+/// If the send fails, no flag is cleared and the changes are sent again
+/// next round. Steps 1 and 4 are the same as [`Link`]'s synced phase; what
+/// `DaisySync` leaves out is the handshake, dump and heartbeat.
+pub struct DaisySync<T> {
+    transport: T,
+    config: SyncConfig,
+}
+
+impl<T: LinkTransport> DaisySync<T> {
+    /// Sync over `transport`.
+    pub fn new(transport: T, config: SyncConfig) -> Self {
+        Self { transport, config }
+    }
+
+    /// Give back the transport.
+    pub fn release(self) -> T {
+        self.transport
+    }
+
+    /// Send every pending change, one batch at a time, and return how
+    /// many were sent.
+    ///
+    /// Each parameter is visited once per call, so a knob that keeps
+    /// turning cannot hold the loop.
+    ///
+    /// # Errors
+    /// The transport error of a send that failed. Changes sent before it
+    /// stay acknowledged.
+    pub async fn sync<M: RawMutex, const N_PAGES: usize, const PARAMS: usize>(
+        &mut self,
+        param_values: &Mutex<M, ParameterValues<N_PAGES, PARAMS>>,
+    ) -> Result<usize, T::Error> {
+        let mut next = 0;
+        let mut sent = 0;
+
+        loop {
+            // ── Step 1: snapshot (mutex held briefly) ──────────────────
+            let (batch, resume) = {
+                let params = param_values.lock().await;
+                ChangeBatch::collect(&params, next, self.config.max_batch)
+            };
+            if batch.is_empty() {
+                return Ok(sent);
+            }
+
+            // ── Step 2: encode (no mutex) ──────────────────────────────
+            let mut buf = [0u8; MAX_BATCH * SET_PARAM_FRAME_LEN];
+            let mut len = 0;
+            for i in 0..batch.len() {
+                len += encode_frame(&batch.message(i), &mut buf[len..])
+                    .expect("buffer holds a full batch of SetParam frames");
+            }
+
+            // ── Step 3: send ───────────────────────────────────────────
+            self.transport.send(&buf[..len]).await?;
+
+            // ── Step 4: clear only what was sent ───────────────────────
+            batch.acknowledge(&mut *param_values.lock().await);
+            sent += batch.len();
+
+            match resume {
+                Some(index) => next = index,
+                None => return Ok(sent),
+            }
+        }
+    }
+}
+
+// ── Tasks ────────────────────────────────────────────────────────────────
+
+/// Periodic parameter push loop.
+///
+/// This is a regular `async fn` — **not** an Embassy `#[task]`. Callers
+/// should create a thin, concrete task wrapper that calls this function,
+/// since Embassy tasks cannot be generic:
+///
+/// ```ignore
+/// #[embassy_executor::task]
+/// async fn daisy_task(
+///     transport: MyConcreteTransport,
+///     params: &'static Mutex<CriticalSectionRawMutex, ParameterValues>,
+///     notify: &'static Signal<CriticalSectionRawMutex, ()>,
+/// ) {
+///     daisy_sync_task(transport, params, SyncConfig::default(), Some(notify)).await
+/// }
+/// ```
+///
+/// Wakes every `config.period`, or as soon as `notify` is signalled (e.g.
+/// by the encoder task after a change), and runs one [`DaisySync::sync`]
+/// round. A failed round is logged; its changes keep their flags and are
+/// retried on the next wake-up.
+pub async fn daisy_sync_task<T, const N_PAGES: usize>(
+    transport: T,
+    param_values: &'static Mutex<
+        CriticalSectionRawMutex,
+        ParameterValues<N_PAGES, PARAMS_PER_PAGE>,
+    >,
+    config: SyncConfig,
+    notify: Option<&'static Signal<CriticalSectionRawMutex, ()>>,
+) -> !
+where
+    T: LinkTransport,
+{
+    let mut sync = DaisySync::new(transport, config);
+    loop {
+        match notify {
+            Some(signal) => {
+                select(Timer::after(config.period), signal.wait()).await;
+            }
+            None => Timer::after(config.period).await,
+        }
+
+        match sync.sync(param_values).await {
+            Ok(0) => {}
+            Ok(_sent) => {
+                #[cfg(feature = "defmt")]
+                defmt::trace!("Sent {} parameter changes to the Daisy", _sent);
+            }
+            Err(_) => {
+                #[cfg(feature = "defmt")]
+                defmt::warn!("Daisy sync failed; changes kept for the next round");
+            }
+        }
+    }
+}
+
+/// Full protocol loop: runs a [`Link`] over `transport`.
+///
+/// A regular `async fn` like [`daisy_sync_task`]; wrap it in a concrete
+/// Embassy task.
+///
+/// Each pass sends everything [`Link::poll`] returns (locking the
+/// parameters only while polling, and batching frames into one send),
+/// then waits for whichever comes first: a frame from the Daisy, the
+/// link's next deadline, or `notify` (signalled after a local change so it
+/// goes out without waiting for the heartbeat). Changes are confirmed
+/// with [`Link::sent`] once their send succeeded; a failed send keeps
+/// their flags and restarts the handshake ([`Link::send_failed`]).
+pub async fn link_sync_task<T, const N_PAGES: usize>(
+    mut transport: T,
+    param_values: &'static Mutex<
+        CriticalSectionRawMutex,
+        ParameterValues<N_PAGES, PARAMS_PER_PAGE>,
+    >,
+    config: LinkConfig,
+    notify: Option<&'static Signal<CriticalSectionRawMutex, ()>>,
+) -> !
+where
+    T: LinkTransport,
+{
+    let mut link = Link::new(config, Instant::now());
+    let mut tx = [0u8; MAX_BATCH * MAX_FRAME_LEN];
+    let mut rx = [0u8; MAX_FRAME_LEN];
+
+    loop {
+        // ── Send what the link has to say ──────────────────────────────
+        let (len, more) = {
+            let mut params = param_values.lock().await;
+            let now = Instant::now();
+            let mut len = 0;
+            // Cleared once the link has nothing more to say.
+            let mut more = true;
+            while len + MAX_FRAME_LEN <= tx.len() {
+                let Some(message) = link.poll(now, &mut params) else {
+                    more = false;
+                    break;
+                };
+                len += encode_frame(&message, &mut tx[len..])
+                    .expect("buffer has room for a whole frame");
+            }
+            (len, more)
+        };
+        if len > 0 {
+            match transport.send(&tx[..len]).await {
+                Ok(()) => link.sent(&mut *param_values.lock().await),
+                Err(_) => {
+                    #[cfg(feature = "defmt")]
+                    defmt::warn!("Daisy link send failed; restarting handshake");
+                    link.send_failed(Instant::now());
+                }
+            }
+        }
+        if more {
+            continue;
+        }
+
+        // ── Wait for a frame, a deadline or a local change ─────────────
+        let changed = async {
+            match notify {
+                Some(signal) => signal.wait().await,
+                None => core::future::pending().await,
+            }
+        };
+        let received = match select3(
+            transport.receive(&mut rx),
+            Timer::at(link.next_deadline()),
+            changed,
+        )
+        .await
+        {
+            Either3::First(received) => received,
+            Either3::Second(()) | Either3::Third(()) => continue,
+        };
+
+        match received {
+            Ok(len) => match decode_frame(&rx[..len]) {
+                Ok((message, _)) => {
+                    let mut params = param_values.lock().await;
+                    link.receive(Instant::now(), &mut params, message);
+                }
+                Err(_e) => {
+                    #[cfg(feature = "defmt")]
+                    defmt::warn!("Dropping frame from the Daisy: {}", _e);
+                }
+            },
+            Err(_) => {
+                #[cfg(feature = "defmt")]
+                defmt::warn!("Daisy link receive failed");
+                Timer::after(RECEIVE_BACKOFF).await;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use std::boxed::Box;
+    use std::cell::Cell;
+    use std::collections::BTreeMap;
+    use std::rc::Rc;
+    use std::vec::Vec;
+
+    use core::pin::pin;
+    use core::task::Poll;
+
+    use embassy_futures::poll_once;
+    use embassy_sync::blocking_mutex::raw::NoopRawMutex;
+    use embassy_time::MockDriver;
+    use spirant::parameter_values::{
+        PageDescriptor, ParamDescriptor, ParameterSchema, ParameterSlot,
+    };
+
+    use super::*;
+    use crate::frame::{decode_frame, VERSION};
+    use crate::link::Authority;
+    use crate::memory::{MemoryChannel, MemoryTransport};
+    use crate::message::Message;
+    use crate::responder::output_value;
+    use crate::schema::schema_hash;
+    use crate::transport::tests::block_on;
+
+    type Params = Mutex<NoopRawMutex, ParameterValues>;
+
+    /// Transport that records every send, failing the first `failures`.
+    #[derive(Default)]
+    struct Recorder<'a> {
+        sends: Vec<Vec<u8>>,
+        failures: usize,
+        /// Parameters whose encoder 0 is turned during each successful
+        /// send, as if the knob moved while the bus was busy.
+        turn_during_send: Option<&'a Params>,
+    }
+
+    impl Recorder<'_> {
+        /// Every message sent, in order.
+        fn messages(&self) -> Vec<Message> {
+            let mut out = Vec::new();
+            for bytes in &self.sends {
+                let mut rest = &bytes[..];
+                while !rest.is_empty() {
+                    let (message, used) = decode_frame(rest).unwrap();
+                    out.push(message);
+                    rest = &rest[used..];
+                }
+            }
+            out
+        }
+    }
+
+    impl LinkTransport for Recorder<'_> {
+        type Error = ();
+
+        async fn send(&mut self, frames: &[u8]) -> Result<(), ()> {
+            if self.failures > 0 {
+                self.failures -= 1;
+                return Err(());
+            }
+            self.sends.push(frames.to_vec());
+            if let Some(params) = self.turn_during_send {
+                params.try_lock().unwrap().update_from_encoder(0, 1);
+            }
+            Ok(())
+        }
+
+        async fn receive(&mut self, _buf: &mut [u8; MAX_FRAME_LEN]) -> Result<usize, ()> {
+            Err(())
+        }
+    }
+
+    fn new_sync(recorder: Recorder<'_>, max_batch: usize) -> DaisySync<Recorder<'_>> {
+        DaisySync::new(
+            recorder,
+            SyncConfig {
+                max_batch,
+                ..SyncConfig::default()
+            },
+        )
+    }
+
+    fn flagged(params: &Params) -> usize {
+        block_on(params.lock())
+            .pages
+            .iter()
+            .flat_map(|page| page.params.iter())
+            .filter(|slot| matches!(slot, ParameterSlot::Active(p) if p.changed_i2c))
+            .count()
+    }
+
+    #[test]
+    fn pending_changes_are_sent_in_batches_and_cleared() {
+        let params: Params = Mutex::new(ParameterValues::new());
+        {
+            let mut pv = block_on(params.lock());
+            for encoder in 0..4 {
+                pv.update_from_encoder(encoder, 1);
+            }
+            pv.set_active_page(1).unwrap();
+            pv.update_from_encoder(2, 3);
+        }
+        let mut sync = new_sync(Recorder::default(), 2);
+
+        assert_eq!(block_on(sync.sync(&params)), Ok(5));
+        let recorder = sync.release();
+        assert_eq!(recorder.sends.len(), 3);
+
+        let indices: Vec<u16> = recorder
+            .messages()
+            .iter()
+            .map(|m| match m {
+                Message::SetParam { index, .. } => *index,
+                other => panic!("unexpected {other:?}"),
+            })
+            .collect();
+        assert_eq!(indices, [0, 1, 2, 3, 6]);
+        assert_eq!(flagged(&params), 0);
+
+        // Nothing left to send.
+        let mut sync = new_sync(Recorder::default(), 2);
+        assert_eq!(block_on(sync.sync(&params)), Ok(0));
+        assert!(sync.release().sends.is_empty());
+    }
+
+    #[test]
+    fn values_are_sent_in_output_units() {
+        let params: Params = Mutex::new(ParameterValues::new());
+        let expected = {
+            let mut pv = block_on(params.lock());
+            pv.update_from_encoder(0, 10);
+            let position = pv.pages[0].params[0].as_ref().unwrap().value;
+            pv.schema().param(0, 0).unwrap().map_value(position)
+        };
+        let mut sync = new_sync(Recorder::default(), MAX_BATCH);
+
+        block_on(sync.sync(&params)).unwrap();
+        assert_eq!(
+            sync.release().messages(),
+            [Message::SetParam {
+                index: 0,
+                value: expected
+            }]
+        );
+    }
+
+    #[test]
+    fn failed_send_keeps_flags() {
+        let params: Params = Mutex::new(ParameterValues::new());
+        block_on(params.lock()).update_from_encoder(1, 1);
+        let recorder = Recorder {
+            failures: 1,
+            ..Recorder::default()
+        };
+        let mut sync = new_sync(recorder, MAX_BATCH);
+
+        assert_eq!(block_on(sync.sync(&params)), Err(()));
+        assert_eq!(flagged(&params), 1);
+        // The next round succeeds.
+        assert_eq!(block_on(sync.sync(&params)), Ok(1));
+        assert_eq!(flagged(&params), 0);
+    }
+
+    #[test]
+    fn change_during_send_keeps_its_flag() {
+        let params: Params = Mutex::new(ParameterValues::new());
+        {
+            let mut pv = block_on(params.lock());
+            pv.update_from_encoder(0, 1);
+            pv.update_from_encoder(1, 1);
+        }
+        let recorder = Recorder {
+            turn_during_send: Some(&params),
+            ..Recorder::default()
+        };
+        let mut sync = new_sync(recorder, MAX_BATCH);
+
+        assert_eq!(block_on(sync.sync(&params)), Ok(2));
+        drop(sync);
+        let pv = block_on(params.lock());
+        // Encoder 0 moved on after its value went out, so it is sent again.
+        assert!(pv.pages[0].params[0].as_ref().unwrap().changed_i2c);
+        assert!(!pv.pages[0].params[1].as_ref().unwrap().changed_i2c);
+    }
+
+    // ── link_sync_task ───────────────────────────────────────────────────
+
+    /// Sixteen pages of active slots: a Pico-authority dump of 64
+    /// `SetParam`s does not fit in one send.
+    static WIDE_SCHEMA: ParameterSchema<16, PARAMS_PER_PAGE> = ParameterSchema::new(
+        [PageDescriptor::new(
+            "Page",
+            [Some(ParamDescriptor::new("Param")); PARAMS_PER_PAGE],
+        ); 16],
+    );
+
+    type Channel = MemoryChannel<CriticalSectionRawMutex, 128>;
+
+    /// Pico end of the channel, counting sends and failing the next
+    /// `fail` of them.
+    struct Probe<'a> {
+        inner: MemoryTransport<'a, CriticalSectionRawMutex, 128>,
+        sends: Rc<Cell<usize>>,
+        fail: Rc<Cell<usize>>,
+    }
+
+    impl LinkTransport for Probe<'_> {
+        type Error = ();
+
+        async fn send(&mut self, frames: &[u8]) -> Result<(), ()> {
+            if self.fail.get() > 0 {
+                self.fail.set(self.fail.get() - 1);
+                return Err(());
+            }
+            self.sends.set(self.sends.get() + 1);
+            self.inner.send(frames).await.map_err(|_| ())
+        }
+
+        async fn receive(&mut self, buf: &mut [u8; MAX_FRAME_LEN]) -> Result<usize, ()> {
+            self.inner.receive(buf).await.map_err(|_| ())
+        }
+    }
+
+    /// Every message waiting at the Daisy end.
+    fn drain(daisy: &mut MemoryTransport<'_, CriticalSectionRawMutex, 128>) -> Vec<Message> {
+        let mut out = Vec::new();
+        let mut buf = [0u8; MAX_FRAME_LEN];
+        while let Poll::Ready(Ok(len)) = poll_once(daisy.receive(&mut buf)) {
+            out.push(decode_frame(&buf[..len]).unwrap().0);
+        }
+        out
+    }
+
+    fn reply(daisy: &mut MemoryTransport<'_, CriticalSectionRawMutex, 128>, message: Message) {
+        let mut buf = [0u8; MAX_FRAME_LEN];
+        let len = encode_frame(&message, &mut buf).unwrap();
+        block_on(daisy.send(&buf[..len])).unwrap();
+    }
+
+    /// Index and value of every `SetParam` in `messages`.
+    fn set_params(messages: &[Message]) -> BTreeMap<u16, i32> {
+        messages
+            .iter()
+            .filter_map(|m| match m {
+                Message::SetParam { index, value } => Some((*index, *value)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn link_task_syncs_over_a_memory_transport() {
+        MockDriver::get().reset();
+        let params: &'static Mutex<CriticalSectionRawMutex, ParameterValues<16, PARAMS_PER_PAGE>> =
+            Box::leak(Box::new(Mutex::new(ParameterValues::with_schema(
+                &WIDE_SCHEMA,
+            ))));
+        let notify: &'static Signal<CriticalSectionRawMutex, ()> =
+            Box::leak(Box::new(Signal::new()));
+        let hash = schema_hash(&WIDE_SCHEMA);
+        let hello = Message::Hello {
+            version: VERSION,
+            schema_hash: hash,
+            param_count: 64,
+        };
+        let flagged = || {
+            block_on(params.lock())
+                .pages
+                .iter()
+                .flat_map(|page| page.params.iter())
+                .filter(|slot| matches!(slot, ParameterSlot::Active(p) if p.changed_i2c))
+                .count()
+        };
+        let output = |index: usize| output_value(&block_on(params.lock()), index).unwrap();
+
+        let channel = Channel::new();
+        let (pico, mut daisy) = channel.split();
+        let sends = Rc::new(Cell::new(0));
+        let fail = Rc::new(Cell::new(0));
+        let probe = Probe {
+            inner: pico,
+            sends: sends.clone(),
+            fail: fail.clone(),
+        };
+        let mut task = pin!(link_sync_task(
+            probe,
+            params,
+            LinkConfig::new(hash, Authority::Pico),
+            Some(notify),
+        ));
+        let mut run = || assert!(poll_once(task.as_mut()).is_pending());
+
+        // Handshake, then the whole dump in one pass: `more` keeps the
+        // task sending until the link has nothing left.
+        run();
+        assert_eq!(drain(&mut daisy), [hello]);
+        reply(&mut daisy, hello);
+        run();
+        let dump = drain(&mut daisy);
+        assert_eq!(dump.len(), 64);
+        assert_eq!(set_params(&dump).len(), 64);
+        assert_eq!(sends.get(), 1 + 3);
+
+        // A local change cancels the wait and goes out at once; the send
+        // succeeded, so its flag is cleared.
+        block_on(params.lock()).update_from_encoder(0, 5);
+        notify.signal(());
+        run();
+        assert_eq!(
+            drain(&mut daisy),
+            [Message::SetParam {
+                index: 0,
+                value: output(0),
+            }]
+        );
+        assert_eq!(flagged(), 0);
+
+        // The receive dropped by that wake-up lost nothing.
+        reply(&mut daisy, Message::Ping { seq: 9 });
+        run();
+        assert_eq!(drain(&mut daisy), [Message::Pong { seq: 9 }]);
+
+        // A failed send keeps the flag and restarts the handshake after
+        // `hello_interval`.
+        fail.set(1);
+        block_on(params.lock()).update_from_encoder(1, 3);
+        notify.signal(());
+        run();
+        assert!(drain(&mut daisy).is_empty());
+        assert_eq!(flagged(), 1);
+        MockDriver::get().advance(Duration::from_millis(249));
+        run();
+        assert!(drain(&mut daisy).is_empty());
+        MockDriver::get().advance(Duration::from_millis(1));
+        run();
+        assert_eq!(drain(&mut daisy), [hello]);
+
+        // The new dump carries the change.
+        reply(&mut daisy, hello);
+        run();
+        let dump = set_params(&drain(&mut daisy));
+        assert_eq!(dump.len(), 64);
+        assert_eq!(dump[&1], output(1));
+        assert_eq!(flagged(), 0);
+    }
+}
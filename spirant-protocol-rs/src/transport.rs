@@ -0,0 +1,110 @@
+//! Moving frames between the boards.
+//!
+//! [`LinkTransport`] is the only thing the sync logic knows about the
+//! wire: it sends frames and waits for frames. The same
+//! [`Link`](crate::Link) and [`DaisySync`](crate::DaisySync) run over any
+//! implementation:
+//!
+//! - **[`I2cControllerTransport`](crate::I2cControllerTransport)** — The
+//!   Pico drives the bus and polls the Daisy for frames.
+//! - **[`I2cTargetTransport`](crate::I2cTargetTransport)** — The Daisy
+//!   drives the bus; frames are queued until it reads them.
+//! - **[`CobsTransport`](crate::CobsTransport)** — Any byte stream (UART,
+//!   USB CDC), with each frame COBS-encoded and ended by a zero byte.
+//! - **[`MemoryChannel`](crate::MemoryChannel)** — Two connected ends in
+//!   memory, for tests and simulations.
+//!
+//! Enabled by the **`task`** feature.
+
+use core::future::Future;
+
+use crate::frame::{frame_len, MAX_FRAME_LEN};
+
+/// A bidirectional, frame-oriented connection to the other board.
+///
+/// Frames are passed whole, as produced by
+/// [`encode_frame`](crate::encode_frame); the transport only needs to keep
+/// them apart, not understand them. Corrupted frames may be delivered or
+/// dropped; the receiver's [`decode_frame`](crate::decode_frame) rejects
+/// them either way.
+///
+/// # Cancellation
+///
+/// The link task waits on [`receive()`](Self::receive) and a timer at the
+/// same time and drops whichever loses. Implementations keep partially
+/// received data in `self`, so a dropped `receive()` future loses at most
+/// the frame being transferred at that moment.
+pub trait LinkTransport {
+    /// Error from the underlying bus or stream.
+    type Error;
+
+    /// Send one or more complete frames, back to back in `frames`.
+    ///
+    /// Depending on the transport this returns once the frames are on the
+    /// wire or once they are queued for the peer to collect.
+    fn send(&mut self, frames: &[u8]) -> impl Future<Output = Result<(), Self::Error>>;
+
+    /// Wait for the next frame, copy it to the start of `buf` and return
+    /// its length.
+    fn receive(
+        &mut self,
+        buf: &mut [u8; MAX_FRAME_LEN],
+    ) -> impl Future<Output = Result<usize, Self::Error>>;
+}
+
+/// Split back-to-back frames into single frames.
+///
+/// Bytes that do not start with a valid header are returned as one final
+/// piece, for the receiver to reject.
+pub(crate) fn split_frames(mut bytes: &[u8]) -> impl Iterator<Item = &[u8]> {
+    core::iter::from_fn(move || {
+        if bytes.is_empty() {
+            return None;
+        }
+        let len = frame_len(bytes).map_or(bytes.len(), |len| len.min(bytes.len()));
+        let (frame, rest) = bytes.split_at(len);
+        bytes = rest;
+        Some(frame)
+    })
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use core::future::Future;
+    use core::pin::pin;
+    use core::task::{Context, Poll, Waker};
+
+    use super::*;
+    use crate::frame::encode_frame;
+    use crate::message::Message;
+
+    /// Run `fut` to completion on the current thread.
+    ///
+    /// Panics after a bounded number of polls, so a future that would
+    /// wait forever fails the test instead of hanging it.
+    pub(crate) fn block_on<F: Future>(fut: F) -> F::Output {
+        let mut fut = pin!(fut);
+        let mut cx = Context::from_waker(Waker::noop());
+        for _ in 0..10_000 {
+            if let Poll::Ready(out) = fut.as_mut().poll(&mut cx) {
+                return out;
+            }
+        }
+        panic!("future did not complete");
+    }
+
+    #[test]
+    fn frames_are_split_on_their_headers() {
+        let mut buf = [0u8; 3 * MAX_FRAME_LEN];
+        let first = encode_frame(&Message::Ping { seq: 1 }, &mut buf).unwrap();
+        let second = encode_frame(&Message::GetParam { index: 3 }, &mut buf[first..]).unwrap();
+        let end = first + second;
+        buf[end..end + 2].copy_from_slice(&[0xFF, 0xFF]);
+
+        let mut pieces = split_frames(&buf[..end + 2]);
+        assert_eq!(pieces.next(), Some(&buf[..first]));
+        assert_eq!(pieces.next(), Some(&buf[first..end]));
+        assert_eq!(pieces.next(), Some(&[0xFF, 0xFF][..]));
+        assert_eq!(pieces.next(), None);
+    }
+}
@@ -0,0 +1,405 @@
+//! Seesaw protocol driver.
+//!
+//! Implements the I2C communication primitives required by the Seesaw
+//! firmware, including the mandatory delay between the write and read
+//! phases of every register read.
+
+use embedded_hal_async::delay::DelayNs;
+use embedded_hal_async::i2c::{I2c, Operation};
+
+use crate::registers::{RegisterAddress, StatusRegister};
+use crate::value::{RegisterValue, MAX_VALUE_SIZE};
+
+/// Timing parameters for a [`Seesaw`] device.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct SeesawConfig {
+    /// Pause between writing a register address and reading the answer,
+    /// in microseconds. The firmware specification requires at least 125;
+    /// slow modules (ADC, touch) may need more.
+    pub read_delay_us: u32,
+    /// Time the firmware needs to restart after a software reset, in
+    /// milliseconds.
+    pub reset_delay_ms: u32,
+}
+
+impl SeesawConfig {
+    /// Timings from the Seesaw firmware specification.
+    pub const DEFAULT: Self = Self {
+        read_delay_us: 125,
+        reset_delay_ms: 10,
+    };
+}
+
+impl Default for SeesawConfig {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+/// A Seesaw device on an I2C bus.
+///
+/// Owns an I2C peripheral and a delay provider and offers register reads
+/// and writes that respect the Seesaw timing requirements. Device drivers
+/// (e.g. the quad encoder board) wrap one of these and add their own
+/// validation on top.
+///
+/// Errors are the bus's own `I2C::Error`; nothing in the protocol layer can
+/// fail otherwise.
+///
+/// `I2C` must keep the [`I2c::transaction`] contract that adjacent
+/// operations of the same kind go out without a STOP or repeated START in
+/// between. [`write_bytes`](Self::write_bytes) sends the register address
+/// and the payload as two writes, and [`read_burst`](Self::read_burst)
+/// reads each value into its own buffer; a HAL that splits them corrupts
+/// both.
+pub struct Seesaw<I2C, D> {
+    i2c: I2C,
+    delay: D,
+    address: u8,
+    config: SeesawConfig,
+}
+
+impl<I2C, D> Seesaw<I2C, D>
+where
+    I2C: I2c,
+    D: DelayNs,
+{
+    /// Create a driver with the default [`SeesawConfig`].
+    ///
+    /// # Arguments
+    /// * `i2c` — I2C peripheral (takes ownership for exclusive access)
+    /// * `delay` — Delay provider for the read and reset pauses
+    /// * `address` — 7-bit I2C device address
+    pub fn new(i2c: I2C, delay: D, address: u8) -> Self {
+        Self::with_config(i2c, delay, address, SeesawConfig::DEFAULT)
+    }
+
+    /// Create a driver with custom timings.
+    pub fn with_config(i2c: I2C, delay: D, address: u8, config: SeesawConfig) -> Self {
+        Self {
+            i2c,
+            delay,
+            address,
+            config,
+        }
+    }
+
+    /// Returns the 7-bit I2C address.
+    pub fn address(&self) -> u8 {
+        self.address
+    }
+
+    /// Returns the current timings.
+    pub fn config(&self) -> &SeesawConfig {
+        &self.config
+    }
+
+    /// Replace the timings.
+    pub fn set_config(&mut self, config: SeesawConfig) {
+        self.config = config;
+    }
+
+    /// Give back the I2C peripheral and delay provider.
+    pub fn release(self) -> (I2C, D) {
+        (self.i2c, self.delay)
+    }
+
+    // -----------------------------------------------------------------------
+    // Core protocol primitives
+    // -----------------------------------------------------------------------
+
+    /// Read `buffer.len()` bytes starting at `register`, waiting the
+    /// configured read delay.
+    ///
+    /// Uses separate write and read transactions rather than `write_read()`
+    /// because many I2C implementations use a repeated-start for
+    /// `write_read()`, which does not leave the firmware time to prepare
+    /// the response.
+    pub async fn read_bytes(
+        &mut self,
+        register: impl Into<RegisterAddress>,
+        buffer: &mut [u8],
+    ) -> Result<(), I2C::Error> {
+        let delay_us = self.config.read_delay_us;
+        self.read_bytes_with_delay(register, buffer, delay_us).await
+    }
+
+    /// Like [`read_bytes`](Self::read_bytes) with an explicit delay, for
+    /// registers that need longer than the configured default.
+    pub async fn read_bytes_with_delay(
+        &mut self,
+        register: impl Into<RegisterAddress>,
+        buffer: &mut [u8],
+        delay_us: u32,
+    ) -> Result<(), I2C::Error> {
+        let register = register.into().to_bytes();
+        self.i2c.write(self.address, &register).await?;
+
+        // Critical delay — Seesaw firmware needs time to prepare response
+        self.delay.delay_us(delay_us).await;
+
+        self.i2c.read(self.address, buffer).await
+    }
+
+    /// Write `register` followed by `data` in a single transaction.
+    ///
+    /// The firmware buffers at most 32 data bytes per write.
+    pub async fn write_bytes(
+        &mut self,
+        register: impl Into<RegisterAddress>,
+        data: &[u8],
+    ) -> Result<(), I2C::Error> {
+        let register = register.into().to_bytes();
+        // Adjacent writes are merged into one I2C transaction (see the
+        // bound on `I2C`), so the address and payload need no intermediate
+        // buffer.
+        self.i2c
+            .transaction(
+                self.address,
+                &mut [Operation::Write(&register), Operation::Write(data)],
+            )
+            .await
+    }
+
+    // -----------------------------------------------------------------------
+    // Typed reads and writes
+    // -----------------------------------------------------------------------
+
+    /// Read one value of type `T` (big-endian on the wire).
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use embedded_hal_async::{delay::DelayNs, i2c::I2c};
+    /// # use seesaw_driver::registers::{EncoderRegister, Register};
+    /// # async fn example<I: I2c, D: DelayNs>(
+    /// #     seesaw: &mut seesaw_driver::Seesaw<I, D>,
+    /// # ) -> Result<(), I::Error> {
+    /// let position: i32 = seesaw.read(EncoderRegister::Position.channel(0)).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn read<T: RegisterValue>(
+        &mut self,
+        register: impl Into<RegisterAddress>,
+    ) -> Result<T, I2C::Error> {
+        let mut buf = [0u8; MAX_VALUE_SIZE];
+        self.read_bytes(register, &mut buf[..T::SIZE]).await?;
+        Ok(T::from_be_slice(&buf))
+    }
+
+    /// Read `N` consecutive values of type `T` starting at `register` in a
+    /// single transaction (e.g. several ADC channels).
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use embedded_hal_async::{delay::DelayNs, i2c::I2c};
+    /// # use seesaw_driver::registers::{AdcRegister, Register};
+    /// # async fn example<I: I2c, D: DelayNs>(
+    /// #     seesaw: &mut seesaw_driver::Seesaw<I, D>,
+    /// # ) -> Result<(), I::Error> {
+    /// let channels: [u16; 4] = seesaw.read_burst(AdcRegister::Channel.channel(0)).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn read_burst<T: RegisterValue, const N: usize>(
+        &mut self,
+        register: impl Into<RegisterAddress>,
+    ) -> Result<[T; N], I2C::Error> {
+        let register = register.into().to_bytes();
+        self.i2c.write(self.address, &register).await?;
+        self.delay.delay_us(self.config.read_delay_us).await;
+
+        // Adjacent reads are merged into one transaction (see the bound on
+        // `I2C`), so each value can land in its own chunk.
+        let mut chunks = [[0u8; MAX_VALUE_SIZE]; N];
+        let mut ops = chunks
+            .each_mut()
+            .map(|chunk| Operation::Read(&mut chunk[..T::SIZE]));
+        self.i2c.transaction(self.address, &mut ops).await?;
+
+        Ok(chunks.map(|chunk| T::from_be_slice(&chunk)))
+    }
+
+    /// Write one value of type `T` (big-endian on the wire).
+    pub async fn write<T: RegisterValue>(
+        &mut self,
+        register: impl Into<RegisterAddress>,
+        value: T,
+    ) -> Result<(), I2C::Error> {
+        let mut buf = [0u8; MAX_VALUE_SIZE];
+        value.write_be_slice(&mut buf);
+        self.write_bytes(register, &buf[..T::SIZE]).await
+    }
+
+    /// Wait `ms` milliseconds using the driver's delay provider.
+    pub async fn delay_ms(&mut self, ms: u32) {
+        self.delay.delay_ms(ms).await;
+    }
+
+    // -----------------------------------------------------------------------
+    // Status module
+    // -----------------------------------------------------------------------
+
+    /// Read the chip's hardware ID (see
+    /// [`SEESAW_HW_IDS`](crate::registers::SEESAW_HW_IDS)).
+    pub async fn hw_id(&mut self) -> Result<u8, I2C::Error> {
+        self.read(StatusRegister::HwId).await
+    }
+
+    /// Read the raw VERSION register: product number in the upper 16 bits,
+    /// build date code in the lower 16.
+    pub async fn version(&mut self) -> Result<u32, I2C::Error> {
+        self.read(StatusRegister::Version).await
+    }
+
+    /// Read the bitmask of compiled-in modules (bit `n` = module ID `n`).
+    pub async fn options(&mut self) -> Result<u32, I2C::Error> {
+        self.read(StatusRegister::Options).await
+    }
+
+    /// Reset the chip and wait the configured restart time.
+    pub async fn software_reset(&mut self) -> Result<(), I2C::Error> {
+        self.write(StatusRegister::SwRst, 0xFFu8).await?;
+        self.delay.delay_ms(self.config.reset_delay_ms).await;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use core::future::Future;
+    use core::pin::pin;
+    use core::task::{Context, Poll, Waker};
+    use std::vec;
+    use std::vec::Vec;
+
+    use embedded_hal_async::i2c::{ErrorKind, ErrorType};
+
+    use super::*;
+    use crate::registers::{AdcRegister, EncoderRegister, NeopixelRegister, Register};
+
+    /// Drive a future that never actually waits to completion.
+    fn block_on<F: Future>(fut: F) -> F::Output {
+        let mut fut = pin!(fut);
+        let mut cx = Context::from_waker(Waker::noop());
+        loop {
+            if let Poll::Ready(out) = fut.as_mut().poll(&mut cx) {
+                return out;
+            }
+        }
+    }
+
+    /// Records every bus transfer and answers reads from a fixed script.
+    #[derive(Default)]
+    struct FakeBus {
+        writes: Vec<Vec<u8>>,
+        reads: Vec<u8>,
+        read_lens: Vec<usize>,
+    }
+
+    impl ErrorType for FakeBus {
+        type Error = ErrorKind;
+    }
+
+    impl I2c for FakeBus {
+        async fn transaction(
+            &mut self,
+            address: u8,
+            operations: &mut [Operation<'_>],
+        ) -> Result<(), Self::Error> {
+            assert_eq!(address, 0x49);
+            let mut write = Vec::new();
+            let mut read_len = 0;
+            for op in operations {
+                match op {
+                    Operation::Write(data) => write.extend_from_slice(data),
+                    Operation::Read(buf) => {
+                        for b in buf.iter_mut() {
+                            *b = self.reads.remove(0);
+                        }
+                        read_len += buf.len();
+                    }
+                }
+            }
+            if !write.is_empty() {
+                self.writes.push(write);
+            }
+            if read_len > 0 {
+                self.read_lens.push(read_len);
+            }
+            Ok(())
+        }
+    }
+
+    /// Sums requested delays.
+    #[derive(Default)]
+    struct FakeDelay {
+        total_ns: u64,
+    }
+
+    impl DelayNs for FakeDelay {
+        async fn delay_ns(&mut self, ns: u32) {
+            self.total_ns += ns as u64;
+        }
+    }
+
+    fn seesaw(reads: &[u8]) -> Seesaw<FakeBus, FakeDelay> {
+        let bus = FakeBus {
+            reads: reads.to_vec(),
+            ..Default::default()
+        };
+        Seesaw::new(bus, FakeDelay::default(), 0x49)
+    }
+
+    #[test]
+    fn typed_read_writes_address_then_waits() {
+        let mut s = seesaw(&[0xFF, 0xFF, 0xFF, 0xFD]);
+        let v: i32 = block_on(s.read(EncoderRegister::Position.channel(1))).unwrap();
+        assert_eq!(v, -3);
+        let (bus, delay) = s.release();
+        assert_eq!(bus.writes, [[0x11, 0x31]]);
+        assert_eq!(delay.total_ns, 125_000);
+    }
+
+    #[test]
+    fn typed_write_is_one_transaction() {
+        let mut s = seesaw(&[]);
+        block_on(s.write(NeopixelRegister::BufLength, 12u16)).unwrap();
+        block_on(s.write(EncoderRegister::IntSet.channel(2), 1u8)).unwrap();
+        let (bus, _) = s.release();
+        assert_eq!(bus.writes, [vec![0x0E, 0x03, 0x00, 0x0C], vec![0x11, 0x12, 0x01]]);
+    }
+
+    #[test]
+    fn burst_read_is_one_transaction() {
+        let mut s = seesaw(&[0x01, 0x00, 0x02, 0x00, 0x03, 0xFF]);
+        let v: [u16; 3] = block_on(s.read_burst(AdcRegister::Channel.channel(0))).unwrap();
+        assert_eq!(v, [0x0100, 0x0200, 0x03FF]);
+        let (bus, _) = s.release();
+        assert_eq!(bus.read_lens, [6]);
+    }
+
+    #[test]
+    fn configurable_delays() {
+        let mut s = seesaw(&[0, 0]);
+        s.set_config(SeesawConfig {
+            read_delay_us: 500,
+            reset_delay_ms: 20,
+        });
+        let _: u16 = block_on(s.read(AdcRegister::Channel.channel(0))).unwrap();
+        block_on(s.software_reset()).unwrap();
+        let (bus, delay) = s.release();
+        assert_eq!(delay.total_ns, 500_000 + 20_000_000);
+        assert_eq!(bus.writes.last().unwrap(), &[0x00, 0x7F, 0xFF]);
+    }
+
+    #[test]
+    fn status_helpers() {
+        let mut s = seesaw(&[0x87, 0x16, 0x78, 0x2A, 0x1C]);
+        assert_eq!(block_on(s.hw_id()).unwrap(), 0x87);
+        assert_eq!(block_on(s.version()).unwrap() >> 16, 5752);
+    }
+}
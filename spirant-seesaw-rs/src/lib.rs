@@ -0,0 +1,52 @@
+//! Async core for Adafruit Seesaw-based I2C breakouts.
+//!
+//! Seesaw is the firmware Adafruit runs on the small microcontrollers of
+//! many breakouts (rotary encoders, NeoSlider, ADC/touch boards, the ANO
+//! navigation wheel, …). Every device speaks the same protocol: a two-byte
+//! register address `[module, offset]`, a short pause while the firmware
+//! prepares the answer, then a big-endian read.
+//!
+//! This crate implements that protocol once so device drivers only deal
+//! with their own registers.
+//!
+//! # Architecture
+//!
+//! - **[`registers`]** — [`Module`] IDs, per-module register enums and
+//!   [`RegisterAddress`].
+//! - **[`RegisterValue`]** — Big-endian encoding of `u8`–`u32`/`i8`–`i32`
+//!   so reads and writes are typed at the call site.
+//! - **[`Seesaw`]** — The bus-owning driver: typed reads and writes,
+//!   multi-register burst reads, and status-module helpers, with the
+//!   read delay configured through [`SeesawConfig`].
+//!
+//! # Quick start
+//!
+//! ```no_run
+//! # use embedded_hal_async::{delay::DelayNs, i2c::I2c};
+//! use seesaw_driver::registers::{EncoderRegister, Register, StatusRegister};
+//! use seesaw_driver::Seesaw;
+//!
+//! # async fn example<I: I2c, D: DelayNs>(i2c: I, delay: D) -> Result<(), I::Error> {
+//! // Any `embedded-hal-async` I2C bus and delay provider
+//! let mut seesaw = Seesaw::new(i2c, delay, 0x49);
+//!
+//! let hw_id: u8 = seesaw.read(StatusRegister::HwId).await?;
+//! let position: i32 = seesaw.read(EncoderRegister::Position.channel(0)).await?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! # Features
+//!
+//! - **`defmt`** — Enable [`defmt::Format`] implementations for embedded
+//!   logging.
+
+#![no_std]
+
+pub use driver::{Seesaw, SeesawConfig};
+pub use registers::{Module, Register, RegisterAddress};
+pub use value::RegisterValue;
+
+mod driver;
+pub mod registers;
+mod value;
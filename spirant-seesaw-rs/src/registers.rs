@@ -0,0 +1,262 @@
+//! Seesaw module IDs and register maps.
+//!
+//! The Seesaw firmware uses a two-byte register addressing scheme:
+//! - Byte 1: Module ID ([`Module`])
+//! - Byte 2: Register offset within the module
+//!
+//! Each module's registers are an enum implementing [`Register`], so an
+//! offset can only be paired with the module it belongs to. Channelled
+//! registers (one per encoder, ADC input, touch pad, …) add the channel
+//! number to the base offset via [`Register::channel`].
+
+// ---------------------------------------------------------------------------
+// Modules
+// ---------------------------------------------------------------------------
+
+/// Seesaw module identifiers (first address byte).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[repr(u8)]
+pub enum Module {
+    /// Identification, options and reset.
+    Status = 0x00,
+    /// Digital pins, pulls and pin-change interrupts.
+    Gpio = 0x01,
+    /// First SERCOM (UART/SPI bridge).
+    Sercom0 = 0x02,
+    /// PWM outputs.
+    Timer = 0x08,
+    /// Analogue inputs.
+    Adc = 0x09,
+    /// Analogue output.
+    Dac = 0x0A,
+    /// Interrupt routing.
+    Interrupt = 0x0B,
+    /// Debug access port.
+    Dap = 0x0C,
+    /// On-chip EEPROM.
+    Eeprom = 0x0D,
+    /// NeoPixel output.
+    Neopixel = 0x0E,
+    /// Capacitive touch inputs.
+    Touch = 0x0F,
+    /// Keypad scanner.
+    Keypad = 0x10,
+    /// Rotary encoders.
+    Encoder = 0x11,
+    /// Audio spectrum analyser.
+    Spectrum = 0x12,
+}
+
+/// HW_ID values of chips that run the Seesaw firmware: SAMD09 and the
+/// ATtiny8x6/8x7/16x6/16x7 family.
+pub const SEESAW_HW_IDS: [u8; 7] = [0x55, 0x84, 0x85, 0x86, 0x87, 0x88, 0x89];
+
+// ---------------------------------------------------------------------------
+// Addresses
+// ---------------------------------------------------------------------------
+
+/// A concrete `[module, offset]` register address.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct RegisterAddress {
+    /// Module ID byte.
+    pub module: u8,
+    /// Register offset within the module.
+    pub offset: u8,
+}
+
+impl RegisterAddress {
+    /// Address from raw bytes, for registers not covered by the enums.
+    pub const fn new(module: u8, offset: u8) -> Self {
+        Self { module, offset }
+    }
+
+    /// The two bytes sent on the bus.
+    pub const fn to_bytes(self) -> [u8; 2] {
+        [self.module, self.offset]
+    }
+}
+
+/// A register belonging to one [`Module`].
+pub trait Register: Copy {
+    /// Module that hosts this register.
+    const MODULE: Module;
+
+    /// Offset of this register within [`MODULE`](Self::MODULE).
+    fn offset(self) -> u8;
+
+    /// Full address of this register.
+    fn address(self) -> RegisterAddress {
+        RegisterAddress::new(Self::MODULE as u8, self.offset())
+    }
+
+    /// Address of channel `n` of a channelled register
+    /// (`offset + n`, e.g. encoder `n`'s position).
+    fn channel(self, n: u8) -> RegisterAddress {
+        RegisterAddress::new(Self::MODULE as u8, self.offset().wrapping_add(n))
+    }
+}
+
+impl<R: Register> From<R> for RegisterAddress {
+    fn from(register: R) -> Self {
+        register.address()
+    }
+}
+
+/// Declare a module's register enum and its [`Register`] impl.
+macro_rules! registers {
+    (
+        $(#[$meta:meta])*
+        $name:ident in $module:ident {
+            $($(#[$vmeta:meta])* $variant:ident = $value:expr,)*
+        }
+    ) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        #[cfg_attr(feature = "defmt", derive(defmt::Format))]
+        #[repr(u8)]
+        pub enum $name {
+            $($(#[$vmeta])* $variant = $value,)*
+        }
+
+        impl Register for $name {
+            const MODULE: Module = Module::$module;
+
+            fn offset(self) -> u8 {
+                self as u8
+            }
+        }
+    };
+}
+
+// ---------------------------------------------------------------------------
+// Register maps
+// ---------------------------------------------------------------------------
+
+registers! {
+    /// Status module registers.
+    StatusRegister in Status {
+        /// Chip hardware ID (8-bit, read-only).
+        HwId = 0x01,
+        /// Firmware version (32-bit, read-only): upper 16 bits are the
+        /// Adafruit product number, lower 16 bits a build date code.
+        Version = 0x02,
+        /// Bitmask of compiled-in modules (32-bit, read-only).
+        Options = 0x03,
+        /// Die temperature (32-bit, Q16.16 °C, read-only).
+        Temp = 0x04,
+        /// Write `0xFF` to reset the chip.
+        SwRst = 0x7F,
+    }
+}
+
+registers! {
+    /// GPIO module registers. All are 32-bit pin bitmasks
+    /// (bit N = Seesaw pin N).
+    GpioRegister in Gpio {
+        /// Configure the masked pins as outputs.
+        DirSetBulk = 0x02,
+        /// Configure the masked pins as inputs.
+        DirClrBulk = 0x03,
+        /// Read the level of all pins (1 = HIGH).
+        Bulk = 0x04,
+        /// Drive the masked pins HIGH (selects pull-up when pulls are on).
+        BulkSet = 0x05,
+        /// Drive the masked pins LOW (selects pull-down when pulls are on).
+        BulkClr = 0x06,
+        /// Toggle the masked pins.
+        BulkToggle = 0x07,
+        /// Enable pin-change interrupts on the masked pins.
+        IntEnSet = 0x08,
+        /// Disable pin-change interrupts on the masked pins.
+        IntEnClr = 0x09,
+        /// Pending interrupt flags; reading clears them and releases INT.
+        IntFlag = 0x0A,
+        /// Enable the pull resistor on the masked pins.
+        PullEnSet = 0x0B,
+        /// Disable the pull resistor on the masked pins.
+        PullEnClr = 0x0C,
+    }
+}
+
+registers! {
+    /// ADC module registers.
+    AdcRegister in Adc {
+        /// Module status (8-bit).
+        Status = 0x00,
+        /// Enable the window interrupt.
+        IntEnSet = 0x02,
+        /// Disable the window interrupt.
+        IntEnClr = 0x03,
+        /// Window comparator mode.
+        WinMode = 0x04,
+        /// Window thresholds (32-bit: upper 16 bits high, lower 16 bits low).
+        WinThresh = 0x05,
+        /// First channel's 16-bit reading; use [`Register::channel`].
+        Channel = 0x07,
+    }
+}
+
+registers! {
+    /// NeoPixel module registers.
+    NeopixelRegister in Neopixel {
+        /// Module status (8-bit).
+        Status = 0x00,
+        /// Output pin (8-bit).
+        Pin = 0x01,
+        /// Data rate: 0 = 400 kHz, 1 = 800 kHz (8-bit).
+        Speed = 0x02,
+        /// Pixel buffer length in bytes (16-bit).
+        BufLength = 0x03,
+        /// Pixel buffer: a 16-bit byte offset followed by pixel data.
+        Buf = 0x04,
+        /// Write (no data) to latch the buffer out to the pixels.
+        Show = 0x05,
+    }
+}
+
+registers! {
+    /// Capacitive touch module registers.
+    TouchRegister in Touch {
+        /// First pad's 16-bit reading; use [`Register::channel`].
+        Channel = 0x10,
+    }
+}
+
+registers! {
+    /// Encoder module registers. All are per encoder; use
+    /// [`Register::channel`] with the encoder index.
+    EncoderRegister in Encoder {
+        /// Encoder status.
+        Status = 0x00,
+        /// Write 1 to enable the encoder's interrupt.
+        IntSet = 0x10,
+        /// Write 1 to disable the encoder's interrupt.
+        IntClr = 0x20,
+        /// Absolute position (32-bit signed, read/write).
+        Position = 0x30,
+        /// Movement since the previous read (32-bit signed); reading
+        /// resets it.
+        Delta = 0x40,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn register_addresses() {
+        assert_eq!(StatusRegister::Version.address().to_bytes(), [0x00, 0x02]);
+        assert_eq!(RegisterAddress::from(GpioRegister::IntFlag).to_bytes(), [0x01, 0x0A]);
+        assert_eq!(NeopixelRegister::Show.address(), RegisterAddress::new(0x0E, 0x05));
+    }
+
+    #[test]
+    fn channelled_registers_add_the_channel() {
+        assert_eq!(EncoderRegister::Position.channel(3).to_bytes(), [0x11, 0x33]);
+        assert_eq!(EncoderRegister::Delta.channel(0).to_bytes(), [0x11, 0x40]);
+        assert_eq!(AdcRegister::Channel.channel(2).to_bytes(), [0x09, 0x09]);
+    }
+}
@@ -0,0 +1,70 @@
+//! Typed register values.
+//!
+//! Seesaw transfers every multi-byte value big-endian. [`RegisterValue`]
+//! captures the width and byte order of each integer type so
+//! [`Seesaw::read`](crate::Seesaw::read) and
+//! [`Seesaw::write`](crate::Seesaw::write) can be generic over it.
+
+/// An integer type that can be stored in a Seesaw register.
+///
+/// Implemented for `u8`, `i8`, `u16`, `i16`, `u32` and `i32`.
+pub trait RegisterValue: Copy {
+    /// Width of the value on the wire, in bytes.
+    const SIZE: usize;
+
+    /// Decode from the first [`SIZE`](Self::SIZE) bytes of `bytes`
+    /// (big-endian).
+    fn from_be_slice(bytes: &[u8]) -> Self;
+
+    /// Encode into the first [`SIZE`](Self::SIZE) bytes of `out`
+    /// (big-endian).
+    fn write_be_slice(self, out: &mut [u8]);
+}
+
+macro_rules! impl_register_value {
+    ($($t:ty),*) => {$(
+        impl RegisterValue for $t {
+            const SIZE: usize = core::mem::size_of::<$t>();
+
+            fn from_be_slice(bytes: &[u8]) -> Self {
+                let mut buf = [0u8; core::mem::size_of::<$t>()];
+                buf.copy_from_slice(&bytes[..Self::SIZE]);
+                <$t>::from_be_bytes(buf)
+            }
+
+            fn write_be_slice(self, out: &mut [u8]) {
+                out[..Self::SIZE].copy_from_slice(&self.to_be_bytes());
+            }
+        }
+    )*};
+}
+
+impl_register_value!(u8, i8, u16, i16, u32, i32);
+
+/// Largest [`RegisterValue::SIZE`] of any implementation.
+pub(crate) const MAX_VALUE_SIZE: usize = 4;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sizes_match_types() {
+        assert_eq!(u8::SIZE, 1);
+        assert_eq!(i16::SIZE, 2);
+        assert_eq!(u32::SIZE, 4);
+        const { assert!(i32::SIZE <= MAX_VALUE_SIZE) };
+    }
+
+    #[test]
+    fn big_endian_round_trip() {
+        let mut buf = [0u8; 4];
+        (-2i32).write_be_slice(&mut buf);
+        assert_eq!(buf, [0xFF, 0xFF, 0xFF, 0xFE]);
+        assert_eq!(i32::from_be_slice(&buf), -2);
+
+        0x1678u16.write_be_slice(&mut buf);
+        assert_eq!(&buf[..2], &[0x16, 0x78]);
+        assert_eq!(u16::from_be_slice(&buf), 5752);
+    }
+}